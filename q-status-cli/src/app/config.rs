@@ -1,9 +1,16 @@
 // ABOUTME: Application configuration with defaults and file loading
 // Supports TOML configuration files and environment variables
 
+use super::state::UiState;
+use crate::data::database::{CompactionThresholds, Q_CONTEXT_WINDOW};
+use crate::utils::cost_calculator::CostMode;
+use crate::utils::date_range::DateRange;
+use crate::utils::timezone::DisplayZone;
+use chrono::{DateTime, Local};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -11,6 +18,9 @@ pub struct AppConfig {
     pub token_limit: u64,
     pub warning_threshold: f64,
     pub critical_threshold: f64,
+    /// Context usage (%) at which compaction is imminent
+    #[serde(default = "default_imminent_threshold")]
+    pub imminent_threshold: f64,
     pub cost_per_1k_tokens: f64,
     pub history_retention_hours: u64,
     pub export_format: ExportFormat,
@@ -21,16 +31,184 @@ pub struct AppConfig {
     pub cost_mode: String,
     #[serde(default)]
     pub claude_config_paths: Vec<String>,
+    /// Amazon Q databases to try before the built-in locations
+    #[serde(default)]
+    pub amazon_q_db_paths: Vec<String>,
+    /// Per-block token limit; 0 means detect it from historical usage
     #[serde(default = "default_claude_token_limit")]
     pub claude_token_limit: usize,
     #[serde(default = "default_claude_warning_threshold")]
     pub claude_warning_threshold: f64,
+    #[serde(default = "default_claude_critical_threshold")]
+    pub claude_critical_threshold: f64,
+    #[serde(default)]
+    pub use_approximate_tokens: bool,
+    /// Leave Claude cache-read tokens out of the context gauge (costs still include them)
+    #[serde(default)]
+    pub exclude_cache_read_from_total: bool,
+    /// Also read rotated `.jsonl.gz` Claude archives
+    #[serde(default = "default_read_compressed")]
+    pub read_compressed: bool,
+    /// Only scan these Claude project directories (encoded name or decoded
+    /// path); empty scans every project
+    #[serde(default)]
+    pub claude_projects_allowlist: Vec<String>,
+    /// Deepest directory level below `projects/` searched for Claude files;
+    /// unset searches the whole tree
+    #[serde(default)]
+    pub claude_max_glob_depth: Option<usize>,
+    #[serde(default)]
+    pub pricing_url: Option<String>,
+    #[serde(default)]
+    pub notifications_enabled: bool,
+    #[serde(default)]
+    pub amazon_q_plan: AmazonQPlan,
+    /// Overrides the plan's default monthly message quota
+    #[serde(default)]
+    pub message_quota_limit: Option<usize>,
+    /// Sessions with activity within this many days count as active
+    #[serde(default = "default_active_session_days")]
+    pub active_session_days: i64,
+    /// Number of burn-rate snapshots kept for the sparkline
+    #[serde(default = "default_burn_rate_history_len")]
+    pub burn_rate_history_len: usize,
+    /// Reset the burn rate when no new data arrived for this many minutes (0 = never)
+    #[serde(default = "default_burn_rate_reset_minutes")]
+    pub burn_rate_reset_minutes: u64,
+    /// Only load Claude entries whose model contains one of these substrings
+    #[serde(default)]
+    pub model_filters: Vec<String>,
+    /// Leave Claude API-error turns out of token, message and cost totals
+    #[serde(default = "default_exclude_api_errors")]
+    pub exclude_api_errors: bool,
+    /// Most recent Claude sessions kept in memory; older ones are rolled up
+    /// into the totals only
+    #[serde(default)]
+    pub max_sessions_retained: Option<usize>,
+    /// Claude sessions idle longer than this many days are rolled up into the
+    /// totals only
+    #[serde(default)]
+    pub max_history_days: Option<i64>,
+    /// Show idle gaps between billing blocks in the timeline view; other
+    /// views never draw them
+    #[serde(default = "default_show_gap_blocks")]
+    pub show_gap_blocks: bool,
+    /// Top conversations printed by the non-interactive status output (0 = all);
+    /// the dashboard's list is sized to its panel instead
+    #[serde(default = "default_top_conversations")]
+    pub top_conversations: usize,
+    /// IANA zone (e.g. "Europe/Berlin") for day buckets and displayed times;
+    /// unset uses the system zone
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Rows shown per page in the session list; unset fits the terminal height
+    #[serde(default)]
+    pub session_page_size: Option<usize>,
+    /// Group Claude sessions by working directory or by git repository
+    #[serde(default)]
+    pub group_by: DirGroupMode,
+    /// Measure Amazon Q usage against the 175K limit where compaction kicks in;
+    /// turn off to show usage against `amazon_q_actual_limit` instead
+    #[serde(default = "default_amazon_q_use_effective_limit")]
+    pub amazon_q_use_effective_limit: bool,
+    /// Amazon Q's full context window
+    #[serde(default = "default_amazon_q_actual_limit")]
+    pub amazon_q_actual_limit: u64,
+    /// Price Amazon Q conversations at a per-1k rate derived from their own
+    /// input/output mix instead of the flat `cost_per_1k_tokens`
+    #[serde(default)]
+    pub amazon_q_auto_blended_rate: bool,
+    /// Monthly spend limit shown as a burn-down gauge in the metrics widget
+    #[serde(default)]
+    pub monthly_budget_usd: Option<f64>,
+    /// Directory the dashboard's export key writes to; unset uses the working directory
+    #[serde(default)]
+    pub export_dir: Option<PathBuf>,
+    /// Spend caps in USD, keyed by a substring of the directory they apply to
+    #[serde(default)]
+    pub directory_caps: BTreeMap<String, f64>,
+    /// Flag a session whose cost grows this many times over between refreshes
+    #[serde(default = "default_spike_factor")]
+    pub spike_factor: f64,
+    /// Also flag a session whose cost grows by at least this much (USD) between refreshes
+    #[serde(default)]
+    pub spike_threshold_usd: Option<f64>,
+    /// Longest poll interval (seconds) the collector backs off to while data is unchanged
+    #[serde(default = "default_max_idle_interval")]
+    pub max_idle_interval: u64,
+    /// Seconds without new data before the header flags it as stale (0 = never)
+    #[serde(default = "default_stale_after_secs")]
+    pub stale_after_secs: u64,
+    /// ISO 4217 code costs are displayed in; calculations stay in USD
+    #[serde(default = "default_display_currency")]
+    pub display_currency: String,
+    /// Units of `display_currency` per US dollar
+    #[serde(default = "default_usd_exchange_rate")]
+    pub usd_exchange_rate: f64,
+    /// Decimal places for displayed costs; unset shows 2, or 4 below one cent
+    #[serde(default)]
+    pub cost_decimals: Option<usize>,
+    /// Serve Prometheus metrics on this localhost port
+    #[serde(default)]
+    pub metrics_port: Option<u16>,
+    /// View mode, sort order and filters from the last run
+    #[serde(default)]
+    pub ui_state: UiState,
+    /// Conversation shown in the current view instead of the latest one
+    #[serde(default)]
+    pub pinned_conversation_id: Option<String>,
+    // Everything below is set per run from CLI flags or at runtime; these
+    // fields are `#[serde(skip)]`, so they are never read from or written to
+    // the config file and don't appear in --dump-config
     #[serde(skip)]
     pub config_path: Option<PathBuf>,
     #[serde(skip)]
     pub debug: bool,
+    /// Write tracing output to this daily-rolled file instead of stderr (--log-file)
+    #[serde(skip)]
+    pub log_file: Option<PathBuf>,
+    /// Show collector warnings in the log pane, not just errors (--verbose)
+    #[serde(skip)]
+    pub verbose: bool,
     #[serde(skip)]
     pub active_data_source: Option<crate::data::DataSourceType>,
+    #[serde(skip)]
+    pub since: Option<DateTime<Local>>,
+    #[serde(skip)]
+    pub until: Option<DateTime<Local>>,
+    #[serde(skip)]
+    pub daily_csv_path: Option<PathBuf>,
+    #[serde(skip)]
+    pub stream: bool,
+    /// Print one status line from this template and exit (--oneline / --format)
+    #[serde(skip)]
+    pub oneline_format: Option<String>,
+    /// Print the N most expensive sessions and exit (--top-cost)
+    #[serde(skip)]
+    pub top_cost: Option<usize>,
+    /// Monochrome, ASCII-only output (--no-color or NO_COLOR)
+    #[serde(skip)]
+    pub no_color: bool,
+    #[serde(skip)]
+    pub export_path: Option<PathBuf>,
+    /// Replace paths and conversation ids with hashed labels in exports (--anonymize)
+    #[serde(skip)]
+    pub anonymize: bool,
+    /// Print the effective configuration and exit (--dump-config)
+    #[serde(skip)]
+    pub dump_config: bool,
+    /// Check data sources, pricing and config, then exit (--doctor)
+    #[serde(skip)]
+    pub doctor: bool,
+    /// Load the chosen source once, print what was parsed, then exit (--check)
+    #[serde(skip)]
+    pub check: bool,
+    /// Top-level keys set in the config file
+    #[serde(skip)]
+    pub file_keys: HashSet<String>,
+    /// Top-level keys overridden by an environment variable or CLI flag, and by which
+    #[serde(skip)]
+    pub overrides: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +217,59 @@ pub enum ExportFormat {
     Json,
     Csv,
     Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// Infer the format from a file extension
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// File extension written for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmazonQPlan {
+    #[default]
+    Free,
+    Pro,
+}
+
+impl AmazonQPlan {
+    /// Default monthly message quota for the plan
+    pub fn default_message_quota(&self) -> usize {
+        match self {
+            Self::Free => 5_000,
+            Self::Pro => 10_000,
+        }
+    }
+}
+
+/// What Claude sessions are grouped on in the directory view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DirGroupMode {
+    /// The session's working directory as recorded
+    #[default]
+    Cwd,
+    /// The nearest enclosing git repository, falling back to the working directory
+    GitRoot,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +280,10 @@ pub enum Theme {
     Auto,
 }
 
+fn default_imminent_threshold() -> f64 {
+    95.0
+}
+
 fn default_data_source() -> String {
     "amazon-q".to_string()
 }
@@ -65,6 +300,76 @@ fn default_claude_warning_threshold() -> f64 {
     0.8
 }
 
+fn default_claude_critical_threshold() -> f64 {
+    0.95
+}
+
+/// Parse a token limit, accepting "auto" for detection from usage (stored as 0)
+pub fn parse_token_limit(value: &str) -> Result<usize, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(0);
+    }
+    value
+        .parse()
+        .map_err(|_| format!("Invalid token limit '{}': expected a number or 'auto'", value))
+}
+
+fn default_active_session_days() -> i64 {
+    7
+}
+
+fn default_burn_rate_history_len() -> usize {
+    30
+}
+
+fn default_burn_rate_reset_minutes() -> u64 {
+    30
+}
+
+fn default_top_conversations() -> usize {
+    5
+}
+
+fn default_amazon_q_use_effective_limit() -> bool {
+    true
+}
+
+fn default_read_compressed() -> bool {
+    true
+}
+
+fn default_exclude_api_errors() -> bool {
+    true
+}
+
+fn default_show_gap_blocks() -> bool {
+    true
+}
+
+fn default_amazon_q_actual_limit() -> u64 {
+    200_000
+}
+
+fn default_spike_factor() -> f64 {
+    3.0
+}
+
+fn default_max_idle_interval() -> u64 {
+    30
+}
+
+fn default_stale_after_secs() -> u64 {
+    300
+}
+
+fn default_display_currency() -> String {
+    "USD".to_string()
+}
+
+fn default_usd_exchange_rate() -> f64 {
+    1.0
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -72,6 +377,7 @@ impl Default for AppConfig {
             token_limit: 44000,
             warning_threshold: 70.0,
             critical_threshold: 90.0,
+            imminent_threshold: default_imminent_threshold(),
             cost_per_1k_tokens: 0.0066,  // Claude 3.5 Sonnet (Oct 2024) blended rate ~30% output
             history_retention_hours: 24,
             export_format: ExportFormat::Csv,
@@ -79,72 +385,354 @@ impl Default for AppConfig {
             data_source: default_data_source(),
             cost_mode: default_cost_mode(),
             claude_config_paths: vec![],
+            amazon_q_db_paths: vec![],
             claude_token_limit: default_claude_token_limit(),
             claude_warning_threshold: default_claude_warning_threshold(),
+            claude_critical_threshold: default_claude_critical_threshold(),
+            use_approximate_tokens: false,
+            exclude_cache_read_from_total: false,
+            read_compressed: default_read_compressed(),
+            claude_projects_allowlist: vec![],
+            claude_max_glob_depth: None,
+            pricing_url: None,
+            notifications_enabled: false,
+            amazon_q_plan: AmazonQPlan::default(),
+            message_quota_limit: None,
+            active_session_days: default_active_session_days(),
+            burn_rate_history_len: default_burn_rate_history_len(),
+            burn_rate_reset_minutes: default_burn_rate_reset_minutes(),
+            model_filters: vec![],
+            exclude_api_errors: default_exclude_api_errors(),
+            max_sessions_retained: None,
+            max_history_days: None,
+            show_gap_blocks: default_show_gap_blocks(),
+            top_conversations: default_top_conversations(),
+            timezone: None,
+            session_page_size: None,
+            group_by: DirGroupMode::default(),
+            amazon_q_use_effective_limit: default_amazon_q_use_effective_limit(),
+            amazon_q_actual_limit: default_amazon_q_actual_limit(),
+            amazon_q_auto_blended_rate: false,
+            monthly_budget_usd: None,
+            export_dir: None,
+            directory_caps: BTreeMap::new(),
+            spike_factor: default_spike_factor(),
+            spike_threshold_usd: None,
+            max_idle_interval: default_max_idle_interval(),
+            stale_after_secs: default_stale_after_secs(),
+            display_currency: default_display_currency(),
+            usd_exchange_rate: default_usd_exchange_rate(),
+            cost_decimals: None,
+            metrics_port: None,
+            ui_state: UiState::default(),
+            pinned_conversation_id: None,
             config_path: None,
             debug: false,
+            log_file: None,
+            verbose: false,
             active_data_source: None,
+            since: None,
+            until: None,
+            daily_csv_path: None,
+            stream: false,
+            oneline_format: None,
+            top_cost: None,
+            no_color: false,
+            export_path: None,
+            anonymize: false,
+            dump_config: false,
+            doctor: false,
+            check: false,
+            file_keys: HashSet::new(),
+            overrides: HashMap::new(),
         }
     }
 }
 
 impl AppConfig {
     pub fn load() -> Self {
-        let mut config = Self::default();
-
-        // Try to load from default location
-        if let Some(proj_dirs) = ProjectDirs::from("com", "q-status", "q-status") {
-            let config_path = proj_dirs.config_dir().join("config.toml");
-            if config_path.exists() {
-                if let Ok(contents) = std::fs::read_to_string(&config_path) {
-                    if let Ok(file_config) = toml::from_str::<Self>(&contents) {
-                        config = file_config;
-                        config.config_path = Some(config_path);
-                    }
-                }
-            }
-        }
+        Self::load_from(None)
+    }
+
+    /// Load from `path` (--config), or the default location, then apply env overrides
+    pub fn load_from(path: Option<&Path>) -> Self {
+        let mut config = match path {
+            Some(path) => Self::load_file(path).unwrap_or_else(|| Self {
+                config_path: Some(path.to_path_buf()),
+                ..Self::default()
+            }),
+            None => Self::default_config_path()
+                .and_then(|path| Self::load_file(&path))
+                .unwrap_or_default(),
+        };
 
         // Override with environment variables
         if let Ok(rate) = std::env::var("Q_STATUS_REFRESH_RATE") {
             if let Ok(parsed) = rate.parse() {
                 config.refresh_rate = parsed;
+                config.set_override("refresh_rate", "env Q_STATUS_REFRESH_RATE");
             }
         }
 
         // Check for data source environment variable
         if let Ok(source) = std::env::var("QSTATUS_DATA_SOURCE") {
             config.data_source = source;
+            config.set_override("data_source", "env QSTATUS_DATA_SOURCE");
+        }
+
+        // Check for an Amazon Q database location, tried before configured paths
+        if let Ok(path) = std::env::var("QSTATUS_AMAZONQ_DB") {
+            config.amazon_q_db_paths.insert(0, path);
+            config.set_override("amazon_q_db_paths", "env QSTATUS_AMAZONQ_DB");
         }
 
         // Check for cost mode environment variable
         if let Ok(mode) = std::env::var("QSTATUS_COST_MODE") {
             config.cost_mode = mode;
+            config.set_override("cost_mode", "env QSTATUS_COST_MODE");
+        }
+
+        // Check for remote pricing URL
+        if let Ok(url) = std::env::var("QSTATUS_PRICING_URL") {
+            config.pricing_url = Some(url);
+            config.set_override("pricing_url", "env QSTATUS_PRICING_URL");
+        }
+
+        // Check for desktop notifications toggle
+        if let Ok(enabled) = std::env::var("QSTATUS_NOTIFICATIONS") {
+            if let Ok(parsed) = enabled.parse() {
+                config.notifications_enabled = parsed;
+                config.set_override("notifications_enabled", "env QSTATUS_NOTIFICATIONS");
+            }
         }
 
         // Check for Claude token limit
         if let Ok(limit) = std::env::var("QSTATUS_CLAUDE_TOKEN_LIMIT") {
-            if let Ok(parsed) = limit.parse() {
+            if let Ok(parsed) = parse_token_limit(&limit) {
                 config.claude_token_limit = parsed;
+                config.set_override("claude_token_limit", "env QSTATUS_CLAUDE_TOKEN_LIMIT");
             }
         }
 
+        // Respect the NO_COLOR convention (any non-empty value)
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            config.no_color = true;
+        }
+
         // Check for Claude warning threshold
         if let Ok(threshold) = std::env::var("QSTATUS_CLAUDE_WARNING_THRESHOLD") {
             if let Ok(parsed) = threshold.parse() {
                 config.claude_warning_threshold = parsed;
+                config.set_override("claude_warning_threshold", "env QSTATUS_CLAUDE_WARNING_THRESHOLD");
+            }
+        }
+
+        // Check for Claude critical threshold
+        if let Ok(threshold) = std::env::var("QSTATUS_CLAUDE_CRITICAL_THRESHOLD") {
+            if let Ok(parsed) = threshold.parse() {
+                config.claude_critical_threshold = parsed;
+                config.set_override("claude_critical_threshold", "env QSTATUS_CLAUDE_CRITICAL_THRESHOLD");
             }
         }
 
         config
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(proj_dirs) = ProjectDirs::from("com", "q-status", "q-status") {
-            let config_dir = proj_dirs.config_dir();
-            std::fs::create_dir_all(config_dir)?;
+    /// The config file used when --config isn't given
+    fn default_config_path() -> Option<PathBuf> {
+        let proj_dirs = ProjectDirs::from("com", "q-status", "q-status")?;
+        Some(proj_dirs.config_dir().join("config.toml"))
+    }
+
+    /// Read a config file, without env overrides
+    fn load_file(config_path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(config_path).ok()?;
+        let mut config = toml::from_str::<Self>(&contents).ok()?;
+        config.config_path = Some(config_path.to_path_buf());
+        if let Ok(toml::Value::Table(table)) = contents.parse::<toml::Value>() {
+            config.file_keys = table.keys().cloned().collect();
+        }
+        Some(config)
+    }
+
+    /// Set one top-level key in the config file, keeping every other line,
+    /// comment and format as it is on disk
+    ///
+    /// Writes to the file the config was loaded from (or --config), and refuses
+    /// to touch a file that doesn't parse rather than replace it. `None` removes
+    /// the key.
+    fn update_file(&self, key: &str, value: Option<toml_edit::Item>) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = self.config_path.clone().or_else(Self::default_config_path) else {
+            return Ok(());
+        };
+        let mut document = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents.parse::<toml_edit::DocumentMut>().map_err(|e| {
+                format!("not updating {} because it doesn't parse: {}", path.display(), e)
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml_edit::DocumentMut::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        match value {
+            Some(value) => document[key] = value,
+            None => {
+                document.remove(key);
+            }
+        }
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, document.to_string())?;
+        Ok(())
+    }
+
+    /// Write the pinned conversation to the config file
+    pub fn save_pinned_conversation(&self, id: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        self.update_file("pinned_conversation_id", id.map(toml_edit::value))
+    }
+
+    /// Write UI preferences to the config file
+    pub fn save_ui_state(&self, ui_state: &UiState) -> Result<(), Box<dyn std::error::Error>> {
+        let table = toml_edit::ser::to_document(ui_state)?.into_table();
+        self.update_file("ui_state", Some(toml_edit::Item::Table(table)))
+    }
+
+    /// Note that a setting came from an environment variable or CLI flag
+    pub fn set_override(&mut self, key: &str, origin: &str) {
+        self.overrides.insert(key.to_string(), origin.to_string());
+    }
+
+    /// Where a top-level setting's value came from
+    fn value_source(&self, key: &str) -> &str {
+        if let Some(origin) = self.overrides.get(key) {
+            origin
+        } else if self.file_keys.contains(key) {
+            "config file"
+        } else {
+            "default"
+        }
+    }
+
+    /// The effective configuration as TOML, each top-level setting annotated
+    /// with where its value came from
+    pub fn dump(&self) -> Result<String, toml::ser::Error> {
+        let contents = toml::to_string_pretty(self)?;
+        let mut out = String::from(
+            "# Effective q-status configuration (defaults < config file < env < CLI)\n\
+             # Per-run flags such as --since, --until and --export are not included\n\n",
+        );
+
+        let mut in_table = false;
+        for line in contents.lines() {
+            let key = if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_table = true;
+                Some(table)
+            } else if in_table {
+                None
+            } else {
+                line.split_once(" = ").map(|(key, _)| key.trim())
+            };
+
+            match key {
+                Some(key) => out.push_str(&format!("{}  # {}\n", line, self.value_source(key))),
+                None => {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Monthly message quota: the explicit limit, or the plan's default
+    pub fn effective_message_quota_limit(&self) -> usize {
+        self.message_quota_limit
+            .unwrap_or_else(|| self.amazon_q_plan.default_message_quota())
+    }
+
+    /// Zone used for day buckets and displayed times, the system zone when
+    /// unset or unrecognized
+    ///
+    /// Called every frame, so it falls back silently; `timezone_warning` is
+    /// checked once at startup instead.
+    pub fn timezone(&self) -> DisplayZone {
+        self.timezone
+            .as_deref()
+            .and_then(|name| DisplayZone::parse(name).ok())
+            .unwrap_or(DisplayZone::Local)
+    }
+
+    /// Why the configured time zone is being ignored, if it is
+    pub fn timezone_warning(&self) -> Option<String> {
+        let error = DisplayZone::parse(self.timezone.as_deref()?).err()?;
+        Some(format!("{}, using the system time zone", error))
+    }
+
+    /// Amazon Q context window usage is measured against
+    pub fn amazon_q_context_window(&self) -> u64 {
+        if self.amazon_q_use_effective_limit {
+            Q_CONTEXT_WINDOW
+        } else {
+            self.amazon_q_actual_limit
+        }
+    }
+
+    /// Spend cap for a directory, from the most specific matching `directory_caps` key
+    pub fn directory_cap(&self, directory: &str) -> Option<f64> {
+        crate::utils::budget::directory_cap(&self.directory_caps, directory)
+    }
+
+    /// A USD cost converted and formatted in the display currency
+    pub fn format_cost(&self, cost_usd: f64) -> String {
+        crate::utils::cost_calculator::format_cost_in(
+            cost_usd,
+            &self.display_currency,
+            self.usd_exchange_rate,
+            self.cost_decimals,
+        )
+    }
 
-            let config_path = config_dir.join("config.toml");
+    /// Whether output may use colors and emoji
+    pub fn color_enabled(&self) -> bool {
+        !self.no_color
+    }
+
+    /// Context-usage percentages used to classify compaction status
+    pub fn compaction_thresholds(&self) -> CompactionThresholds {
+        CompactionThresholds {
+            warning: self.warning_threshold,
+            critical: self.critical_threshold,
+            imminent: self.imminent_threshold,
+        }
+    }
+
+    /// Parsed cost mode, falling back to auto for unrecognized values
+    pub fn effective_cost_mode(&self) -> CostMode {
+        CostMode::from_str(&self.cost_mode).unwrap_or_default()
+    }
+
+    /// The --since / --until window applied to all aggregations
+    pub fn date_range(&self) -> DateRange {
+        DateRange::new(self.since, self.until)
+    }
+
+    /// File dashboard exports append their anonymized labels to, beside the
+    /// config file so the mapping never sits in `export_dir` next to the reports
+    pub fn anonymize_mapping_path(&self) -> Option<PathBuf> {
+        self.config_path
+            .clone()
+            .or_else(Self::default_config_path)
+            .map(|path| path.with_file_name("anonymized-labels.txt"))
+    }
+
+    /// Write the whole config to the file it was loaded from, or the default location
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(config_path) = self.config_path.clone().or_else(Self::default_config_path) {
+            if let Some(dir) = config_path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
             let contents = toml::to_string_pretty(self)?;
             std::fs::write(config_path, contents)?;
         }
@@ -152,3 +740,106 @@ impl AppConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_annotates_value_sources() {
+        let mut config = AppConfig {
+            refresh_rate: 5,
+            monthly_budget_usd: Some(50.0),
+            ..AppConfig::default()
+        };
+        config.file_keys = ["refresh_rate", "monthly_budget_usd"].into_iter().map(String::from).collect();
+        config.cost_mode = "calculate".to_string();
+        config.set_override("cost_mode", "--cost-mode");
+        config.debug = true;
+
+        let dump = config.dump().unwrap();
+        assert!(dump.contains("refresh_rate = 5  # config file"));
+        assert!(dump.contains("monthly_budget_usd = 50.0  # config file"));
+        assert!(dump.contains("cost_mode = \"calculate\"  # --cost-mode"));
+        assert!(dump.contains("data_source = \"amazon-q\"  # default"));
+        assert!(!dump.contains("debug"));
+
+        // Comments don't stop the dump from being read back
+        let reparsed: AppConfig = toml::from_str(&dump).unwrap();
+        assert_eq!(reparsed.refresh_rate, 5);
+        assert_eq!(reparsed.cost_mode, "calculate");
+    }
+
+    #[test]
+    fn test_ui_state_update_keeps_rest_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.toml");
+        let settings = toml::to_string(&AppConfig { refresh_rate: 5, ..AppConfig::default() }).unwrap();
+        std::fs::write(&path, format!("# tuned by hand\n{}", settings)).unwrap();
+
+        let config = AppConfig::load_from(Some(&path));
+        assert_eq!(config.config_path.as_deref(), Some(path.as_path()));
+        assert_eq!(config.refresh_rate, 5);
+
+        let ui_state = UiState { show_active_only: false, ..config.ui_state.clone() };
+        config.save_ui_state(&ui_state).unwrap();
+        config.save_pinned_conversation(Some("conv-1")).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("# tuned by hand\n"));
+        assert!(written.contains("pinned_conversation_id = \"conv-1\""));
+        let reloaded = AppConfig::load_from(Some(&path));
+        assert_eq!(reloaded.ui_state, ui_state);
+        assert_eq!(reloaded.pinned_conversation_id.as_deref(), Some("conv-1"));
+
+        config.save_pinned_conversation(None).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("pinned_conversation_id"));
+    }
+
+    #[test]
+    fn test_unparseable_config_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let broken = "refresh_rate = [unclosed\n";
+        std::fs::write(&path, broken).unwrap();
+
+        let config = AppConfig::load_from(Some(&path));
+        assert!(config.save_ui_state(&UiState::default()).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), broken);
+    }
+
+    #[test]
+    fn test_format_cost_uses_configured_decimals() {
+        let mut config = AppConfig {
+            display_currency: "EUR".to_string(),
+            usd_exchange_rate: 0.5,
+            ..AppConfig::default()
+        };
+        assert_eq!(config.format_cost(3.0), "€1.50");
+        assert_eq!(config.format_cost(0.01), "€0.0050");
+
+        config.cost_decimals = Some(3);
+        assert_eq!(config.format_cost(3.0), "€1.500");
+        assert_eq!(config.format_cost(0.01), "€0.005");
+
+        let parsed: AppConfig = toml::from_str(&config.dump().unwrap()).unwrap();
+        assert_eq!(parsed.cost_decimals, Some(3));
+    }
+
+    #[test]
+    fn test_unknown_timezone_falls_back_with_one_warning() {
+        let config = AppConfig {
+            timezone: Some("Mars/Olympus_Mons".to_string()),
+            ..AppConfig::default()
+        };
+        assert!(matches!(config.timezone(), DisplayZone::Local));
+        assert!(config.timezone_warning().unwrap().contains("Unknown time zone 'Mars/Olympus_Mons'"));
+
+        let valid = AppConfig {
+            timezone: Some("Europe/Berlin".to_string()),
+            ..AppConfig::default()
+        };
+        assert!(valid.timezone_warning().is_none());
+        assert!(AppConfig::default().timezone_warning().is_none());
+    }
+}
@@ -0,0 +1,200 @@
+// ABOUTME: Self-check for --doctor: data sources, pricing and config file
+// Reports PASS/FAIL per check so setup problems can be diagnosed without the TUI
+
+use super::config::AppConfig;
+use crate::data::datasource::DataSource;
+use crate::data::{DataSourceFactory, DataSourceType};
+use crate::utils::cost_calculator::CostCalculator;
+use crate::utils::error::Result;
+use std::path::PathBuf;
+
+/// Outcome of one diagnostic check
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self { name: name.into(), passed: true, detail: detail.into() }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self { name: name.into(), passed: false, detail: detail.into() }
+    }
+}
+
+/// Every check, plus whether the configured data source can be used
+#[derive(Debug, Clone)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+    pub preferred_available: bool,
+}
+
+impl DoctorReport {
+    /// One `PASS`/`FAIL` line per check
+    pub fn render(&self) -> String {
+        let width = self.checks.iter().map(|check| check.name.len()).max().unwrap_or(0);
+        self.checks
+            .iter()
+            .map(|check| {
+                format!(
+                    "{}  {:<width$}  {}\n",
+                    if check.passed { "PASS" } else { "FAIL" },
+                    check.name,
+                    check.detail,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Detection, open and row-count checks for one data source
+///
+/// Returns the checks and whether the source opened.
+pub async fn source_checks(
+    name: &str,
+    located: Result<Vec<PathBuf>>,
+    opened: Result<Box<dyn DataSource>>,
+    cost_per_1k: f64,
+) -> (Vec<Check>, bool) {
+    let mut checks = vec![match located {
+        Ok(paths) => Check::pass(
+            format!("{} detected", name),
+            paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", "),
+        ),
+        Err(e) => Check::fail(format!("{} detected", name), e.to_string()),
+    }];
+
+    let source = match opened {
+        Ok(source) => source,
+        Err(e) => {
+            checks.push(Check::fail(format!("{} opens", name), e.to_string()));
+            return (checks, false);
+        }
+    };
+    checks.push(Check::pass(format!("{} opens", name), "ok"));
+
+    let conversations = source.get_all_conversation_summaries(cost_per_1k).await;
+    let sessions = source.get_all_sessions(cost_per_1k).await;
+    checks.push(match (conversations, sessions) {
+        (Ok(conversations), Ok(sessions)) => Check::pass(
+            format!("{} data", name),
+            format!("{} conversations, {} sessions", conversations.len(), sessions.len()),
+        ),
+        (Err(e), _) | (_, Err(e)) => Check::fail(format!("{} data", name), e.to_string()),
+    });
+
+    (checks, true)
+}
+
+/// Pricing the Claude Code source will use
+fn pricing_check(config: &AppConfig) -> Check {
+    let cache = CostCalculator::pricing_cache_path().filter(|path| path.exists());
+    match (&config.pricing_url, cache) {
+        (Some(_), Some(path)) => Check::pass("Pricing", format!("cached remote pricing at {}", path.display())),
+        (Some(url), None) => Check::fail("Pricing", format!("no cached pricing from {}, using built-in prices", url)),
+        (None, _) => Check::pass(
+            "Pricing",
+            format!("built-in prices for {} models", CostCalculator::new().available_models().len()),
+        ),
+    }
+}
+
+/// Where settings were read from
+fn config_check(config: &AppConfig) -> Check {
+    match &config.config_path {
+        Some(path) => Check::pass("Config file", path.display().to_string()),
+        None => Check::pass("Config file", "none found, using defaults"),
+    }
+}
+
+/// Run every check against the real data sources
+pub async fn run_doctor(config: &AppConfig) -> DoctorReport {
+    let preferred = DataSourceType::from_str(&config.data_source).unwrap_or(DataSourceType::AmazonQ);
+
+    let mut checks = Vec::new();
+    let mut opened = Vec::new();
+    for source in [DataSourceType::AmazonQ, DataSourceType::ClaudeCode] {
+        let (source_checks, ok) = source_checks(
+            source.display_name(),
+            DataSourceFactory::locate(source, config),
+            DataSourceFactory::create(source, config),
+            config.cost_per_1k_tokens,
+        )
+        .await;
+        checks.extend(source_checks);
+        if ok {
+            opened.push(source);
+        }
+    }
+    checks.push(pricing_check(config));
+    checks.push(config_check(config));
+
+    let preferred_available = match preferred {
+        DataSourceType::Merged => !opened.is_empty(),
+        source => opened.contains(&source),
+    };
+    checks.push(if preferred_available {
+        Check::pass("Preferred source", preferred.display_name())
+    } else {
+        Check::fail("Preferred source", format!("{} is unavailable", preferred.display_name()))
+    });
+
+    DoctorReport { checks, preferred_available }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::database::QDatabase;
+    use crate::utils::error::QStatusError;
+    use rusqlite::Connection;
+
+    fn q_fixture() -> QDatabase {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("
+            CREATE TABLE conversations (key TEXT PRIMARY KEY, value TEXT);
+            INSERT INTO conversations VALUES ('/work/api', '{\"conversation_id\": \"conv-api\", \"history\": []}');
+        ").unwrap();
+        QDatabase::from_connection(conn).with_approximate_tokens(true)
+    }
+
+    #[tokio::test]
+    async fn test_present_source_passes_every_check() {
+        let (checks, ok) = source_checks(
+            "Amazon Q",
+            Ok(vec![PathBuf::from("/data/amazon-q/data.sqlite3")]),
+            Ok(Box::new(q_fixture())),
+            0.0066,
+        )
+        .await;
+
+        assert!(ok);
+        assert!(checks.iter().all(|check| check.passed));
+        assert_eq!(checks[0].detail, "/data/amazon-q/data.sqlite3");
+        assert_eq!(checks[2].detail, "1 conversations, 1 sessions");
+    }
+
+    #[tokio::test]
+    async fn test_absent_source_fails_and_stops() {
+        let (checks, ok) = source_checks(
+            "Amazon Q",
+            Err(QStatusError::DatabaseNotFound),
+            Err(QStatusError::DatabaseNotFound),
+            0.0066,
+        )
+        .await;
+
+        assert!(!ok);
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|check| !check.passed));
+
+        let report = DoctorReport { checks, preferred_available: false };
+        let rendered = report.render();
+        assert!(rendered.starts_with("FAIL  Amazon Q detected  "));
+        assert!(rendered.lines().all(|line| line.starts_with("FAIL")));
+    }
+}
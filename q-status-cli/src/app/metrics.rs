@@ -0,0 +1,131 @@
+// ABOUTME: Prometheus-compatible /metrics endpoint for scraping q-status
+// Serves AppState values in the text exposition format over plain HTTP
+
+use super::state::AppState;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Pause after a failed accept, such as running out of file descriptors
+const ACCEPT_RETRY_MIN: Duration = Duration::from_millis(10);
+
+/// Longest pause between accepts while they keep failing
+const ACCEPT_RETRY_MAX: Duration = Duration::from_secs(1);
+
+/// Render current state in the Prometheus text exposition format
+///
+/// Exposed metrics, each labeled with `source` (the active data source):
+/// - `qstatus_total_tokens`: tokens across all conversations
+/// - `qstatus_total_cost_usd`: estimated cost across all conversations
+/// - `qstatus_session_percentage`: context usage of the current conversation
+/// - `qstatus_message_quota_used`: messages counted against the monthly quota
+pub fn render_metrics(state: &AppState) -> String {
+    let source = state.get_active_data_source().as_str().to_string();
+    let (total_tokens, total_cost, quota_used) = match *state.global_stats.lock().unwrap() {
+        Some(ref stats) => (
+            stats.total_tokens as f64,
+            stats.total_cost_estimate,
+            stats.message_quota_used as f64,
+        ),
+        None => (0.0, 0.0, 0.0),
+    };
+    let session_percentage = state.token_usage.lock().unwrap().percentage;
+
+    let metrics = [
+        ("qstatus_total_tokens", "Total tokens across all conversations", total_tokens),
+        ("qstatus_total_cost_usd", "Estimated total cost in USD", total_cost),
+        ("qstatus_session_percentage", "Context window usage of the current conversation", session_percentage),
+        ("qstatus_message_quota_used", "Messages used against the monthly quota", quota_used),
+    ];
+
+    let mut out = String::new();
+    for (name, help, value) in metrics {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        let _ = writeln!(out, "{}{{source=\"{}\"}} {}", name, source, value);
+    }
+    out
+}
+
+/// Serve `/metrics` on localhost until the task is aborted
+pub async fn spawn_metrics_server(state: Arc<AppState>, port: u16) -> std::io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+
+    Ok(tokio::spawn(async move {
+        let mut retry = ACCEPT_RETRY_MIN;
+        loop {
+            // A persistent error would otherwise spin this loop at full speed
+            let mut stream = match listener.accept().await {
+                Ok((stream, _)) => {
+                    retry = ACCEPT_RETRY_MIN;
+                    stream
+                }
+                Err(e) => {
+                    tracing::debug!("Metrics accept failed, retrying in {:?}: {}", retry, e);
+                    tokio::time::sleep(retry).await;
+                    retry = (retry * 2).min(ACCEPT_RETRY_MAX);
+                    continue;
+                }
+            };
+            let state = state.clone();
+
+            tokio::spawn(async move {
+                // Only the request line matters; scrapers send small GET requests
+                let mut buf = [0u8; 1024];
+                let Ok(n) = stream.read(&mut buf).await else {
+                    return;
+                };
+                let request = String::from_utf8_lossy(&buf[..n]);
+
+                let response = if request.starts_with("GET /metrics") {
+                    let body = render_metrics(&state);
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                };
+
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::AppConfig;
+
+    #[test]
+    fn test_exposition_text_parses() {
+        let state = AppState::new(AppConfig::default());
+        state.token_usage.lock().unwrap().percentage = 42.5;
+
+        let text = render_metrics(&state);
+        let mut samples = Vec::new();
+
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                assert!(comment.starts_with("HELP ") || comment.starts_with("TYPE "));
+                continue;
+            }
+
+            // name{labels} value
+            let (series, value) = line.rsplit_once(' ').expect("sample has a value");
+            let (name, labels) = series.split_once('{').expect("sample has labels");
+            assert!(labels.ends_with('}'));
+            assert!(labels.starts_with("source=\""));
+            samples.push((name.to_string(), value.parse::<f64>().expect("value is numeric")));
+        }
+
+        assert_eq!(samples.len(), 4);
+        assert!(samples.contains(&("qstatus_session_percentage".to_string(), 42.5)));
+        assert!(samples.contains(&("qstatus_total_tokens".to_string(), 0.0)));
+    }
+}
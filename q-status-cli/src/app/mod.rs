@@ -1,5 +1,11 @@
+pub mod check;
 pub mod config;
+pub mod doctor;
+pub mod metrics;
+pub mod oneline;
 pub mod state;
+pub mod stream;
+pub mod top_cost;
 
-pub use config::{AppConfig, ExportFormat, Theme};
-pub use state::{AppEvent, AppState, CostAnalysis, TokenUsage};
+pub use config::{AmazonQPlan, AppConfig, ExportFormat, Theme};
+pub use state::{AppEvent, AppState, CostAnalysis, TokenUsage, UiState};
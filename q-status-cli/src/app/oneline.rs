@@ -0,0 +1,97 @@
+// ABOUTME: Single-line usage summary for tmux status bars and shell prompts
+// Collects once from the data source and fills in a placeholder template
+
+use super::config::AppConfig;
+use crate::data::{DataSourceFactory, DataSourceType};
+use crate::ui::dashboard::format_token_count;
+use crate::utils::error::Result;
+
+/// Template used by `--oneline` when no `--format` is given
+pub const DEFAULT_ONELINE_FORMAT: &str = "{provider} {pct} {used}/{limit} {cost}";
+
+/// Usage of the current conversation, as shown on one line
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnelineStatus {
+    pub provider: String,
+    pub percentage: f64,
+    pub used: u64,
+    pub limit: u64,
+    pub cost: f64,
+}
+
+impl OnelineStatus {
+    /// Fill `{provider}`, `{pct}`, `{used}`, `{limit}` and `{cost}` in the template
+    pub fn render(&self, template: &str, config: &AppConfig) -> String {
+        template
+            .replace("{provider}", &self.provider)
+            .replace("{pct}", &format!("{:.0}%", self.percentage))
+            .replace("{used}", &format_token_count(self.used))
+            .replace("{limit}", &format_token_count(self.limit))
+            .replace("{cost}", &config.format_cost(self.cost))
+    }
+}
+
+/// Read the current conversation once, the same way the collector does
+pub async fn collect_oneline(config: &AppConfig) -> Result<OnelineStatus> {
+    let source_type = DataSourceType::from_str(&config.data_source).unwrap_or(DataSourceType::AmazonQ);
+    let (data_source, actual_type) = DataSourceFactory::create_with_fallback(source_type, config)?;
+
+    let conversation = match &config.pinned_conversation_id {
+        Some(id) => data_source.get_conversation_by_id(id).await?,
+        None => None,
+    };
+    let conversation = match conversation {
+        Some(conv) => Some(conv),
+        None => data_source.get_current_conversation(None).await?,
+    };
+
+    // The source prices the conversation, so Claude uses its per-model cost
+    let (used, limit, percentage, cost) = match conversation {
+        Some(conv) => {
+            let usage = data_source.get_token_usage(&conv).await?;
+            let cost = data_source.get_conversation_cost(&conv, &usage, config.cost_per_1k_tokens).await?;
+            (usage.total_tokens, usage.context_window, usage.percentage, cost)
+        }
+        None => (0, 0, 0.0, 0.0),
+    };
+
+    Ok(OnelineStatus {
+        provider: actual_type.tag().to_string(),
+        percentage,
+        used,
+        limit,
+        cost,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> OnelineStatus {
+        OnelineStatus {
+            provider: "Q".to_string(),
+            percentage: 42.4,
+            used: 128_000,
+            limit: 175_000,
+            cost: 1.234,
+        }
+    }
+
+    #[test]
+    fn test_default_format() {
+        let line = status().render(DEFAULT_ONELINE_FORMAT, &AppConfig::default());
+        assert_eq!(line, "Q 42% 128K/175K $1.23");
+    }
+
+    #[test]
+    fn test_custom_template_and_repeats() {
+        let config = AppConfig {
+            display_currency: "EUR".to_string(),
+            usd_exchange_rate: 0.5,
+            ..AppConfig::default()
+        };
+        let line = status().render("[{provider}] {pct} ({pct}) {cost} {unknown}", &config);
+        assert_eq!(line, "[Q] 42% (42%) €0.62 {unknown}");
+    }
+}
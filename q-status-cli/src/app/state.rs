@@ -2,18 +2,42 @@
 // Manages all runtime data and coordinates between components
 
 use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use super::config::AppConfig;
 use crate::data::database::{CompactionStatus, ConversationSummary, GlobalStats, Session, DirectoryGroup, PeriodMetrics};
 use crate::data::DataSourceType;
 use crate::data::claude_datasource::ClaudeSession;
+use crate::utils::session_blocks::SessionBlock;
 
 // Type alias for usage history
 pub type UsageHistory = Vec<(DateTime<Local>, u64)>;
 
+/// How long a session stays marked after its cost spiked
+const SPIKE_MARKER_MINUTES: i64 = 10;
+
+/// Smallest increase (USD) the relative spike factor applies to, so fractions
+/// of a cent on a new session don't count as a 3x jump
+const MIN_RELATIVE_SPIKE_USD: f64 = 0.01;
+
+/// Collector messages kept for the log pane; older ones are dropped
+pub const LOG_CAPACITY: usize = 100;
+
+/// Whether a session's cost moving from `previous` to `current` in one refresh
+/// is abnormal: `factor` times over, or up by at least `threshold_usd`
+pub fn is_cost_spike(previous: f64, current: f64, factor: f64, threshold_usd: Option<f64>) -> bool {
+    let increase = current - previous;
+    if increase <= 0.0 {
+        return false;
+    }
+    let relative = previous > 0.0 && increase >= MIN_RELATIVE_SPIKE_USD && current >= previous * factor;
+    let absolute = threshold_usd.is_some_and(|threshold| increase >= threshold);
+    relative || absolute
+}
+
 #[derive(Debug, Clone)]
 pub struct TokenSnapshot {
     pub timestamp: DateTime<Local>,
@@ -41,25 +65,169 @@ pub struct CostAnalysis {
     pub session_cost: f64,
     pub daily_cost: f64,
     pub monthly_cost: f64,
+    /// Savings from prompt caching in the active Claude session
+    pub cache_savings: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
 pub struct BurnRate {
     pub tokens_per_minute: f64,
     pub cost_per_minute: f64,
-    pub snapshots: VecDeque<TokenSnapshot>,  // Keep last 10 minutes of snapshots
+    pub snapshots: VecDeque<TokenSnapshot>,  // Capped at config.burn_rate_history_len
     pub ema_tokens_per_minute: f64,  // Exponential Moving Average
     pub last_update: DateTime<Local>,
     pub last_total_tokens: u64,
 }
 
-#[derive(Debug, Clone)]
+impl BurnRate {
+    /// Tokens added between consecutive snapshots, oldest first
+    pub fn snapshot_deltas(&self) -> Vec<u64> {
+        self.snapshots
+            .iter()
+            .zip(self.snapshots.iter().skip(1))
+            .map(|(prev, next)| next.total_tokens.saturating_sub(prev.total_tokens))
+            .collect()
+    }
+
+    /// Drop the smoothed rate and snapshots, measuring again from `total_tokens`
+    pub fn reset(&mut self, total_tokens: u64, now: DateTime<Local>) {
+        self.tokens_per_minute = 0.0;
+        self.cost_per_minute = 0.0;
+        self.ema_tokens_per_minute = 0.0;
+        self.snapshots.clear();
+        self.last_update = now;
+        self.last_total_tokens = total_tokens;
+    }
+
+    /// Whether more than `after_minutes` passed since the last update (0 = never)
+    pub fn is_stale(&self, now: DateTime<Local>, after_minutes: u64) -> bool {
+        after_minutes > 0 && now - self.last_update > chrono::Duration::minutes(after_minutes as i64)
+    }
+}
+
+/// Unit the burn rate is displayed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BurnRateUnit {
+    #[default]
+    TokensPerMinute,
+    TokensPerHour,
+    CostPerHour,
+}
+
+impl BurnRateUnit {
+    /// Next unit in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            Self::TokensPerMinute => Self::TokensPerHour,
+            Self::TokensPerHour => Self::CostPerHour,
+            Self::CostPerHour => Self::TokensPerMinute,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::TokensPerMinute => "tokens/min",
+            Self::TokensPerHour => "tokens/hour",
+            Self::CostPerHour => "cost/hour",
+        }
+    }
+
+    /// Convert a tokens-per-minute rate into this unit, costing tokens at `cost_per_1k`
+    pub fn convert(&self, tokens_per_minute: f64, cost_per_1k: f64) -> f64 {
+        match self {
+            Self::TokensPerMinute => tokens_per_minute,
+            Self::TokensPerHour => tokens_per_minute * 60.0,
+            Self::CostPerHour => tokens_per_minute * 60.0 / 1000.0 * cost_per_1k,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ViewMode {
     CurrentDirectory,  // Show latest conversation (most recently modified)
     GlobalOverview,    // Show all conversations summary
     ConversationList,  // List all conversations
     SessionList,       // List all sessions grouped by directory
     SessionDetail,     // Detailed view of a specific session
+    UsageGraph,        // Token usage over the retained history window
+    Timeline,          // Claude billing blocks with idle gaps between them
+}
+
+/// UI preferences restored across restarts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub view_mode: ViewMode,
+    pub sort_key: SortKey,
+    pub show_active_only: bool,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            view_mode: ViewMode::SessionList,
+            sort_key: SortKey::default(),
+            show_active_only: true,
+        }
+    }
+}
+
+/// Sort order for the session list (always descending)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Tokens,
+    Cost,
+    #[default]
+    LastActivity,
+    Messages,
+}
+
+impl SortKey {
+    /// Next sort key in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            Self::Tokens => Self::Cost,
+            Self::Cost => Self::LastActivity,
+            Self::LastActivity => Self::Messages,
+            Self::Messages => Self::Tokens,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Tokens => "Tokens",
+            Self::Cost => "Cost",
+            Self::LastActivity => "Last Activity",
+            Self::Messages => "Messages",
+        }
+    }
+
+    /// Compare two sessions so the largest/most recent sorts first
+    pub fn compare(&self, a: &Session, b: &Session) -> std::cmp::Ordering {
+        match self {
+            Self::Tokens => b.token_usage.total_tokens.cmp(&a.token_usage.total_tokens),
+            Self::Cost => b.session_cost.partial_cmp(&a.session_cost).unwrap_or(std::cmp::Ordering::Equal),
+            Self::LastActivity => b.last_activity.cmp(&a.last_activity),
+            Self::Messages => b.message_count.cmp(&a.message_count),
+        }
+    }
+}
+
+/// Severity of a collector log entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Warning,
+    Error,
+}
+
+/// A collector problem surfaced in the UI instead of stderr
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub at: DateTime<Local>,
+    pub level: LogLevel,
+    pub message: String,
 }
 
 #[derive(Debug)]
@@ -69,6 +237,8 @@ pub struct AppState {
     pub usage_history: Arc<Mutex<UsageHistory>>,
     pub current_conversation: Arc<Mutex<Option<String>>>,
     pub is_connected: Arc<Mutex<bool>>,
+    /// Why the data source is unreachable, shown as a banner until it recovers
+    pub source_error: Arc<Mutex<Option<String>>>,
     pub last_update: Arc<Mutex<DateTime<Local>>>,
     pub config: AppConfig,
     pub active_data_source: Arc<Mutex<DataSourceType>>,
@@ -77,16 +247,27 @@ pub struct AppState {
     pub global_stats: Arc<Mutex<Option<GlobalStats>>>,
     pub view_mode: Arc<Mutex<ViewMode>>,
     pub selected_conversation_index: Arc<Mutex<usize>>,
+    pub search_query: Arc<Mutex<Option<String>>>,  // Conversation list filter, None when not searching
     // Session-level tracking
     pub all_sessions: Arc<Mutex<Vec<Session>>>,
     pub directory_groups: Arc<Mutex<Vec<DirectoryGroup>>>,
     pub selected_session: Arc<Mutex<Option<Session>>>,
     pub show_active_only: Arc<Mutex<bool>>,
+    pub sort_key: Arc<Mutex<SortKey>>,  // Session list sort order
     pub last_refresh: Arc<Mutex<DateTime<Local>>>,
     pub scroll_offset: Arc<Mutex<u16>>,  // For scrolling in lists
     pub burn_rate: Arc<Mutex<BurnRate>>,  // Track token burn rate
+    pub burn_rate_unit: Arc<Mutex<BurnRateUnit>>,  // Unit the burn rate is shown in
     pub period_metrics: Arc<Mutex<Option<PeriodMetrics>>>,  // Time-based metrics
     pub active_claude_session: Arc<Mutex<Option<ClaudeSession>>>,  // Active Claude session within 5 hours
+    pub active_claude_block: Arc<Mutex<Option<SessionBlock>>>,  // Active 5-hour billing block
+    pub claude_blocks: Arc<Mutex<Vec<SessionBlock>>>,  // All billing blocks, including gaps
+    pub last_compaction_status: Arc<Mutex<Option<CompactionStatus>>>,  // Status seen on the previous collection
+    pub pinned_conversation: Arc<Mutex<Option<String>>>,  // Followed instead of the latest conversation
+    pub session_costs: Arc<Mutex<HashMap<String, f64>>>,  // Cost per conversation id at the previous collection
+    pub cost_spikes: Arc<Mutex<HashMap<String, DateTime<Local>>>>,  // When each conversation's cost last spiked
+    pub refresh_requested: Arc<tokio::sync::Notify>,  // Wakes the collector for a forced refresh
+    pub log: Arc<Mutex<VecDeque<LogEntry>>>,  // Recent collector errors (and warnings with --verbose)
 }
 
 impl AppState {
@@ -106,19 +287,211 @@ impl AppState {
         *self.active_claude_session.lock().unwrap() = session;
     }
 
+    pub fn get_source_error(&self) -> Option<String> {
+        self.source_error.lock().unwrap().clone()
+    }
+
+    pub fn set_source_error(&self, error: Option<String>) {
+        *self.source_error.lock().unwrap() = error;
+    }
+
+    pub fn get_active_claude_block(&self) -> Option<SessionBlock> {
+        self.active_claude_block.lock().unwrap().clone()
+    }
+
+    pub fn set_active_claude_block(&self, block: Option<SessionBlock>) {
+        *self.active_claude_block.lock().unwrap() = block;
+    }
+
+    /// Active, idle or no Claude usage, from the last collected blocks
+    pub fn claude_block_status(&self) -> crate::utils::session_blocks::BlockStatus {
+        crate::utils::session_blocks::block_status(&self.claude_blocks.lock().unwrap())
+    }
+
+    /// Ask the collector to re-collect now, regardless of detected changes
+    pub fn request_refresh(&self) {
+        self.refresh_requested.notify_one();
+    }
+
+    /// Token total across all sessions, which the burn rate is measured on
+    pub fn total_session_tokens(&self) -> u64 {
+        self.all_sessions.lock().unwrap().iter().map(|s| s.token_usage.total_tokens).sum()
+    }
+
+    /// Clear burn-rate momentum, e.g. after resuming from a long break
+    pub fn reset_burn_rate(&self) {
+        let total_tokens = self.total_session_tokens();
+        self.burn_rate.lock().unwrap().reset(total_tokens, Local::now());
+    }
+
+    /// Claude per-block token limit, detected from past blocks when set to auto
+    pub fn claude_token_limit(&self) -> u64 {
+        match self.config.claude_token_limit {
+            0 => crate::utils::session_blocks::detect_token_limit(&self.claude_blocks.lock().unwrap()),
+            limit => limit as u64,
+        }
+    }
+
+    /// Snapshot of the UI preferences worth persisting
+    pub fn ui_state(&self) -> UiState {
+        UiState {
+            view_mode: self.view_mode.lock().unwrap().clone(),
+            sort_key: *self.sort_key.lock().unwrap(),
+            show_active_only: *self.show_active_only.lock().unwrap(),
+        }
+    }
+
+    pub fn get_pinned_conversation(&self) -> Option<String> {
+        self.pinned_conversation.lock().unwrap().clone()
+    }
+
+    /// Pin a conversation, or unpin it if it is already pinned; returns the new pin
+    pub fn toggle_pinned_conversation(&self, conversation_id: &str) -> Option<String> {
+        let mut pinned = self.pinned_conversation.lock().unwrap();
+        if pinned.as_deref() == Some(conversation_id) {
+            *pinned = None;
+        } else {
+            *pinned = Some(conversation_id.to_string());
+        }
+        pinned.clone()
+    }
+
+    /// Sessions visible in the session list, grouped by directory and sorted
+    ///
+    /// Sessions are sorted within each group by the current sort key, and groups
+    /// are ordered by their top session. Flattening the groups gives the order
+    /// used for list navigation.
+    pub fn visible_session_groups(&self) -> Vec<(String, Vec<Session>)> {
+        let directory_groups = self.directory_groups.lock().unwrap();
+        let show_active_only = *self.show_active_only.lock().unwrap();
+        let sort_key = *self.sort_key.lock().unwrap();
+
+        let mut groups: Vec<(String, Vec<Session>)> = directory_groups
+            .iter()
+            .map(|group| {
+                let mut sessions: Vec<Session> = group.sessions.iter()
+                    .filter(|s| !show_active_only || s.is_active)
+                    .cloned()
+                    .collect();
+                sessions.sort_by(|a, b| sort_key.compare(a, b));
+                (group.directory.clone(), sessions)
+            })
+            .filter(|(_, sessions)| !sessions.is_empty())
+            .collect();
+
+        groups.sort_by(|(_, a), (_, b)| sort_key.compare(&a[0], &b[0]));
+        groups
+    }
+
+    /// Sessions in session list navigation order
+    pub fn visible_sessions(&self) -> Vec<Session> {
+        self.visible_session_groups()
+            .into_iter()
+            .flat_map(|(_, sessions)| sessions)
+            .collect()
+    }
+
+    /// Conversations matching the search query (case-insensitive path substring)
+    pub fn visible_conversations(&self) -> Vec<ConversationSummary> {
+        let conversations = self.all_conversations.lock().unwrap();
+        let query = self.search_query.lock().unwrap();
+
+        match query.as_deref().map(str::to_lowercase) {
+            Some(query) if !query.is_empty() => conversations
+                .iter()
+                .filter(|c| c.path.to_lowercase().contains(&query))
+                .cloned()
+                .collect(),
+            _ => conversations.clone(),
+        }
+    }
+
+    /// Record the latest compaction status, returning true when it escalated
+    /// into Warning, Critical or Imminent since the previous collection
+    pub fn record_compaction_status(&self, status: CompactionStatus) -> bool {
+        let mut last = self.last_compaction_status.lock().unwrap();
+        let previous = last.replace(status.clone()).unwrap_or(CompactionStatus::Safe);
+        status != CompactionStatus::Safe && status > previous
+    }
+
+    /// Compare session costs with the previous collection, returning the
+    /// sessions whose cost spiked as `(id, previous, current)`
+    ///
+    /// Sessions seen for the first time are never flagged.
+    pub fn record_session_costs(&self, sessions: &[Session]) -> Vec<(String, f64, f64)> {
+        let mut costs = self.session_costs.lock().unwrap();
+        let mut marked = self.cost_spikes.lock().unwrap();
+        let now = Local::now();
+        let mut spikes = Vec::new();
+
+        for session in sessions {
+            let previous = costs.insert(session.conversation_id.clone(), session.session_cost);
+            let Some(previous) = previous else {
+                continue;
+            };
+            if is_cost_spike(previous, session.session_cost, self.config.spike_factor, self.config.spike_threshold_usd) {
+                marked.insert(session.conversation_id.clone(), now);
+                spikes.push((session.conversation_id.clone(), previous, session.session_cost));
+            }
+        }
+
+        marked.retain(|_, at| now - *at < chrono::Duration::minutes(SPIKE_MARKER_MINUTES));
+        spikes
+    }
+
+    /// Whether the session's cost spiked recently
+    pub fn has_cost_spike(&self, conversation_id: &str) -> bool {
+        self.cost_spikes.lock().unwrap().contains_key(conversation_id)
+    }
+
+    /// Record a collector error for the footer toast and log pane
+    pub fn log_error(&self, message: impl Into<String>) {
+        self.push_log(LogLevel::Error, message.into());
+    }
+
+    /// Record a warning; only kept with --verbose
+    pub fn log_warning(&self, message: impl Into<String>) {
+        if self.config.verbose {
+            self.push_log(LogLevel::Warning, message.into());
+        }
+    }
+
+    fn push_log(&self, level: LogLevel, message: String) {
+        tracing::debug!("{:?}: {}", level, message);
+        let mut log = self.log.lock().unwrap();
+        if log.len() >= LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(LogEntry { at: Local::now(), level, message });
+    }
+
+    /// The newest log entries, oldest first
+    pub fn recent_log(&self, count: usize) -> Vec<LogEntry> {
+        let log = self.log.lock().unwrap();
+        log.iter().skip(log.len().saturating_sub(count)).cloned().collect()
+    }
+
     pub fn new(config: AppConfig) -> Self {
         let initial_source = config.active_data_source.unwrap_or(DataSourceType::AmazonQ);
+        let pinned_conversation = config.pinned_conversation_id.clone();
+        let burn_rate_history_len = config.burn_rate_history_len;
+        let ui_state = config.ui_state.clone();
+        // The detail view needs a selected session, which isn't persisted
+        let view_mode = match ui_state.view_mode {
+            ViewMode::SessionDetail => ViewMode::SessionList,
+            mode => mode,
+        };
         Self {
             active_data_source: Arc::new(Mutex::new(initial_source)),
             token_usage: Arc::new(Mutex::new(TokenUsage {
                 used: 0,
-                limit: 175_000,  // Effective limit before compaction triggers
+                limit: config.amazon_q_context_window(),
                 percentage: 0.0,
                 rate_per_minute: 0.0,
                 time_remaining: None,
                 history_tokens: 0,
                 context_tokens: 0,
-                context_window: 175_000,
+                context_window: config.amazon_q_context_window(),
                 compaction_status: CompactionStatus::Safe,
                 has_summary: false,
                 message_count: 0,
@@ -127,32 +500,45 @@ impl AppState {
                 session_cost: 0.0,
                 daily_cost: 0.0,
                 monthly_cost: 0.0,
+                cache_savings: None,
             })),
             usage_history: Arc::new(Mutex::new(Vec::with_capacity(3600))),
             current_conversation: Arc::new(Mutex::new(None)),
             is_connected: Arc::new(Mutex::new(false)),
+            source_error: Arc::new(Mutex::new(None)),
             last_update: Arc::new(Mutex::new(Local::now())),
             config,
             all_conversations: Arc::new(Mutex::new(Vec::new())),
             global_stats: Arc::new(Mutex::new(None)),
-            view_mode: Arc::new(Mutex::new(ViewMode::SessionList)), // Start with session list view
+            view_mode: Arc::new(Mutex::new(view_mode)), // Restored from the last run
             selected_conversation_index: Arc::new(Mutex::new(0)),
+            search_query: Arc::new(Mutex::new(None)),
             all_sessions: Arc::new(Mutex::new(Vec::new())),
             directory_groups: Arc::new(Mutex::new(Vec::new())),
             selected_session: Arc::new(Mutex::new(None)),
-            show_active_only: Arc::new(Mutex::new(true)), // Default to showing only active sessions
+            show_active_only: Arc::new(Mutex::new(ui_state.show_active_only)),
+            sort_key: Arc::new(Mutex::new(ui_state.sort_key)),
             last_refresh: Arc::new(Mutex::new(Local::now())),
             scroll_offset: Arc::new(Mutex::new(0)),
             burn_rate: Arc::new(Mutex::new(BurnRate {
                 tokens_per_minute: 0.0,
                 cost_per_minute: 0.0,
-                snapshots: VecDeque::with_capacity(10),
+                snapshots: VecDeque::with_capacity(burn_rate_history_len),
                 ema_tokens_per_minute: 0.0,
                 last_update: Local::now(),
                 last_total_tokens: 0,
             })),
+            burn_rate_unit: Arc::new(Mutex::new(BurnRateUnit::default())),
             period_metrics: Arc::new(Mutex::new(None)),
             active_claude_session: Arc::new(Mutex::new(None)),
+            active_claude_block: Arc::new(Mutex::new(None)),
+            claude_blocks: Arc::new(Mutex::new(Vec::new())),
+            last_compaction_status: Arc::new(Mutex::new(None)),
+            session_costs: Arc::new(Mutex::new(HashMap::new())),
+            cost_spikes: Arc::new(Mutex::new(HashMap::new())),
+            pinned_conversation: Arc::new(Mutex::new(pinned_conversation)),
+            refresh_requested: Arc::new(tokio::sync::Notify::new()),
+            log: Arc::new(Mutex::new(VecDeque::with_capacity(LOG_CAPACITY))),
         }
     }
 
@@ -191,31 +577,32 @@ impl AppState {
         let mut history = self.usage_history.lock().unwrap();
         history.push((now, details.total_tokens));
 
-        // Keep only last hour of data
-        if history.len() > 3600 {
-            let drain_count = history.len() - 3600;
-            history.drain(0..drain_count);
-        }
+        // Keep only the configured retention window
+        let cutoff = now - chrono::Duration::hours(self.config.history_retention_hours as i64);
+        let expired = history.partition_point(|(timestamp, _)| *timestamp < cutoff);
+        history.drain(..expired);
 
         *self.last_update.lock().unwrap() = now;
     }
     
     // Kept for backward compatibility
     pub fn update_token_usage(&self, used: u64) {
+        let context_window = self.config.amazon_q_context_window();
+        let percentage = (used as f64 / context_window as f64) * 100.0;
         let details = crate::data::database::TokenUsageDetails {
             history_tokens: used,
             context_tokens: 0,
             total_tokens: used,
-            context_window: 175_000,
-            percentage: (used as f64 / 175_000.0) * 100.0,
-            compaction_status: match (used as f64 / 175_000.0) * 100.0 {
-                p if p < 70.0 => crate::data::database::CompactionStatus::Safe,
-                p if p < 90.0 => crate::data::database::CompactionStatus::Warning,
-                p if p < 95.0 => crate::data::database::CompactionStatus::Critical,
-                _ => crate::data::database::CompactionStatus::Imminent,
-            },
+            context_window,
+            percentage,
+            compaction_status: crate::data::database::compaction_status_for(
+                percentage,
+                &self.config.compaction_thresholds(),
+            ),
             has_summary: false,
             message_count: 0,
+            input_tokens: 0,
+            output_tokens: 0,
         };
         self.update_token_usage_details(details);
     }
@@ -228,5 +615,234 @@ pub enum AppEvent {
     Tick,
     Input(crossterm::event::KeyEvent),
     Resize(u16, u16),
+    ForceRefresh,
     Quit,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_compaction_status_only_on_escalation() {
+        let state = AppState::new(AppConfig::default());
+
+        assert!(!state.record_compaction_status(CompactionStatus::Safe));
+        assert!(state.record_compaction_status(CompactionStatus::Warning));
+        assert!(!state.record_compaction_status(CompactionStatus::Warning));
+        assert!(state.record_compaction_status(CompactionStatus::Imminent));
+
+        // Dropping back (e.g. after compaction) and re-crossing fires again
+        assert!(!state.record_compaction_status(CompactionStatus::Safe));
+        assert!(state.record_compaction_status(CompactionStatus::Critical));
+    }
+
+    #[test]
+    fn test_log_is_bounded_and_warnings_need_verbose() {
+        let state = AppState::new(AppConfig::default());
+        state.log_warning("slow read");
+        assert!(state.recent_log(10).is_empty());
+
+        for i in 0..LOG_CAPACITY + 5 {
+            state.log_error(format!("error {}", i));
+        }
+        assert_eq!(state.log.lock().unwrap().len(), LOG_CAPACITY);
+        let recent = state.recent_log(2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[1].message, format!("error {}", LOG_CAPACITY + 4));
+        assert_eq!(recent[1].level, LogLevel::Error);
+
+        let verbose = AppState::new(AppConfig { verbose: true, ..AppConfig::default() });
+        verbose.log_warning("slow read");
+        assert_eq!(verbose.recent_log(10)[0].level, LogLevel::Warning);
+    }
+
+    #[test]
+    fn test_cost_spike_detection() {
+        // Relative: 3x over, but not for sub-cent changes
+        assert!(is_cost_spike(0.50, 1.50, 3.0, None));
+        assert!(!is_cost_spike(0.50, 1.40, 3.0, None));
+        assert!(!is_cost_spike(0.001, 0.004, 3.0, None));
+        assert!(!is_cost_spike(0.0, 2.0, 3.0, None));
+        // Absolute threshold catches steady growth on large sessions
+        assert!(is_cost_spike(20.0, 25.0, 3.0, Some(5.0)));
+        assert!(!is_cost_spike(20.0, 24.0, 3.0, Some(5.0)));
+        assert!(!is_cost_spike(2.0, 1.0, 3.0, Some(0.5)));
+
+        let state = AppState::new(AppConfig::default());
+        let mut sessions = vec![session("conv-a", 1000, 0.5, 1, 1), session("conv-b", 1000, 1.0, 1, 1)];
+        assert!(state.record_session_costs(&sessions).is_empty());
+
+        sessions[0].session_cost = 2.0;
+        sessions[1].session_cost = 1.2;
+        sessions.push(session("conv-c", 1000, 9.0, 1, 1));
+        assert_eq!(state.record_session_costs(&sessions), vec![("conv-a".to_string(), 0.5, 2.0)]);
+        assert!(state.has_cost_spike("conv-a"));
+        assert!(!state.has_cost_spike("conv-b"));
+        assert!(!state.has_cost_spike("conv-c"));
+
+        // The marker outlasts the tick that raised it
+        assert!(state.record_session_costs(&sessions).is_empty());
+        assert!(state.has_cost_spike("conv-a"));
+    }
+
+    #[test]
+    fn test_toggle_pinned_conversation() {
+        let config = AppConfig {
+            pinned_conversation_id: Some("conv-a".to_string()),
+            ..AppConfig::default()
+        };
+        let state = AppState::new(config);
+
+        assert_eq!(state.get_pinned_conversation().as_deref(), Some("conv-a"));
+        assert_eq!(state.toggle_pinned_conversation("conv-b").as_deref(), Some("conv-b"));
+        assert_eq!(state.toggle_pinned_conversation("conv-b"), None);
+        assert_eq!(state.get_pinned_conversation(), None);
+    }
+
+    #[test]
+    fn test_burn_rate_snapshot_deltas() {
+        let state = AppState::new(AppConfig::default());
+        let mut burn_rate = state.burn_rate.lock().unwrap();
+        for total in [100, 150, 150, 400, 300] {
+            burn_rate.snapshots.push_back(TokenSnapshot {
+                timestamp: Local::now(),
+                total_tokens: total,
+            });
+        }
+
+        // A drop (e.g. a session aging out) reads as zero rather than underflowing
+        assert_eq!(burn_rate.snapshot_deltas(), vec![50, 0, 250, 0]);
+    }
+
+    #[test]
+    fn test_burn_rate_resets_after_idle_gap() {
+        let state = AppState::new(AppConfig::default());
+        let now = Local::now();
+        {
+            let mut burn_rate = state.burn_rate.lock().unwrap();
+            burn_rate.ema_tokens_per_minute = 800.0;
+            burn_rate.tokens_per_minute = 800.0;
+            burn_rate.last_total_tokens = 1_000;
+            burn_rate.last_update = now - chrono::Duration::minutes(45);
+            burn_rate.snapshots.push_back(TokenSnapshot { timestamp: now, total_tokens: 1_000 });
+
+            assert!(burn_rate.is_stale(now, 30));
+            assert!(!burn_rate.is_stale(now, 60));
+            assert!(!burn_rate.is_stale(now, 0));
+        }
+
+        *state.all_sessions.lock().unwrap() = vec![session("conv-a", 5_000, 1.0, 1, 1)];
+        state.reset_burn_rate();
+
+        let burn_rate = state.burn_rate.lock().unwrap();
+        assert_eq!(burn_rate.ema_tokens_per_minute, 0.0);
+        assert_eq!(burn_rate.tokens_per_minute, 0.0);
+        assert_eq!(burn_rate.last_total_tokens, 5_000);
+        assert!(burn_rate.snapshots.is_empty());
+        assert!(!burn_rate.is_stale(Local::now(), 30));
+    }
+
+    #[test]
+    fn test_burn_rate_unit_conversions() {
+        let start = BurnRateUnit::default();
+        assert_eq!(start, BurnRateUnit::TokensPerMinute);
+        assert_eq!(start.next().next().next(), start);
+
+        // 500 tokens/min at $0.006 per 1K tokens
+        assert_eq!(BurnRateUnit::TokensPerMinute.convert(500.0, 0.006), 500.0);
+        assert_eq!(BurnRateUnit::TokensPerHour.convert(500.0, 0.006), 30_000.0);
+        assert!((BurnRateUnit::CostPerHour.convert(500.0, 0.006) - 0.18).abs() < 1e-9);
+        assert_eq!(BurnRateUnit::CostPerHour.convert(0.0, 0.006), 0.0);
+    }
+
+    #[test]
+    fn test_ui_state_round_trip() {
+        let state = AppState::new(AppConfig::default());
+        *state.view_mode.lock().unwrap() = ViewMode::UsageGraph;
+        *state.sort_key.lock().unwrap() = SortKey::Cost;
+        *state.show_active_only.lock().unwrap() = false;
+
+        let config = AppConfig {
+            ui_state: state.ui_state(),
+            ..AppConfig::default()
+        };
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let restored: AppConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(restored.ui_state, state.ui_state());
+
+        let restored_state = AppState::new(restored);
+        assert_eq!(*restored_state.view_mode.lock().unwrap(), ViewMode::UsageGraph);
+        assert_eq!(*restored_state.sort_key.lock().unwrap(), SortKey::Cost);
+        assert!(!*restored_state.show_active_only.lock().unwrap());
+    }
+
+    fn session(id: &str, tokens: u64, cost: f64, minutes_ago: i64, messages: usize) -> Session {
+        let mut session = Session {
+            conversation_id: id.to_string(),
+            last_activity: Local::now() - chrono::Duration::minutes(minutes_ago),
+            message_count: messages,
+            ..Session::test("", tokens, cost)
+        };
+        session.token_usage.message_count = messages;
+        session
+    }
+
+    fn group(directory: &str, sessions: Vec<Session>) -> DirectoryGroup {
+        DirectoryGroup {
+            directory: directory.to_string(),
+            total_tokens: sessions.iter().map(|s| s.token_usage.total_tokens).sum(),
+            total_cost: sessions.iter().map(|s| s.session_cost).sum(),
+            active_session_count: sessions.len(),
+            sessions,
+        }
+    }
+
+    #[test]
+    fn test_visible_sessions_follow_sort_key() {
+        let state = AppState::new(AppConfig::default());
+        *state.directory_groups.lock().unwrap() = vec![
+            group("/a", vec![session("a-old", 100, 5.0, 60, 1), session("a-new", 300, 1.0, 1, 2)]),
+            group("/b", vec![session("b-mid", 200, 3.0, 30, 9)]),
+        ];
+
+        let ids = |state: &AppState| -> Vec<String> {
+            state.visible_sessions().into_iter().map(|s| s.conversation_id).collect()
+        };
+
+        // Default: most recent activity first, groups ordered by their top session
+        assert_eq!(ids(&state), ["a-new", "a-old", "b-mid"]);
+
+        *state.sort_key.lock().unwrap() = SortKey::Messages;
+        assert_eq!(ids(&state), ["b-mid", "a-new", "a-old"]);
+
+        *state.sort_key.lock().unwrap() = SortKey::Cost;
+        assert_eq!(ids(&state), ["a-old", "a-new", "b-mid"]);
+    }
+
+    #[test]
+    fn test_visible_conversations_match_search() {
+        let state = AppState::new(AppConfig::default());
+        *state.all_conversations.lock().unwrap() = ["/work/API-server", "/work/web", "/home/api-docs"]
+            .iter()
+            .map(|path| ConversationSummary::test(path, path, 0))
+            .collect();
+
+        assert_eq!(state.visible_conversations().len(), 3);
+
+        *state.search_query.lock().unwrap() = Some("api".to_string());
+        let paths: Vec<String> = state.visible_conversations().into_iter().map(|c| c.path).collect();
+        assert_eq!(paths, vec!["/work/API-server", "/home/api-docs"]);
+
+        // An empty query while typing shows everything
+        *state.search_query.lock().unwrap() = Some(String::new());
+        assert_eq!(state.visible_conversations().len(), 3);
+    }
+
+    #[test]
+    fn test_sort_key_cycles() {
+        let start = SortKey::default();
+        assert_eq!(start, SortKey::LastActivity);
+        assert_eq!(start.next().next().next().next(), start);
+    }
+}
@@ -0,0 +1,89 @@
+// ABOUTME: Headless JSON Lines output for long-running monitoring
+// Writes one usage snapshot per refresh to stdout instead of drawing the dashboard
+
+use super::state::{AppEvent, AppState};
+use crate::utils::error::Result;
+use chrono::Local;
+use crossbeam_channel::Receiver;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// One line of `--stream` output
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamRecord {
+    pub timestamp: String,
+    pub source: String,
+    pub conversation_id: Option<String>,
+    pub tokens_used: u64,
+    pub token_limit: u64,
+    pub percentage: f64,
+    pub rate_per_minute: f64,
+    pub session_cost: f64,
+    pub daily_cost: f64,
+    pub monthly_cost: f64,
+}
+
+/// Snapshot the current token usage and cost analysis
+pub fn stream_record(state: &AppState) -> StreamRecord {
+    let usage = state.token_usage.lock().unwrap().clone();
+    let cost = state.cost_analysis.lock().unwrap().clone();
+
+    StreamRecord {
+        timestamp: Local::now().to_rfc3339(),
+        source: state.get_active_data_source().as_str().to_string(),
+        conversation_id: state.current_conversation.lock().unwrap().clone(),
+        tokens_used: usage.used,
+        token_limit: usage.limit,
+        percentage: usage.percentage,
+        rate_per_minute: usage.rate_per_minute,
+        session_cost: cost.session_cost,
+        daily_cost: cost.daily_cost,
+        monthly_cost: cost.monthly_cost,
+    }
+}
+
+/// Print a record every refresh until SIGINT
+pub async fn run_stream(state: Arc<AppState>, event_rx: Receiver<AppEvent>) -> Result<()> {
+    let mut ticker = tokio::time::interval(Duration::from_secs(state.config.refresh_rate.max(1)));
+    let stdout = std::io::stdout();
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                // Nothing reads collector events in headless mode; drain them so it never blocks
+                for _ in event_rx.try_iter() {}
+
+                let mut out = stdout.lock();
+                serde_json::to_writer(&mut out, &stream_record(&state))?;
+                writeln!(out)?;
+                out.flush()?;
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::AppConfig;
+
+    #[test]
+    fn test_record_serializes_to_one_line() {
+        let state = AppState::new(AppConfig::default());
+        state.token_usage.lock().unwrap().used = 1_234;
+        state.cost_analysis.lock().unwrap().session_cost = 0.5;
+
+        let line = serde_json::to_string(&stream_record(&state)).unwrap();
+        assert!(!line.contains('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["tokens_used"], 1_234);
+        assert_eq!(value["session_cost"], 0.5);
+        assert!(value["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,113 @@
+// ABOUTME: Headless "most expensive sessions" report for --top-cost
+// Ranks sessions by cost within the --since / --until window and prints a table
+
+use super::config::AppConfig;
+use crate::data::database::Session;
+use crate::data::{DataSourceFactory, DataSourceType};
+use crate::ui::dashboard::format_token_count;
+use crate::utils::date_range::DateRange;
+use crate::utils::error::Result;
+
+/// The `count` most expensive sessions active within `range`, costliest first
+pub fn top_by_cost(sessions: Vec<Session>, count: usize, range: &DateRange) -> Vec<Session> {
+    let mut sessions: Vec<Session> = sessions
+        .into_iter()
+        .filter(|session| range.contains(&session.last_activity))
+        .collect();
+    sessions.sort_by(|a, b| b.session_cost.total_cmp(&a.session_cost));
+    sessions.truncate(count);
+    sessions
+}
+
+/// Models a session used, most expensive first, or "-" when unknown
+fn models_of(session: &Session) -> String {
+    let mut usage: Vec<_> = session.model_usage.iter().collect();
+    usage.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+    let models: Vec<&str> = usage.iter().map(|usage| usage.model.as_str()).collect();
+    if models.is_empty() {
+        "-".to_string()
+    } else {
+        models.join(", ")
+    }
+}
+
+/// One row per session: rank, cost, tokens, models and directory
+pub fn render_top_cost(sessions: &[Session], config: &AppConfig) -> String {
+    if sessions.is_empty() {
+        return "No sessions in range\n".to_string();
+    }
+
+    let mut out = format!("{:>3}  {:>10}  {:>7}  {:<28}  {}\n", "#", "Cost", "Tokens", "Models", "Directory");
+    for (rank, session) in sessions.iter().enumerate() {
+        out.push_str(&format!(
+            "{:>3}  {:>10}  {:>7}  {:<28}  {}\n",
+            rank + 1,
+            config.format_cost(session.session_cost),
+            format_token_count(session.token_usage.total_tokens),
+            models_of(session),
+            session.directory,
+        ));
+    }
+    out
+}
+
+/// Read every session once and keep the most expensive
+pub async fn collect_top_cost(config: &AppConfig, count: usize) -> Result<Vec<Session>> {
+    let source_type = DataSourceType::from_str(&config.data_source).unwrap_or(DataSourceType::AmazonQ);
+    let (data_source, _) = DataSourceFactory::create_with_fallback(source_type, config)?;
+
+    let sessions = data_source.get_all_sessions(config.cost_per_1k_tokens).await?;
+    Ok(top_by_cost(sessions, count, &DateRange::new(config.since, config.until)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::database::ModelUsage;
+    use chrono::{Duration, Local};
+
+    fn session(directory: &str, tokens: u64, cost: f64, days_ago: i64) -> Session {
+        Session {
+            last_activity: Local::now() - Duration::days(days_ago),
+            is_active: false,
+            ..Session::test(directory, tokens, cost)
+        }
+    }
+
+    #[test]
+    fn test_top_by_cost_ranks_by_cost_not_tokens() {
+        let sessions = vec![
+            session("/big-but-cheap", 900_000, 1.0, 1),
+            session("/pricey", 50_000, 12.0, 2),
+            session("/old-and-pricey", 50_000, 40.0, 30),
+            session("/middle", 100_000, 5.0, 3),
+        ];
+
+        let all = top_by_cost(sessions.clone(), 10, &DateRange::default());
+        let order: Vec<&str> = all.iter().map(|s| s.directory.as_str()).collect();
+        assert_eq!(order, ["/old-and-pricey", "/pricey", "/middle", "/big-but-cheap"]);
+
+        let last_week = DateRange::new(Some(Local::now() - Duration::days(7)), None);
+        let top = top_by_cost(sessions, 2, &last_week);
+        let order: Vec<&str> = top.iter().map(|s| s.directory.as_str()).collect();
+        assert_eq!(order, ["/pricey", "/middle"]);
+    }
+
+    #[test]
+    fn test_render_lists_models_by_cost() {
+        let mut pricey = session("/work/api", 52_000, 12.5, 0);
+        pricey.model_usage = vec![
+            ModelUsage { model: "claude-3-5-haiku".to_string(), tokens: 40_000, cost: 0.5, output_cost: 0.2 },
+            ModelUsage { model: "claude-opus-4".to_string(), tokens: 12_000, cost: 12.0, output_cost: 9.0 },
+        ];
+
+        let table = render_top_cost(&[pricey], &AppConfig::default());
+        let row = table.lines().nth(1).unwrap();
+        assert!(row.contains("$12.50"));
+        assert!(row.contains("52K"));
+        assert!(row.contains("claude-opus-4, claude-3-5-haiku"));
+        assert!(row.ends_with("/work/api"));
+
+        assert_eq!(render_top_cost(&[], &AppConfig::default()), "No sessions in range\n");
+    }
+}
@@ -2,22 +2,32 @@
 // Provides DataSource trait implementation for Claude Code conversation and token usage tracking
 
 use crate::data::database::{
-    CompactionStatus, ConversationSummary, DirectoryGroup, GlobalStats,
-    PeriodMetrics, QConversation, Session, TokenUsageDetails,
+    compaction_status_for, CompactionStatus, CompactionThresholds, ConversationSummary, DirectoryGroup, GlobalStats,
+    ModelUsage, PeriodMetrics, QConversation, Session, TokenUsageDetails,
 };
+use crate::app::config::{AppConfig, DirGroupMode};
 use crate::data::datasource::DataSource;
 use crate::utils::cost_calculator::{CostCalculator, CostMode, TokenUsage as CostTokenUsage};
+use crate::utils::date_range::DateRange;
 use crate::utils::error::{QStatusError, Result};
+use crate::utils::session_blocks::{self, identify_session_blocks, SessionBlock};
+use crate::utils::timezone::DisplayZone;
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Local, Utc};
 use glob::glob;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::fs;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+/// Context window used when a session's models have no known limit
+const DEFAULT_CONTEXT_WINDOW: u64 = 200_000;
+
 /// Claude Code usage entry from JSONL files
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeUsageEntry {
@@ -25,7 +35,7 @@ pub struct ClaudeUsageEntry {
     #[serde(rename = "sessionId")]
     pub session_id: Option<String>,
     pub message: ClaudeMessage,
-    #[serde(rename = "costUSD")]
+    #[serde(rename = "costUSD", default, deserialize_with = "deserialize_cost_usd")]
     pub cost_usd: Option<f64>,
     #[serde(rename = "requestId")]
     pub request_id: Option<String>,
@@ -35,19 +45,100 @@ pub struct ClaudeUsageEntry {
     pub version: Option<String>,
 }
 
+/// Turn Claude's encoded project directory name back into a readable path
+///
+/// Claude replaces path separators with dashes (`/Users/me/code/foo` is stored
+/// as `-Users-me-code-foo`, `C:\work\foo` as `C--work-foo`). Dashes that were
+/// part of a directory name can't be told apart, so prefer the entry's `cwd`.
+pub fn decode_project_name(raw: &str) -> String {
+    // Windows drive: "C--work-foo" -> "C:/work/foo"
+    let mut chars = raw.chars();
+    if let (Some(drive), Some('-'), Some('-')) = (chars.next(), chars.next(), chars.next()) {
+        if drive.is_ascii_alphabetic() {
+            return format!("{}:/{}", drive, raw[3..].replace('-', "/"));
+        }
+    }
+
+    // Unix absolute path
+    if raw.starts_with('-') {
+        return raw.replace('-', "/");
+    }
+
+    raw.to_string()
+}
+
+/// Nearest directory at or above `directory` containing `.git` (a folder, or a
+/// file for worktrees and submodules)
+pub fn find_git_root(directory: &Path) -> Option<PathBuf> {
+    directory
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+// Content is usually an array of blocks, but can be a bare string or null;
+// anything else is dropped rather than failing the line
+fn deserialize_content<'de, D>(deserializer: D) -> std::result::Result<Option<Vec<MessageContent>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(text) => Some(vec![MessageContent { kind: Some("text".to_string()), text: Some(text), name: None }]),
+        blocks @ Value::Array(_) => serde_json::from_value(blocks).ok(),
+        _ => None,
+    })
+}
+
+// Custom deserializer for costUSD: some Claude Code versions write it as a
+// string, which would otherwise fail the whole line and drop its cost
+fn deserialize_cost_usd<'de, D>(deserializer: D) -> std::result::Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let cost = match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+
+    // Negative costs are bogus; never let them reduce totals
+    Ok(cost.filter(|c| c.is_finite()).map(|c| c.max(0.0)))
+}
+
 /// Claude message structure containing usage and model info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeMessage {
     pub usage: ClaudeTokenUsage,
     pub model: Option<String>,
     pub id: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_content")]
     pub content: Option<Vec<MessageContent>>,
 }
 
-/// Message content structure
+impl ClaudeMessage {
+    /// Every text block joined by newlines, with tool calls noted as `[tool: name]`
+    pub fn content_text(&self) -> Option<String> {
+        let parts: Vec<String> = self.content.iter()
+            .flatten()
+            .filter_map(|block| match block.kind.as_deref() {
+                Some("tool_use") => Some(format!("[tool: {}]", block.name.as_deref().unwrap_or("unknown"))),
+                Some("text") | None => block.text.clone().filter(|text| !text.is_empty()),
+                _ => None,
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    }
+}
+
+/// One block of message content: text, a tool call, thinking, ...
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct MessageContent {
-    text: Option<String>,
+pub struct MessageContent {
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+    pub text: Option<String>,
+    /// Tool name on `tool_use` blocks
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
 /// Token usage details from Claude
@@ -68,6 +159,17 @@ impl ClaudeTokenUsage {
             + self.cache_read_input_tokens.unwrap_or(0) as u64
     }
 
+    /// Total shown against the context window, optionally treating cache reads as free
+    ///
+    /// Costs always use `total()`; this only changes what the gauge counts.
+    pub fn display_total(&self, exclude_cache_read: bool) -> u64 {
+        if exclude_cache_read {
+            self.total() - self.cache_read_input_tokens.unwrap_or(0) as u64
+        } else {
+            self.total()
+        }
+    }
+
     /// Add another token usage to this one
     fn add(&mut self, other: &ClaudeTokenUsage) {
         self.input_tokens += other.input_tokens;
@@ -108,40 +210,171 @@ pub struct ClaudeSession {
     pub models: HashSet<String>,
 }
 
-/// Cache entry for tracking file modifications
+impl ClaudeSession {
+    /// Running token total after each entry, in order
+    pub fn token_timeline(&self) -> Vec<u64> {
+        self.entries
+            .iter()
+            .scan(0u64, |total, entry| {
+                *total += entry.message.usage.total();
+                Some(*total)
+            })
+            .collect()
+    }
+}
+
+/// Cache entry for tracking how far each JSONL file has been parsed
 #[derive(Debug, Clone)]
 struct CacheEntry {
-    path: PathBuf,
     modified: std::time::SystemTime,
-    entries: Vec<ClaudeUsageEntry>,
+    /// Byte offset just past the last parsed line
+    offset: u64,
+}
+
+/// Totals of sessions dropped from the in-memory cache by the retention limits
+#[derive(Debug, Clone, Default)]
+struct PrunedTotals {
+    /// Ids of the dropped sessions; later entries for them are folded in here
+    ids: HashSet<String>,
+    sessions: usize,
+    messages: usize,
+    tokens: u64,
+    cost: f64,
+    cost_by_model: HashMap<String, f64>,
+    tokens_by_model: HashMap<String, u64>,
+}
+
+impl PrunedTotals {
+    fn add(&mut self, session: &ClaudeSession, cost_of: impl Fn(&ClaudeUsageEntry) -> f64) {
+        self.sessions += 1;
+        self.ids.insert(session.id.clone());
+        for entry in &session.entries {
+            self.add_entry(entry, cost_of(entry));
+        }
+    }
+
+    /// Count one entry of an already pruned session
+    fn add_entry(&mut self, entry: &ClaudeUsageEntry, cost: f64) {
+        let tokens = entry.message.usage.total();
+        self.messages += 1;
+        self.tokens += tokens;
+        self.cost += cost;
+        let model = entry.message.model.clone().unwrap_or_else(|| "unknown".to_string());
+        *self.cost_by_model.entry(model.clone()).or_insert(0.0) += cost;
+        *self.tokens_by_model.entry(model).or_insert(0) += tokens;
+    }
+}
+
+/// Session an entry belongs to; entries without one each form their own session
+fn session_key(entry: &ClaudeUsageEntry) -> String {
+    entry.session_id.clone().unwrap_or_else(|| format!("no-session-{}", entry.timestamp))
+}
+
+/// Share of malformed complete lines in one file that triggers a warning
+const MALFORMED_WARNING_RATIO: f64 = 0.1;
+
+/// Complete lines seen while parsing a chunk, and how many failed to parse
+#[derive(Debug, Clone, Copy, Default)]
+struct LineCounts {
+    total: usize,
+    malformed: usize,
+    /// API-error entries skipped because of `exclude_api_errors`
+    api_errors: usize,
 }
 
 /// Claude Code data source implementation
 pub struct ClaudeCodeDataSource {
-    /// Cached data
+    /// Claude data directories resolved at startup
+    claude_paths: Vec<PathBuf>,
+    /// Per-file parse progress
     cache: Arc<Mutex<HashMap<PathBuf, CacheEntry>>>,
+    /// Dedup keys of every entry loaded so far, kept across refreshes
+    seen_ids: Arc<Mutex<HashSet<String>>>,
+    /// Complete lines skipped because they weren't valid usage entries
+    malformed_lines: Arc<Mutex<usize>>,
+    /// Files already warned about for a high share of malformed lines
+    malformed_warned: Arc<Mutex<HashSet<PathBuf>>>,
     /// Last check time for changes
     last_check: Arc<Mutex<Option<std::time::SystemTime>>>,
     /// Cached sessions
     sessions: Arc<Mutex<Vec<ClaudeSession>>>,
+    /// Totals of sessions pruned by `max_sessions_retained` / `max_history_days`
+    pruned: Arc<Mutex<PrunedTotals>>,
+    /// Cached deduplicated entries, sorted by timestamp
+    entries: Arc<Mutex<Vec<ClaudeUsageEntry>>>,
     /// Whether cache needs refresh
     needs_refresh: Arc<Mutex<bool>>,
     /// Cost calculator instance
     cost_calculator: CostCalculator,
     /// Cost calculation mode
     cost_mode: CostMode,
+    /// Window entries must fall within to be loaded
+    date_range: DateRange,
+    /// Monthly message quota reported in global stats
+    message_quota_limit: usize,
+    /// Sessions active within this many days are marked active
+    active_session_days: i64,
+    /// Lowercased model substrings; entries must match one when non-empty
+    model_filters: Vec<String>,
+    /// Skip entries flagged `isApiErrorMessage`
+    exclude_api_errors: bool,
+    /// Most recent sessions kept in the cache
+    max_sessions_retained: Option<usize>,
+    /// Sessions idle longer than this many days are pruned from the cache
+    max_history_days: Option<i64>,
+    compaction_thresholds: CompactionThresholds,
+    /// Leave cache reads out of context-window percentages
+    exclude_cache_read: bool,
+    /// Include gzip-compressed `.jsonl.gz` archives
+    read_compressed: bool,
+    /// Project directories to scan; all of them when empty
+    projects_allowlist: Vec<String>,
+    /// Deepest directory level below `projects/` searched for files
+    max_glob_depth: Option<usize>,
+    /// What directory groups are keyed on
+    group_by: DirGroupMode,
+    /// Git root found for each session directory, so each is only walked once
+    git_roots: Arc<Mutex<HashMap<String, String>>>,
+    /// Zone that decides which day activity falls on
+    timezone: DisplayZone,
 }
 
 impl ClaudeCodeDataSource {
     /// Create a new Claude Code data source
     pub fn new() -> Result<Self> {
+        Self::with_config(&AppConfig::default())
+    }
+
+    /// Create a Claude Code data source honoring the app configuration
+    pub fn with_config(config: &AppConfig) -> Result<Self> {
         let source = Self {
+            claude_paths: Self::get_claude_paths()?,
             cache: Arc::new(Mutex::new(HashMap::new())),
+            seen_ids: Arc::new(Mutex::new(HashSet::new())),
+            malformed_lines: Arc::new(Mutex::new(0)),
+            malformed_warned: Arc::new(Mutex::new(HashSet::new())),
             last_check: Arc::new(Mutex::new(None)),
             sessions: Arc::new(Mutex::new(Vec::new())),
+            pruned: Arc::new(Mutex::new(PrunedTotals::default())),
+            entries: Arc::new(Mutex::new(Vec::new())),
             needs_refresh: Arc::new(Mutex::new(true)),
-            cost_calculator: CostCalculator::new(),
-            cost_mode: CostMode::Auto,
+            cost_calculator: CostCalculator::for_config(config).with_fallback_rate(config.cost_per_1k_tokens),
+            cost_mode: config.effective_cost_mode(),
+            date_range: config.date_range(),
+            message_quota_limit: config.effective_message_quota_limit(),
+            active_session_days: config.active_session_days,
+            model_filters: config.model_filters.iter().map(|m| m.to_lowercase()).collect(),
+            exclude_api_errors: config.exclude_api_errors,
+            max_sessions_retained: config.max_sessions_retained,
+            max_history_days: config.max_history_days,
+            compaction_thresholds: config.compaction_thresholds(),
+            exclude_cache_read: config.exclude_cache_read_from_total,
+            read_compressed: config.read_compressed,
+            projects_allowlist: config.claude_projects_allowlist.clone(),
+            max_glob_depth: config.claude_max_glob_depth,
+            group_by: config.group_by,
+            git_roots: Arc::new(Mutex::new(HashMap::new())),
+            timezone: config.timezone(),
         };
 
         // Load initial data
@@ -150,50 +383,151 @@ impl ClaudeCodeDataSource {
         Ok(source)
     }
 
-    /// Get the currently active Claude session (within last 5 hours)
+    /// Group loaded entries into 5-hour billing blocks, including gap blocks
+    pub fn get_session_blocks(&self) -> Result<Vec<SessionBlock>> {
+        let entries = self.entries.lock().unwrap();
+        let block_entries: Vec<session_blocks::ClaudeUsageEntry> = entries
+            .iter()
+            .map(|entry| self.to_block_entry(entry))
+            .collect();
+
+        Ok(identify_session_blocks(&block_entries, None))
+    }
+
+    /// Directory a session is grouped under, per the configured grouping mode
+    fn group_directory(&self, directory: &str) -> String {
+        match self.group_by {
+            DirGroupMode::Cwd => directory.to_string(),
+            DirGroupMode::GitRoot => self
+                .git_roots
+                .lock()
+                .unwrap()
+                .entry(directory.to_string())
+                .or_insert_with(|| {
+                    find_git_root(Path::new(directory))
+                        .map(|root| root.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| directory.to_string())
+                })
+                .clone(),
+        }
+    }
+
+    /// Whether there is an active billing block, or how long usage has been idle
+    pub fn block_status(&self) -> Result<session_blocks::BlockStatus> {
+        Ok(session_blocks::block_status(&self.get_session_blocks()?))
+    }
+
+    /// Get the usage in the currently active 5-hour billing block
     pub async fn get_active_session(&self) -> Result<Option<ClaudeSession>> {
         if *self.needs_refresh.lock().unwrap() {
             self.refresh_cache().await?;
         }
 
-        let sessions = self.sessions.lock().unwrap();
-        let now = Utc::now();
-        let five_hours_ago = now - Duration::hours(5);
-
-        // Find the most recent session within the 5-hour window
-        let active_session = sessions.iter()
-            .filter(|s| s.end_time > five_hours_ago)
-            .max_by_key(|s| s.end_time)
-            .map(|session| {
-                // For active sessions, provide both cumulative total and current context
-                let mut adjusted_session = session.clone();
-
-                // Get the most recent entry to find current context usage
-                if let Some(last_entry) = session.entries.last() {
-                    // Use cache_read_input_tokens as the actual context in use
-                    // This is what Claude has in memory after compaction
-                    let cache_read = last_entry.message.usage.cache_read_input_tokens.unwrap_or(0);
-                    let cache_creation = last_entry.message.usage.cache_creation_input_tokens.unwrap_or(0);
-                    let input = last_entry.message.usage.input_tokens;
-
-                    // Set the context_tokens to show current context usage
-                    adjusted_session.context_tokens = Some(ClaudeTokenUsage {
-                        input_tokens: input,  // Current input tokens
-                        output_tokens: 0,     // Not relevant for context display
-                        cache_creation_input_tokens: Some(cache_creation),
-                        cache_read_input_tokens: Some(cache_read),
-                    });
-                }
+        let blocks = self.get_session_blocks()?;
+        let Some(block) = blocks.iter().find(|b| b.is_active && !b.is_gap) else {
+            return Ok(None);
+        };
 
-                // total_tokens remains unchanged - it's the cumulative total across all entries
-                adjusted_session
-            });
+        let entries = self.entries.lock().unwrap();
+        let block_entries: Vec<ClaudeUsageEntry> = entries
+            .iter()
+            .filter(|e| {
+                self.parse_timestamp(&e.timestamp)
+                    .map(|t| t >= block.start_time && t < block.end_time)
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
 
-        Ok(active_session)
+        Ok(self.block_to_session(block, block_entries))
+    }
+
+    /// Convert an entry to the session block calculator's format, resolving its cost
+    fn to_block_entry(&self, entry: &ClaudeUsageEntry) -> session_blocks::ClaudeUsageEntry {
+        session_blocks::ClaudeUsageEntry {
+            timestamp: entry.timestamp.clone(),
+            session_id: entry.session_id.clone(),
+            message: session_blocks::ClaudeMessage {
+                usage: session_blocks::ClaudeTokenUsage {
+                    input_tokens: entry.message.usage.input_tokens,
+                    output_tokens: entry.message.usage.output_tokens,
+                    cache_creation_input_tokens: entry.message.usage.cache_creation_input_tokens,
+                    cache_read_input_tokens: entry.message.usage.cache_read_input_tokens,
+                },
+                model: entry.message.model.clone(),
+                id: entry.message.id.clone(),
+            },
+            cost_usd: Some(self.calculate_cost(entry)),
+            request_id: entry.request_id.clone(),
+            cwd: entry.cwd.clone(),
+            version: entry.version.clone(),
+            is_api_error_message: entry.is_api_error_message,
+        }
+    }
+
+    /// Build a session view of a billing block from the entries it contains
+    fn block_to_session(&self, block: &SessionBlock, entries: Vec<ClaudeUsageEntry>) -> Option<ClaudeSession> {
+        let first = entries.first()?;
+        let last = entries.last()?;
+
+        let mut total_tokens = ClaudeTokenUsage::default();
+        let mut from_jsonl = 0.0;
+        let mut calculated = 0.0;
+        for entry in &entries {
+            total_tokens.add(&entry.message.usage);
+            let cost = self.calculate_cost(entry);
+            if entry.cost_usd.is_some_and(|c| c > 0.0) {
+                from_jsonl += cost;
+            } else {
+                calculated += cost;
+            }
+        }
+        let total_cost = from_jsonl + calculated;
+
+        // Use the most recent entry as the current context in use
+        // (cache reads are what Claude has in memory after compaction)
+        let context_tokens = Some(ClaudeTokenUsage {
+            input_tokens: last.message.usage.input_tokens,
+            output_tokens: 0,
+            cache_creation_input_tokens: Some(last.message.usage.cache_creation_input_tokens.unwrap_or(0)),
+            cache_read_input_tokens: Some(last.message.usage.cache_read_input_tokens.unwrap_or(0)),
+        });
+
+        Some(ClaudeSession {
+            id: last.session_id.clone().unwrap_or_else(|| block.id.clone()),
+            project: last.cwd.clone().unwrap_or_else(|| "unknown".to_string()),
+            directory: last.cwd.clone(),
+            start_time: self.parse_timestamp(&first.timestamp).unwrap_or(block.start_time),
+            end_time: block.actual_end_time.unwrap_or(block.start_time),
+            total_tokens,
+            context_tokens,
+            total_cost,
+            cost_breakdown: CostBreakdown {
+                total: total_cost,
+                from_jsonl,
+                calculated,
+                percent_actual: if total_cost > 0.0 { (from_jsonl / total_cost) * 100.0 } else { 0.0 },
+            },
+            models: block.models.clone(),
+            entries,
+        })
+    }
+
+    /// Drop roots whose `projects` directory resolves to one already listed,
+    /// e.g. when `~/.config/claude` is a symlink to `~/.claude`
+    fn dedup_roots(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        paths
+            .into_iter()
+            .filter(|path| {
+                let projects = path.join("projects");
+                seen.insert(fs::canonicalize(&projects).unwrap_or(projects))
+            })
+            .collect()
     }
 
     /// Get Claude data directories
-    fn get_claude_paths(&self) -> Result<Vec<PathBuf>> {
+    pub(crate) fn get_claude_paths() -> Result<Vec<PathBuf>> {
         let mut paths = Vec::new();
 
         // Check environment variable first (comma-separated paths)
@@ -225,6 +559,8 @@ impl ClaudeCodeDataSource {
             }
         }
 
+        let paths = Self::dedup_roots(paths);
+
         if paths.is_empty() {
             return Err(QStatusError::Config(
                 "No valid Claude data directories found. Please ensure ~/.claude/projects or ~/.config/claude/projects exists".to_string()
@@ -253,101 +589,341 @@ impl ClaudeCodeDataSource {
             .map_err(|e| QStatusError::Config(format!("Invalid timestamp: {}", e)))
     }
 
-    /// Calculate cost for an entry - prioritizes actual cost_usd from JSONL
+    /// Calculate cost for an entry according to the cost mode
+    ///
+    /// Auto prefers the JSONL cost_usd field, Calculate always prices tokens,
+    /// and Display only uses cost_usd.
     fn calculate_cost(&self, entry: &ClaudeUsageEntry) -> f64 {
-        // Always prioritize the actual cost_usd field if present
-        if let Some(actual_cost) = entry.cost_usd {
-            if actual_cost > 0.0 {
-                return actual_cost;
-            }
-        }
-
-        // Fall back to calculated cost if no actual cost
         let model = entry.message.model.as_deref().unwrap_or("claude-3-5-sonnet-20241022");
 
-        // Convert to cost calculator's token usage format
-        let tokens = CostTokenUsage {
-            input_tokens: entry.message.usage.input_tokens,
-            output_tokens: entry.message.usage.output_tokens,
-            cache_creation_input_tokens: entry.message.usage.cache_creation_input_tokens,
-            cache_read_input_tokens: entry.message.usage.cache_read_input_tokens,
-        };
-
         self.cost_calculator.calculate_cost(
-            &tokens,
+            &Self::cost_tokens(&entry.message.usage),
             model,
             self.cost_mode,
             entry.cost_usd,
         )
     }
 
-    /// Load JSONL files and parse entries
-    async fn load_jsonl_files(&self) -> Result<Vec<ClaudeUsageEntry>> {
-        let paths = self.get_claude_paths()?;
-        let mut all_entries = Vec::new();
-        let mut seen_ids = HashSet::new();
+    /// Convert to the cost calculator's token usage format
+    fn cost_tokens(usage: &ClaudeTokenUsage) -> CostTokenUsage {
+        CostTokenUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+        }
+    }
 
-        for base_path in paths {
-            let projects_path = base_path.join("projects");
-            let pattern = projects_path.join("**/*.jsonl");
+    /// Re-bucket a session's entries by model, most expensive first
+    pub fn model_usage(&self, session: &ClaudeSession) -> Vec<ModelUsage> {
+        let mut by_model: HashMap<String, ModelUsage> = HashMap::new();
+        for entry in &session.entries {
+            let model = entry.message.model.clone().unwrap_or_else(|| "unknown".to_string());
+            let cost = self.calculate_cost(entry);
+            // Never more than the entry's cost, which may be a pre-calculated figure
+            let output_cost = self.cost_calculator
+                .output_cost(&Self::cost_tokens(&entry.message.usage), &model)
+                .min(cost);
+            let usage = by_model.entry(model.clone()).or_insert(ModelUsage { model, tokens: 0, cost: 0.0, output_cost: 0.0 });
+            usage.tokens += entry.message.usage.total();
+            usage.cost += cost;
+            usage.output_cost += output_cost;
+        }
 
-            let glob_pattern = pattern.to_string_lossy();
-            for entry in glob(&glob_pattern).map_err(|e| QStatusError::Config(format!("Glob pattern error: {}", e)))? {
-                let file_path = entry.map_err(|e| QStatusError::Config(format!("Glob error: {}", e)))?;
+        let mut usage: Vec<ModelUsage> = by_model.into_values().collect();
+        usage.sort_by(|a, b| b.cost.total_cmp(&a.cost).then_with(|| a.model.cmp(&b.model)));
+        usage
+    }
 
-                // Read file line by line
-                let content = fs::read_to_string(&file_path)
-                    .map_err(|e| QStatusError::Io(e))?;
+    /// Money saved by prompt caching across a session's messages
+    pub fn cache_savings(&self, session: &ClaudeSession) -> f64 {
+        session
+            .entries
+            .iter()
+            .map(|entry| {
+                let model = entry.message.model.as_deref().unwrap_or("claude-3-5-sonnet-20241022");
+                self.cost_calculator.cache_savings(&Self::cost_tokens(&entry.message.usage), model)
+            })
+            .sum()
+    }
 
-                let project = self.extract_project_from_path(&file_path);
+    /// Directories to scan under each root: `projects/` itself, or just the
+    /// allowlisted project subdirectories (by encoded name or decoded path)
+    fn scan_dirs(&self, base_path: &Path) -> Vec<PathBuf> {
+        let projects = base_path.join("projects");
+        if self.projects_allowlist.is_empty() {
+            return vec![projects];
+        }
 
-                for line in content.lines() {
-                    if line.trim().is_empty() {
-                        continue;
-                    }
+        let Ok(entries) = fs::read_dir(&projects) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let decoded = decode_project_name(&name);
+                self.projects_allowlist.iter().any(|allowed| *allowed == name || *allowed == decoded)
+            })
+            .map(|entry| entry.path())
+            .collect()
+    }
 
-                    // Parse JSON line
-                    match serde_json::from_str::<ClaudeUsageEntry>(line) {
-                        Ok(mut entry) => {
-                            // Deduplicate by request ID or message ID
-                            let unique_id = entry.request_id.as_ref()
-                                .or(entry.message.id.as_ref())
-                                .map(|s| s.to_string())
-                                .unwrap_or_else(|| format!("{}-{}", entry.timestamp, entry.message.usage.total()));
-
-                            if !seen_ids.contains(&unique_id) {
-                                seen_ids.insert(unique_id);
-
-                                // Add project directory if not in cwd
-                                if entry.cwd.is_none() {
-                                    entry.cwd = Some(project.clone());
-                                }
-
-                                all_entries.push(entry);
-                            }
-                        }
-                        Err(_) => {
-                            // Skip malformed lines
-                            continue;
-                        }
+    /// Glob patterns relative to a scanned directory, at most `max_glob_depth`
+    /// levels below `projects/` when set
+    fn file_patterns(&self) -> Vec<String> {
+        let names: &[&str] = if self.read_compressed { &["*.jsonl", "*.jsonl.gz"] } else { &["*.jsonl"] };
+        let Some(max_depth) = self.max_glob_depth else {
+            return names.iter().map(|name| format!("**/{}", name)).collect();
+        };
+
+        // Allowlisted directories already sit one level below `projects/`
+        let first_level = if self.projects_allowlist.is_empty() { 1 } else { 2 };
+        (first_level..=max_depth)
+            .flat_map(|depth| names.iter().map(move |name| format!("{}{}", "*/".repeat(depth - first_level), name)))
+            .collect()
+    }
+
+    /// Find all JSONL files under the Claude data directories, plus
+    /// compressed archives when enabled
+    fn find_jsonl_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let patterns = self.file_patterns();
+
+        for base_path in &self.claude_paths {
+            for dir in self.scan_dirs(base_path) {
+                for pattern in &patterns {
+                    let pattern = Path::new(&glob::Pattern::escape(&dir.to_string_lossy())).join(pattern);
+                    let glob_pattern = pattern.to_string_lossy();
+                    for entry in glob(&glob_pattern).map_err(|e| QStatusError::Config(format!("Glob pattern error: {}", e)))? {
+                        files.push(entry.map_err(|e| QStatusError::Config(format!("Glob error: {}", e)))?);
                     }
                 }
             }
         }
 
+        Ok(files)
+    }
+
+    /// Parse JSONL data appended since the last refresh
+    ///
+    /// Files with unchanged mtime and size are skipped, and the rest are read from
+    /// the byte offset where the previous parse stopped. If any file shrank or
+    /// disappeared the cache is reset and everything is re-parsed; the returned
+    /// flag tells the caller to discard previously loaded entries.
+    async fn load_jsonl_files(&self) -> Result<(Vec<ClaudeUsageEntry>, bool)> {
+        let files = self.find_jsonl_files()?;
+        let mut cache = self.cache.lock().unwrap();
+        let mut seen_ids = self.seen_ids.lock().unwrap();
+
+        let reset = cache.iter().any(|(path, cached)| {
+            fs::metadata(path).map(|m| m.len() < cached.offset).unwrap_or(true)
+        });
+        if reset {
+            cache.clear();
+            seen_ids.clear();
+            *self.malformed_lines.lock().unwrap() = 0;
+        }
+
+        let mut new_entries = Vec::new();
+
+        for file_path in files {
+            let metadata = fs::metadata(&file_path).map_err(QStatusError::Io)?;
+            let modified = metadata.modified().map_err(QStatusError::Io)?;
+            let len = metadata.len();
+
+            let offset = match cache.get(&file_path) {
+                Some(cached) if cached.modified == modified && cached.offset == len => continue,
+                Some(cached) => cached.offset,
+                None => 0,
+            };
+
+            let project = decode_project_name(&self.extract_project_from_path(&file_path));
+            let mut counts = LineCounts::default();
+
+            // Archives can't be read from an offset, so they are parsed whole;
+            // entries seen before are dropped by the dedup keys
+            let is_compressed = file_path.extension().is_some_and(|ext| ext == "gz");
+            let next_offset = if is_compressed {
+                let mut buffer = Vec::new();
+                let decoded = flate2::read::MultiGzDecoder::new(fs::File::open(&file_path).map_err(QStatusError::Io)?)
+                    .read_to_end(&mut buffer);
+                if let Err(e) = decoded {
+                    // Possibly still being written; try again on the next refresh
+                    tracing::warn!("Could not decompress {}: {}", file_path.display(), e);
+                    continue;
+                }
+                self.parse_jsonl_chunk(&buffer, &project, &mut seen_ids, &mut new_entries, &mut counts);
+                len
+            } else {
+                // Read only the bytes appended since the last parse
+                let mut file = fs::File::open(&file_path).map_err(QStatusError::Io)?;
+                file.seek(SeekFrom::Start(offset)).map_err(QStatusError::Io)?;
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer).map_err(QStatusError::Io)?;
+                offset + self.parse_jsonl_chunk(&buffer, &project, &mut seen_ids, &mut new_entries, &mut counts) as u64
+            };
+            if counts.api_errors > 0 {
+                tracing::debug!("Excluded {} API error message(s) in {}", counts.api_errors, file_path.display());
+            }
+            self.record_malformed_lines(&file_path, counts);
+
+            cache.insert(file_path, CacheEntry {
+                modified,
+                offset: next_offset,
+            });
+        }
+
         // Sort by timestamp
-        all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        new_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+        Ok((new_entries, reset))
+    }
+
+    /// Complete lines skipped as malformed since the cache was last reset
+    pub fn malformed_line_count(&self) -> usize {
+        *self.malformed_lines.lock().unwrap()
+    }
 
-        Ok(all_entries)
+    /// Count skipped lines, warning once per file when too many of them fail
+    fn record_malformed_lines(&self, file_path: &Path, counts: LineCounts) {
+        if counts.malformed == 0 {
+            return;
+        }
+
+        let total = {
+            let mut malformed = self.malformed_lines.lock().unwrap();
+            *malformed += counts.malformed;
+            *malformed
+        };
+        tracing::debug!(
+            "Skipped {} malformed line(s) in {} ({} total)",
+            counts.malformed,
+            file_path.display(),
+            total
+        );
+
+        let ratio = counts.malformed as f64 / counts.total as f64;
+        if ratio > MALFORMED_WARNING_RATIO && self.malformed_warned.lock().unwrap().insert(file_path.to_path_buf()) {
+            tracing::warn!(
+                "{} of {} lines in {} could not be parsed; usage from this file may be incomplete",
+                counts.malformed,
+                counts.total,
+                file_path.display()
+            );
+        }
     }
 
-    /// Group entries into sessions
-    fn group_into_sessions(&self, entries: Vec<ClaudeUsageEntry>) -> Vec<ClaudeSession> {
-        let mut sessions_map: HashMap<String, ClaudeSession> = HashMap::new();
+    /// Parse complete lines from a chunk of JSONL, returning the bytes consumed
+    ///
+    /// A trailing line without a newline is only consumed if it parses, so a
+    /// line still being written is picked up whole on the next refresh.
+    fn parse_jsonl_chunk(
+        &self,
+        buffer: &[u8],
+        project: &str,
+        seen_ids: &mut HashSet<String>,
+        new_entries: &mut Vec<ClaudeUsageEntry>,
+        counts: &mut LineCounts,
+    ) -> usize {
+        let mut consumed = 0;
+
+        for raw_line in buffer.split_inclusive(|&b| b == b'\n') {
+            let complete = raw_line.ends_with(b"\n");
+            let line = String::from_utf8_lossy(raw_line);
+            let line = line.trim();
+
+            if line.is_empty() {
+                consumed += raw_line.len();
+                continue;
+            }
+
+            // Parse JSON line
+            let mut entry = match serde_json::from_str::<ClaudeUsageEntry>(line) {
+                Ok(entry) => entry,
+                Err(_) if !complete => break,
+                Err(_) => {
+                    // User, summary and tool lines are valid JSON without usage;
+                    // only lines that aren't JSON at all count as malformed
+                    counts.total += 1;
+                    if serde_json::from_str::<serde_json::Value>(line).is_err() {
+                        counts.malformed += 1;
+                    }
+                    consumed += raw_line.len();
+                    continue;
+                }
+            };
+            counts.total += 1;
+            consumed += raw_line.len();
+
+            // Skip entries outside the --since / --until window
+            if !self.date_range.is_unbounded() {
+                let in_range = self.parse_timestamp(&entry.timestamp)
+                    .map(|t| self.date_range.contains(&t))
+                    .unwrap_or(false);
+                if !in_range {
+                    continue;
+                }
+            }
+
+            // Skip entries not matching any --model filter
+            if !self.matches_model_filter(&entry) {
+                continue;
+            }
+
+            // Error turns carry tokens that produced nothing useful
+            if self.exclude_api_errors && entry.is_api_error_message == Some(true) {
+                counts.api_errors += 1;
+                continue;
+            }
+
+            // Deduplicate by request ID or message ID, falling back to a hash of the
+            // raw line so distinct messages with the same timestamp and token totals
+            // are never merged while exact duplicates still are
+            let unique_id = entry.request_id.as_ref()
+                .or(entry.message.id.as_ref())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| {
+                    let mut hasher = DefaultHasher::new();
+                    line.hash(&mut hasher);
+                    format!("{}-{}-{:016x}", entry.timestamp, entry.message.usage.total(), hasher.finish())
+                });
+
+            if seen_ids.insert(unique_id) {
+                // Add project directory if not in cwd
+                if entry.cwd.is_none() {
+                    entry.cwd = Some(project.to_string());
+                }
+
+                new_entries.push(entry);
+            }
+        }
+
+        consumed
+    }
+
+    /// Whether an entry's model matches one of the configured filters
+    fn matches_model_filter(&self, entry: &ClaudeUsageEntry) -> bool {
+        if self.model_filters.is_empty() {
+            return true;
+        }
+        let Some(model) = entry.message.model.as_deref() else {
+            return false;
+        };
+        let model = model.to_lowercase();
+        self.model_filters.iter().any(|filter| model.contains(filter.as_str()))
+    }
+
+    /// Merge entries into existing sessions, creating new sessions as needed
+    fn group_into_sessions(&self, existing: Vec<ClaudeSession>, entries: Vec<ClaudeUsageEntry>) -> Vec<ClaudeSession> {
+        let mut sessions_map: HashMap<String, ClaudeSession> = existing
+            .into_iter()
+            .map(|session| (session.id.clone(), session))
+            .collect();
 
         for entry in entries {
-            let session_id = entry.session_id.clone()
-                .unwrap_or_else(|| format!("no-session-{}", entry.timestamp));
+            let session_id = session_key(&entry);
 
             let timestamp = self.parse_timestamp(&entry.timestamp).unwrap_or_else(|_| Utc::now());
 
@@ -360,7 +936,8 @@ impl ClaudeCodeDataSource {
             sessions_map
                 .entry(session_id.clone())
                 .and_modify(|session| {
-                    session.end_time = timestamp;
+                    session.start_time = session.start_time.min(timestamp);
+                    session.end_time = session.end_time.max(timestamp);
                     session.total_tokens.add(&entry.message.usage);
                     session.total_cost += cost;
 
@@ -412,21 +989,75 @@ impl ClaudeCodeDataSource {
         }
 
         let mut sessions: Vec<ClaudeSession> = sessions_map.into_values().collect();
-        sessions.sort_by(|a, b| b.end_time.cmp(&a.end_time));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.end_time));
 
         sessions
     }
 
-    /// Refresh the cache with latest data
-    async fn refresh_cache(&self) -> Result<()> {
-        let entries = self.load_jsonl_files().await?;
-        let sessions = self.group_into_sessions(entries);
+    /// Drop sessions beyond the retention limits, and their entries, keeping their totals
+    ///
+    /// `sessions` must be sorted most recent first, as `group_into_sessions` leaves it.
+    fn prune_sessions(&self, sessions: &mut Vec<ClaudeSession>, entries: &mut Vec<ClaudeUsageEntry>) {
+        let mut keep = self.max_sessions_retained.unwrap_or(usize::MAX).min(sessions.len());
+        if let Some(days) = self.max_history_days {
+            let cutoff = Utc::now() - Duration::days(days);
+            keep = keep.min(sessions.iter().take_while(|s| s.end_time >= cutoff).count());
+        }
+        if keep == sessions.len() {
+            return;
+        }
+
+        let mut pruned = self.pruned.lock().unwrap();
+        for session in sessions.drain(keep..) {
+            pruned.add(&session, |entry| self.calculate_cost(entry));
+        }
+        entries.retain(|entry| !pruned.ids.contains(&session_key(entry)));
+    }
+
+    /// Refresh the cache with data appended since the last refresh
+    ///
+    /// Returns the number of newly loaded entries.
+    pub(crate) async fn refresh_cache(&self) -> Result<usize> {
+        let (new_entries, reset) = self.load_jsonl_files().await?;
+        let new_count = new_entries.len();
+
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let mut sessions = self.sessions.lock().unwrap();
+            if reset {
+                entries.clear();
+                sessions.clear();
+                *self.pruned.lock().unwrap() = PrunedTotals::default();
+            }
+
+            // Sessions already pruned only add to the pruned totals, so they
+            // aren't rebuilt from their newest entries and counted twice
+            let new_entries: Vec<ClaudeUsageEntry> = {
+                let mut pruned = self.pruned.lock().unwrap();
+                new_entries
+                    .into_iter()
+                    .filter(|entry| {
+                        if !pruned.ids.contains(&session_key(entry)) {
+                            return true;
+                        }
+                        pruned.add_entry(entry, self.calculate_cost(entry));
+                        false
+                    })
+                    .collect()
+            };
+
+            if !new_entries.is_empty() {
+                *sessions = self.group_into_sessions(std::mem::take(&mut *sessions), new_entries.clone());
+                entries.extend(new_entries);
+                entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+                self.prune_sessions(&mut sessions, &mut entries);
+            }
+        }
 
-        *self.sessions.lock().unwrap() = sessions;
         *self.needs_refresh.lock().unwrap() = false;
         *self.last_check.lock().unwrap() = Some(std::time::SystemTime::now());
 
-        Ok(())
+        Ok(new_count)
     }
 
     /// Convert Claude session to QConversation
@@ -444,10 +1075,7 @@ impl ClaudeCodeDataSource {
             ])));
 
             // Add assistant message with content if available
-            let content = entry.message.content.as_ref()
-                .and_then(|c| c.first())
-                .and_then(|c| c.text.clone())
-                .unwrap_or_else(|| "...".to_string());
+            let content = entry.message.content_text().unwrap_or_else(|| "...".to_string());
 
             message_pair.push(Value::Object(serde_json::Map::from_iter(vec![
                 ("role".to_string(), Value::String("assistant".to_string())),
@@ -469,12 +1097,21 @@ impl ClaudeCodeDataSource {
         }
     }
 
+    /// Context window for a set of models, using the largest known window
+    fn context_window_for(&self, models: &HashSet<String>) -> u64 {
+        models.iter()
+            .filter_map(|model| self.cost_calculator.get_model_pricing(model).max_tokens)
+            .max()
+            .map(|tokens| tokens as u64)
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+    }
+
     /// Calculate token usage details for a session
     fn calculate_token_usage(&self, session: &ClaudeSession) -> TokenUsageDetails {
         // Check if we have context_tokens set (for active sessions)
         let (total_tokens, history_tokens, context_tokens) = if let Some(ref ctx_tokens) = session.context_tokens {
             // Use the actual context tokens for active sessions
-            let context_total = ctx_tokens.total();
+            let context_total = ctx_tokens.display_total(self.exclude_cache_read);
             let cache_read = ctx_tokens.cache_read_input_tokens.unwrap_or(0) as u64;
             let cache_creation = ctx_tokens.cache_creation_input_tokens.unwrap_or(0) as u64;
 
@@ -482,7 +1119,7 @@ impl ClaudeCodeDataSource {
             (context_total, cache_read, cache_creation)
         } else {
             // For historical sessions, use cumulative totals
-            let total = session.total_tokens.total();
+            let total = session.total_tokens.display_total(self.exclude_cache_read);
             let history = session.total_tokens.input_tokens as u64
                 + session.total_tokens.cache_read_input_tokens.unwrap_or(0) as u64;
             let context = session.total_tokens.output_tokens as u64
@@ -490,15 +1127,14 @@ impl ClaudeCodeDataSource {
             (total, history, context)
         };
 
-        let context_window = 200_000u64; // Claude 3.5 Sonnet context window
+        let context_window = self.context_window_for(&session.models);
         let percentage = (total_tokens as f64 / context_window as f64) * 100.0;
+        let input_tokens = session.total_tokens.input_tokens as u64
+            + session.total_tokens.cache_creation_input_tokens.unwrap_or(0) as u64
+            + session.total_tokens.cache_read_input_tokens.unwrap_or(0) as u64;
+        let output_tokens = session.total_tokens.output_tokens as u64;
 
-        let compaction_status = match percentage {
-            p if p < 70.0 => CompactionStatus::Safe,
-            p if p < 90.0 => CompactionStatus::Warning,
-            p if p < 95.0 => CompactionStatus::Critical,
-            _ => CompactionStatus::Imminent,
-        };
+        let compaction_status = compaction_status_for(percentage, &self.compaction_thresholds);
 
         TokenUsageDetails {
             history_tokens,
@@ -509,6 +1145,8 @@ impl ClaudeCodeDataSource {
             compaction_status,
             has_summary: false,
             message_count: session.entries.len(),
+            input_tokens,
+            output_tokens,
         }
     }
 }
@@ -521,23 +1159,14 @@ impl DataSource for ClaudeCodeDataSource {
 
     async fn has_changed(&mut self) -> Result<bool> {
         // Check if any JSONL files have been modified since last check
-        let paths = self.get_claude_paths()?;
-
-        for base_path in paths {
-            let projects_path = base_path.join("projects");
-            let pattern = projects_path.join("**/*.jsonl");
-
-            let glob_pattern = pattern.to_string_lossy();
-            for entry in glob(&glob_pattern).map_err(|e| QStatusError::Config(format!("Glob pattern error: {}", e)))? {
-                let file_path = entry.map_err(|e| QStatusError::Config(format!("Glob error: {}", e)))?;
-                let metadata = fs::metadata(&file_path).map_err(|e| QStatusError::Io(e))?;
-                let modified = metadata.modified().map_err(|e| QStatusError::Io(e))?;
-
-                if let Some(last_check) = *self.last_check.lock().unwrap() {
-                    if modified > last_check {
-                        *self.needs_refresh.lock().unwrap() = true;
-                        return Ok(true);
-                    }
+        for file_path in self.find_jsonl_files()? {
+            let metadata = fs::metadata(&file_path).map_err(QStatusError::Io)?;
+            let modified = metadata.modified().map_err(QStatusError::Io)?;
+
+            if let Some(last_check) = *self.last_check.lock().unwrap() {
+                if modified > last_check {
+                    *self.needs_refresh.lock().unwrap() = true;
+                    return Ok(true);
                 }
             }
         }
@@ -563,7 +1192,18 @@ impl DataSource for ClaudeCodeDataSource {
         Ok(session.map(|s| self.session_to_conversation(s)))
     }
 
-    async fn get_all_conversation_summaries(&self) -> Result<Vec<ConversationSummary>> {
+    async fn get_conversation_by_id(&self, conversation_id: &str) -> Result<Option<QConversation>> {
+        if *self.needs_refresh.lock().unwrap() {
+            self.refresh_cache().await?;
+        }
+
+        let sessions = self.sessions.lock().unwrap();
+        Ok(sessions.iter()
+            .find(|s| s.id == conversation_id)
+            .map(|s| self.session_to_conversation(s)))
+    }
+
+    async fn get_all_conversation_summaries(&self, _cost_per_1k: f64) -> Result<Vec<ConversationSummary>> {
         if *self.needs_refresh.lock().unwrap() {
             self.refresh_cache().await?;
         }
@@ -580,11 +1220,13 @@ impl DataSource for ClaudeCodeDataSource {
                 token_usage,
                 last_updated: Some(session.end_time.with_timezone(&Local)),
                 json_size_bytes: 0, // Not tracked for Claude Code
+                cost_estimate: session.total_cost,
+                provider: None,
             });
         }
 
         // Sort by total tokens (largest first)
-        summaries.sort_by(|a, b| b.token_usage.total_tokens.cmp(&a.token_usage.total_tokens));
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.token_usage.total_tokens));
 
         Ok(summaries)
     }
@@ -598,11 +1240,11 @@ impl DataSource for ClaudeCodeDataSource {
         let mut result = Vec::new();
 
         let now = Utc::now();
-        let seven_days_ago = now - Duration::days(7);
+        let active_cutoff = now - Duration::days(self.active_session_days);
 
         for session in sessions.iter() {
             let token_usage = self.calculate_token_usage(session);
-            let is_active = session.end_time > seven_days_ago;
+            let is_active = session.end_time > active_cutoff;
 
             result.push(Session {
                 conversation_id: session.id.clone(),
@@ -613,6 +1255,11 @@ impl DataSource for ClaudeCodeDataSource {
                 session_cost: session.total_cost,
                 is_active,
                 has_active_context: !session.entries.is_empty(),
+                invocations: None,
+                model_usage: self.model_usage(session),
+                token_timeline: Vec::new(),
+                context_files: Vec::new(),
+                provider: None,
             });
         }
 
@@ -625,15 +1272,19 @@ impl DataSource for ClaudeCodeDataSource {
         }
 
         let sessions = self.sessions.lock().unwrap();
+        // Sessions pruned from the cache still count towards the totals
+        let pruned = self.pruned.lock().unwrap().clone();
 
-        let total_conversations = sessions.len();
-        let mut total_tokens = 0u64;
-        let mut total_cost = 0.0;
-        let mut total_messages = 0;
+        let total_conversations = sessions.len() + pruned.sessions;
+        let mut total_tokens = pruned.tokens;
+        let mut total_cost = pruned.cost;
+        let mut total_messages = pruned.messages;
         let mut conversations_warning = 0;
         let mut conversations_critical = 0;
         let mut largest_conversation: Option<ConversationSummary> = None;
         let mut largest_tokens = 0u64;
+        let mut cost_by_model = pruned.cost_by_model;
+        let mut tokens_by_model = pruned.tokens_by_model;
 
         for session in sessions.iter() {
             let tokens = session.total_tokens.total();
@@ -641,6 +1292,12 @@ impl DataSource for ClaudeCodeDataSource {
             total_cost += session.total_cost;
             total_messages += session.entries.len();
 
+            for entry in &session.entries {
+                let model = entry.message.model.clone().unwrap_or_else(|| "unknown".to_string());
+                *cost_by_model.entry(model.clone()).or_insert(0.0) += self.calculate_cost(entry);
+                *tokens_by_model.entry(model).or_insert(0) += entry.message.usage.total();
+            }
+
             let token_usage = self.calculate_token_usage(session);
 
             match token_usage.compaction_status {
@@ -657,6 +1314,8 @@ impl DataSource for ClaudeCodeDataSource {
                     token_usage,
                     last_updated: Some(session.end_time.with_timezone(&Local)),
                     json_size_bytes: 0,
+                    cost_estimate: session.total_cost,
+                    provider: None,
                 });
             }
         }
@@ -677,7 +1336,11 @@ impl DataSource for ClaudeCodeDataSource {
             total_cost_estimate: total_cost,
             total_messages,
             message_quota_used: total_messages,
-            message_quota_limit: 5000,
+            message_quota_limit: self.message_quota_limit,
+            cost_by_model,
+            tokens_by_model,
+            // Bad JSONL lines are skipped individually, see `malformed_line_count`
+            parse_failures: 0,
         })
     }
 
@@ -689,7 +1352,7 @@ impl DataSource for ClaudeCodeDataSource {
         let sessions = self.sessions.lock().unwrap();
         let now = Utc::now();
 
-        let today = now.date_naive();
+        let today = self.timezone.date_of(&now);
         let week_start = now - Duration::days(7);
         let month_start = now - Duration::days(30);
         let year_start = now - Duration::days(365);
@@ -707,7 +1370,7 @@ impl DataSource for ClaudeCodeDataSource {
             let tokens = session.total_tokens.total();
             let cost = session.total_cost;
 
-            if session.end_time.date_naive() == today {
+            if self.timezone.date_of(&session.end_time) == today {
                 today_tokens += tokens;
                 today_cost += cost;
             }
@@ -747,7 +1410,7 @@ impl DataSource for ClaudeCodeDataSource {
 
         let sessions = self.sessions.lock().unwrap();
         let now = Utc::now();
-        let seven_days_ago = now - Duration::days(7);
+        let active_cutoff = now - Duration::days(self.active_session_days);
 
         let mut groups: HashMap<String, DirectoryGroup> = HashMap::new();
 
@@ -755,22 +1418,29 @@ impl DataSource for ClaudeCodeDataSource {
             let directory = session.directory.clone().unwrap_or_else(|| session.project.clone());
             let tokens = session.total_tokens.total();
             let cost = session.total_cost;
-            let is_active = session.end_time > seven_days_ago;
+            let is_active = session.end_time > active_cutoff;
             let token_usage = self.calculate_token_usage(session);
 
+            let group_key = self.group_directory(&directory);
+
             let session_data = Session {
                 conversation_id: session.id.clone(),
-                directory: directory.clone(),
+                directory,
                 token_usage,
                 last_activity: session.end_time.with_timezone(&Local),
                 message_count: session.entries.len(),
                 session_cost: cost,
                 is_active,
                 has_active_context: !session.entries.is_empty(),
+                invocations: None,
+                model_usage: self.model_usage(session),
+                token_timeline: Vec::new(),
+                context_files: Vec::new(),
+                provider: None,
             };
 
             groups
-                .entry(directory.clone())
+                .entry(group_key.clone())
                 .and_modify(|group| {
                     group.sessions.push(session_data.clone());
                     group.total_tokens += tokens;
@@ -780,7 +1450,7 @@ impl DataSource for ClaudeCodeDataSource {
                     }
                 })
                 .or_insert_with(|| DirectoryGroup {
-                    directory,
+                    directory: group_key,
                     sessions: vec![session_data],
                     total_tokens: tokens,
                     total_cost: cost,
@@ -789,7 +1459,7 @@ impl DataSource for ClaudeCodeDataSource {
         }
 
         let mut result: Vec<DirectoryGroup> = groups.into_values().collect();
-        result.sort_by(|a, b| b.total_tokens.cmp(&a.total_tokens));
+        result.sort_by_key(|g| std::cmp::Reverse(g.total_tokens));
 
         Ok(result)
     }
@@ -806,14 +1476,47 @@ impl DataSource for ClaudeCodeDataSource {
                 history_tokens: 0,
                 context_tokens: 0,
                 total_tokens: 0,
-                context_window: 200_000,
+                context_window: DEFAULT_CONTEXT_WINDOW,
                 percentage: 0.0,
                 compaction_status: CompactionStatus::Safe,
                 has_summary: false,
                 message_count: 0,
+                input_tokens: 0,
+                output_tokens: 0,
             })
         }
     }
+
+    async fn get_conversation_cost(
+        &self,
+        conversation: &QConversation,
+        usage: &TokenUsageDetails,
+        cost_per_1k: f64,
+    ) -> Result<f64> {
+        // Sessions already carry their per-model cost
+        let sessions = self.sessions.lock().unwrap();
+        Ok(match sessions.iter().find(|s| s.id == conversation.conversation_id) {
+            Some(session) => session.total_cost,
+            None => (usage.total_tokens as f64 / 1000.0) * cost_per_1k,
+        })
+    }
+
+    async fn get_token_timeline(&self, conversation_id: &str) -> Result<Vec<u64>> {
+        let sessions = self.sessions.lock().unwrap();
+        Ok(sessions
+            .iter()
+            .find(|s| s.id == conversation_id)
+            .map(ClaudeSession::token_timeline)
+            .unwrap_or_default())
+    }
+
+    async fn get_last_activity(&self, conversation_id: &str) -> Result<Option<DateTime<Local>>> {
+        let sessions = self.sessions.lock().unwrap();
+        Ok(sessions
+            .iter()
+            .find(|s| s.id == conversation_id)
+            .map(|s| s.end_time.with_timezone(&Local)))
+    }
 }
 
 impl Default for ClaudeCodeDataSource {
@@ -3,10 +3,41 @@
 
 #[cfg(test)]
 mod tests {
-    use super::super::claude_datasource::ClaudeCodeDataSource;
+    use super::super::claude_datasource::{ClaudeCodeDataSource, ClaudeTokenUsage};
     use super::super::datasource::DataSource;
+    use crate::app::config::{AppConfig, DirGroupMode};
+    use crate::utils::session_blocks::BlockStatus;
+    use chrono::{Local, TimeZone};
     use tempfile::TempDir;
     use std::fs;
+    use std::sync::{Mutex, MutexGuard};
+
+    // Tests mutate CLAUDE_CONFIG_DIR/HOME, so they must not run concurrently
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn env_lock() -> MutexGuard<'static, ()> {
+        ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Write JSONL data into a temp Claude dir and load a data source from it
+    fn load_source(jsonl_data: &str) -> (TempDir, ClaudeCodeDataSource) {
+        load_source_with_config(jsonl_data, &AppConfig::default())
+    }
+
+    fn load_source_with_config(jsonl_data: &str, config: &AppConfig) -> (TempDir, ClaudeCodeDataSource) {
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join("claude");
+        let project_dir = claude_dir.join("projects").join("test-project");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(project_dir.join("usage.jsonl"), jsonl_data).unwrap();
+
+        let _guard = env_lock();
+        std::env::set_var("CLAUDE_CONFIG_DIR", claude_dir.to_str().unwrap());
+        let data_source = ClaudeCodeDataSource::with_config(config).unwrap();
+        std::env::remove_var("CLAUDE_CONFIG_DIR");
+
+        (temp_dir, data_source)
+    }
 
     fn create_test_jsonl_data() -> String {
         r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-1"},"costUSD":0.001,"requestId":"req-1","cwd":"/test/project"}
@@ -30,6 +61,7 @@ mod tests {
         fs::write(&jsonl_file, create_test_jsonl_data()).unwrap();
 
         // Set environment variable to use our test directory
+        let guard = env_lock();
         std::env::set_var("CLAUDE_CONFIG_DIR", claude_dir.to_str().unwrap());
 
         // Create data source - this should load the data
@@ -37,6 +69,7 @@ mod tests {
 
         // Clean up environment variable
         std::env::remove_var("CLAUDE_CONFIG_DIR");
+        drop(guard);
 
         // Check that it loaded successfully
         assert!(result.is_ok(), "Failed to create ClaudeCodeDataSource: {:?}", result.err());
@@ -44,14 +77,23 @@ mod tests {
         let data_source = result.unwrap();
 
         // Test getting conversation summaries
-        let summaries = data_source.get_all_conversation_summaries().await.unwrap();
+        let summaries = data_source.get_all_conversation_summaries(0.0).await.unwrap();
         assert_eq!(summaries.len(), 2, "Should have 2 sessions");
+        // Summaries carry each session's summed JSONL cost
+        let summary_cost: f64 = summaries.iter().map(|s| s.cost_estimate).sum();
+        assert!((summary_cost - 0.008).abs() < 1e-9);
 
         // Test getting global stats
         let stats = data_source.get_global_stats(0.0).await.unwrap();
         assert_eq!(stats.total_conversations, 2);
         assert_eq!(stats.total_messages, 3); // 3 unique messages (one duplicate)
 
+        // Per-model breakdown uses the JSONL costs
+        assert_eq!(stats.tokens_by_model.get("claude-3-5-sonnet-20241022"), Some(&525));
+        assert_eq!(stats.tokens_by_model.get("claude-3-opus-20240229"), Some(&225));
+        let opus_cost = stats.cost_by_model.get("claude-3-opus-20240229").unwrap();
+        assert!((opus_cost - 0.005).abs() < 1e-9);
+
         // Test getting all sessions
         let sessions = data_source.get_all_sessions(0.0).await.unwrap();
         assert_eq!(sessions.len(), 2);
@@ -81,11 +123,13 @@ mod tests {
         let jsonl_file = project_dir.join("usage.jsonl");
         fs::write(&jsonl_file, jsonl_data).unwrap();
 
+        let guard = env_lock();
         std::env::set_var("CLAUDE_CONFIG_DIR", claude_dir.to_str().unwrap());
 
         let data_source = ClaudeCodeDataSource::new().unwrap();
 
         std::env::remove_var("CLAUDE_CONFIG_DIR");
+        drop(guard);
 
         // Get session and check calculated cost
         let sessions = data_source.get_all_sessions(0.0).await.unwrap();
@@ -100,6 +144,7 @@ mod tests {
     #[tokio::test]
     async fn test_no_data_directory() {
         // Save current env var if exists
+        let _guard = env_lock();
         let original_env = std::env::var("CLAUDE_CONFIG_DIR").ok();
         let original_home = std::env::var("HOME").ok();
 
@@ -148,11 +193,13 @@ mod tests {
         let jsonl_file = project_dir.join("usage.jsonl");
         fs::write(&jsonl_file, jsonl_data).unwrap();
 
+        let guard = env_lock();
         std::env::set_var("CLAUDE_CONFIG_DIR", claude_dir.to_str().unwrap());
 
         let data_source = ClaudeCodeDataSource::new().unwrap();
 
         std::env::remove_var("CLAUDE_CONFIG_DIR");
+        drop(guard);
 
         let sessions = data_source.get_all_sessions(0.0).await.unwrap();
         assert_eq!(sessions.len(), 1);
@@ -160,4 +207,608 @@ mod tests {
         let session = &sessions[0];
         assert_eq!(session.message_count, 2, "Should have 2 messages after deduplication");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_context_window_follows_model() {
+        let jsonl_data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":1000,"output_tokens":500},"model":"claude-instant-1.2","id":"msg-1"},"requestId":"req-1"}
+{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-2","message":{"usage":{"input_tokens":1000,"output_tokens":500},"model":"claude-instant-1.2","id":"msg-2"},"requestId":"req-2"}
+{"timestamp":"2024-01-15T10:01:00Z","sessionId":"session-2","message":{"usage":{"input_tokens":1000,"output_tokens":500},"model":"claude-3-5-sonnet-20241022","id":"msg-3"},"requestId":"req-3"}"#;
+
+        let (_temp_dir, data_source) = load_source(jsonl_data);
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+
+        // Single small-window model uses its own limit
+        let instant = sessions.iter().find(|s| s.conversation_id == "session-1").unwrap();
+        assert_eq!(instant.token_usage.context_window, 100_000);
+        assert!((instant.token_usage.percentage - 1.5).abs() < 0.001);
+
+        // Mixed models use the largest window
+        let mixed = sessions.iter().find(|s| s.conversation_id == "session-2").unwrap();
+        assert_eq!(mixed.token_usage.context_window, 200_000);
+    }
+
+    #[tokio::test]
+    async fn test_session_blocks_and_active_block() {
+        let now = chrono::Utc::now();
+        let recent_a = (now - chrono::Duration::minutes(10)).to_rfc3339();
+        let recent_b = (now - chrono::Duration::minutes(5)).to_rfc3339();
+        let jsonl_data = format!(
+            r#"{{"timestamp":"2024-01-15T10:00:00Z","sessionId":"old-session","message":{{"usage":{{"input_tokens":5000,"output_tokens":5000}},"model":"claude-3-5-sonnet-20241022","id":"msg-1"}},"requestId":"req-1"}}
+{{"timestamp":"{}","sessionId":"session-a","message":{{"usage":{{"input_tokens":100,"output_tokens":50}},"model":"claude-3-5-sonnet-20241022","id":"msg-2"}},"requestId":"req-2"}}
+{{"timestamp":"{}","sessionId":"session-b","message":{{"usage":{{"input_tokens":200,"output_tokens":100,"cache_read_input_tokens":300}},"model":"claude-3-5-sonnet-20241022","id":"msg-3"}},"requestId":"req-3"}}"#,
+            recent_a, recent_b
+        );
+
+        let (_temp_dir, data_source) = load_source(&jsonl_data);
+
+        // Old block, gap block, then the current block
+        let blocks = data_source.get_session_blocks().unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert!(blocks[1].is_gap);
+        assert!(!blocks[0].is_active);
+        assert!(blocks[2].is_active);
+        assert_eq!(blocks[2].entries.len(), 2);
+
+        // Active session covers the whole billing block, across session IDs
+        let active = data_source.get_active_session().await.unwrap().unwrap();
+        assert_eq!(active.id, "session-b");
+        assert_eq!(active.entries.len(), 2);
+        assert_eq!(active.total_tokens.total(), 750);
+        assert_eq!(active.context_tokens.unwrap().total(), 500);
+    }
+
+    #[tokio::test]
+    async fn test_date_range_filters_entries() {
+        let jsonl_data = r#"{"timestamp":"2024-01-10T10:00:00Z","sessionId":"before","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-1"},"requestId":"req-1"}
+{"timestamp":"2024-01-15T10:00:00Z","sessionId":"inside","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-2"},"requestId":"req-2"}
+{"timestamp":"2024-01-20T10:00:00Z","sessionId":"after","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-3"},"requestId":"req-3"}"#;
+
+        let config = AppConfig {
+            since: Some(Local.with_ymd_and_hms(2024, 1, 12, 0, 0, 0).unwrap()),
+            until: Some(Local.with_ymd_and_hms(2024, 1, 18, 0, 0, 0).unwrap()),
+            ..AppConfig::default()
+        };
+        let (_temp_dir, data_source) = load_source_with_config(jsonl_data, &config);
+
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].conversation_id, "inside");
+
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_messages, 1);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_refresh_parses_only_appended_lines() {
+        let jsonl_data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-1"},"requestId":"req-1"}
+{"timestamp":"2024-01-15T10:01:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":200,"output_tokens":100},"model":"claude-3-5-sonnet-20241022","id":"msg-2"},"requestId":"req-2"}
+"#;
+        let (temp_dir, data_source) = load_source(jsonl_data);
+        let file = temp_dir.path().join("claude/projects/test-project/usage.jsonl");
+
+        // Nothing changed: nothing re-parsed
+        assert_eq!(data_source.refresh_cache().await.unwrap(), 0);
+
+        // Append one new line plus a duplicate of an existing request
+        let mut appended = fs::read_to_string(&file).unwrap();
+        appended.push_str(r#"{"timestamp":"2024-01-15T10:02:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":300,"output_tokens":150},"model":"claude-3-5-sonnet-20241022","id":"msg-3"},"requestId":"req-3"}
+{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-1"},"requestId":"req-1"}
+"#);
+        fs::write(&file, appended).unwrap();
+
+        assert_eq!(data_source.refresh_cache().await.unwrap(), 1);
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].message_count, 3);
+
+        // A truncated file invalidates the cache and is re-parsed from scratch
+        fs::write(&file, jsonl_data.lines().next().unwrap()).unwrap();
+        assert_eq!(data_source.refresh_cache().await.unwrap(), 1);
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+        assert_eq!(sessions[0].message_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_dedup_keeps_distinct_messages() {
+        // No request or message IDs, same timestamp and token totals, different content
+        let jsonl_data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","content":[{"text":"first"}]}}
+{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","content":[{"text":"second"}]}}
+{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","content":[{"text":"second"}]}}"#;
+
+        let (_temp_dir, data_source) = load_source(jsonl_data);
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+
+        // Both distinct messages survive; the exact duplicate is still dropped
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].message_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_conversation_by_id() {
+        let jsonl_data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022"},"requestId":"req-1"}
+{"timestamp":"2024-01-15T11:00:00Z","sessionId":"session-2","message":{"usage":{"input_tokens":200,"output_tokens":80},"model":"claude-3-5-sonnet-20241022"},"requestId":"req-2"}"#;
+
+        let (_temp_dir, data_source) = load_source(jsonl_data);
+
+        let pinned = data_source.get_conversation_by_id("session-1").await.unwrap();
+        assert_eq!(pinned.map(|c| c.conversation_id).as_deref(), Some("session-1"));
+        assert!(data_source.get_conversation_by_id("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_active_session_window_is_configurable() {
+        let three_days_ago = (chrono::Utc::now() - chrono::Duration::days(3)).to_rfc3339();
+        let jsonl_data = format!(
+            r#"{{"timestamp":"{}","sessionId":"session-1","message":{{"usage":{{"input_tokens":100,"output_tokens":50}},"model":"claude-3-5-sonnet-20241022"}},"requestId":"req-1"}}"#,
+            three_days_ago
+        );
+
+        let (_temp_dir, data_source) = load_source(&jsonl_data);
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+        assert!(sessions[0].is_active);
+
+        let config = AppConfig {
+            active_session_days: 2,
+            ..AppConfig::default()
+        };
+        let (_temp_dir, data_source) = load_source_with_config(&jsonl_data, &config);
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+        assert!(!sessions[0].is_active);
+
+        let groups = data_source.get_directory_groups(0.0).await.unwrap();
+        assert_eq!(groups[0].active_session_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_model_filters_or_together() {
+        let jsonl_data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-opus-4-20250514"},"requestId":"req-1"}
+{"timestamp":"2024-01-15T10:01:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":200,"output_tokens":50},"model":"claude-sonnet-4-20250514"},"requestId":"req-2"}
+{"timestamp":"2024-01-15T10:02:00Z","sessionId":"session-2","message":{"usage":{"input_tokens":300,"output_tokens":50},"model":"claude-3-5-haiku-20241022"},"requestId":"req-3"}"#;
+
+        let config = AppConfig {
+            model_filters: vec!["Opus".to_string(), "haiku".to_string()],
+            ..AppConfig::default()
+        };
+        let (_temp_dir, data_source) = load_source_with_config(jsonl_data, &config);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_tokens, 150 + 350);
+        assert!(!stats.tokens_by_model.contains_key("claude-sonnet-4-20250514"));
+
+        // No matches yields zeros rather than errors
+        let config = AppConfig {
+            model_filters: vec!["gpt".to_string()],
+            ..AppConfig::default()
+        };
+        let (_temp_dir, data_source) = load_source_with_config(jsonl_data, &config);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_tokens, 0);
+        assert_eq!(stats.average_tokens, 0);
+        assert!(data_source.get_all_sessions(0.0).await.unwrap().is_empty());
+        let periods = data_source.get_period_metrics(0.0).await.unwrap();
+        assert_eq!(periods.year_tokens, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cost_usd_accepts_strings_and_clamps_negatives() {
+        let jsonl_data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-1"},"costUSD":"0.0123","requestId":"req-1"}
+{"timestamp":"2024-01-15T10:01:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"msg-2"},"costUSD":-5.0,"requestId":"req-2"}"#;
+
+        let config = AppConfig {
+            cost_mode: "display".to_string(),
+            ..AppConfig::default()
+        };
+        let (_temp_dir, data_source) = load_source_with_config(jsonl_data, &config);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+
+        // Both lines are kept; the string is parsed and the negative counts as zero
+        assert_eq!(stats.total_messages, 2);
+        assert!((stats.total_cost_estimate - 0.0123).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decode_project_name() {
+        use super::super::claude_datasource::decode_project_name;
+
+        assert_eq!(decode_project_name("-Users-me-code-foo"), "/Users/me/code/foo");
+        assert_eq!(decode_project_name("-home-dev"), "/home/dev");
+        assert_eq!(decode_project_name("C--work-foo"), "C:/work/foo");
+        // Not an encoded path: left alone
+        assert_eq!(decode_project_name("test-project"), "test-project");
+        assert_eq!(decode_project_name("unknown"), "unknown");
+    }
+
+    #[test]
+    fn test_cost_mode_changes_totals() {
+        // One entry with a JSONL cost, one without
+        let jsonl_data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":1000000,"output_tokens":0},"model":"claude-3-5-sonnet-20241022"},"costUSD":1.0,"requestId":"req-1"}
+{"timestamp":"2024-01-15T10:01:00Z","sessionId":"session-1","message":{"usage":{"input_tokens":1000000,"output_tokens":0},"model":"claude-3-5-sonnet-20241022"},"requestId":"req-2"}"#;
+
+        let total_for = |mode: &str| {
+            let config = AppConfig {
+                cost_mode: mode.to_string(),
+                ..AppConfig::default()
+            };
+            let (_temp_dir, data_source) = load_source_with_config(jsonl_data, &config);
+            futures::executor::block_on(data_source.get_global_stats(0.0))
+                .unwrap()
+                .total_cost_estimate
+        };
+
+        // Sonnet input is $3 per million tokens
+        assert!((total_for("calculate") - 6.0).abs() < 1e-6);
+        assert!((total_for("display") - 1.0).abs() < 1e-6);
+        assert!((total_for("auto") - 4.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_model_usage_splits_a_session_by_model() {
+        let data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"s","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-3-5-sonnet-20241022","id":"m1"},"costUSD":0.001,"requestId":"r1"}
+{"timestamp":"2024-01-15T10:05:00Z","sessionId":"s","message":{"usage":{"input_tokens":300,"output_tokens":100},"model":"claude-3-opus-20240229","id":"m2"},"costUSD":0.009,"requestId":"r2"}"#;
+        let (_temp_dir, data_source) = load_source(data);
+
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        let usage = &sessions[0].model_usage;
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].model, "claude-3-opus-20240229");
+        assert_eq!(usage[0].tokens, 400);
+        assert!((usage[0].cost - 0.009).abs() < 1e-9);
+        assert_eq!(usage[1].tokens, 150);
+    }
+
+    #[tokio::test]
+    async fn test_stale_entries_report_idle() {
+        let (_temp_dir, data_source) = load_source(&create_test_jsonl_data());
+
+        assert!(data_source.get_active_session().await.unwrap().is_none());
+        match data_source.block_status().unwrap() {
+            BlockStatus::Idle { last_activity } => {
+                assert_eq!(last_activity.to_rfc3339(), "2024-01-15T11:00:00+00:00");
+            }
+            other => panic!("expected idle, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_counts_skipped_malformed_lines() {
+        let data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"s","message":{"usage":{"input_tokens":100,"output_tokens":50},"id":"m1"},"requestId":"r1"}
+{"timestamp":"2024-01-15T10:01:00Z","sessionId":"s","mess
+not json at all
+{"timestamp":"2024-01-15T10:02:00Z","sessionId":"s","message":{"usage":{"input_tokens":100,"output_tokens":50},"id":"m2"},"requestId":"r2"}
+{"timestamp":"2024-01-15T10:03:00Z","sessionId":"s","message":{"usage""#;
+        let (_temp_dir, data_source) = load_source(data);
+
+        // The half-written last line is still pending, not malformed
+        assert_eq!(data_source.malformed_line_count(), 2);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_messages, 2);
+    }
+
+    #[tokio::test]
+    async fn test_conversation_cost_uses_session_cost_not_flat_rate() {
+        let (_temp_dir, data_source) = load_source(&create_test_jsonl_data());
+
+        let conversation = data_source.get_conversation_by_id("session-2").await.unwrap().unwrap();
+        let usage = data_source.get_token_usage(&conversation).await.unwrap();
+        let cost = data_source.get_conversation_cost(&conversation, &usage, 1.0).await.unwrap();
+
+        let sessions = data_source.get_all_sessions(1.0).await.unwrap();
+        let session = sessions.iter().find(|s| s.conversation_id == "session-2").unwrap();
+        assert!((cost - session.session_cost).abs() < 1e-12);
+        assert!((cost - 0.005).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_lines_without_usage_are_not_malformed() {
+        let data = r#"{"type":"summary","summary":"Add health check","leafUuid":"u-1"}
+{"type":"user","timestamp":"2024-01-15T10:00:00Z","sessionId":"s","message":{"role":"user","content":"add a health check"}}
+{"timestamp":"2024-01-15T10:01:00Z","sessionId":"s","message":{"usage":{"input_tokens":100,"output_tokens":50},"id":"m1"},"requestId":"r1"}
+{"type":"user","timestamp":"2024-01-15T10:02:00Z","sessionId":"s","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}
+"#;
+        let (_temp_dir, data_source) = load_source(data);
+
+        assert_eq!(data_source.malformed_line_count(), 0);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_messages, 1);
+    }
+
+    #[test]
+    fn test_display_total_can_exclude_cache_reads() {
+        let usage = ClaudeTokenUsage {
+            input_tokens: 100,
+            output_tokens: 50,
+            cache_creation_input_tokens: Some(20),
+            cache_read_input_tokens: Some(1_000),
+        };
+        assert_eq!(usage.display_total(false), 1_170);
+        assert_eq!(usage.display_total(true), 170);
+        assert_eq!(usage.total(), 1_170);
+    }
+
+    #[tokio::test]
+    async fn test_exclude_cache_read_changes_percentage_not_cost() {
+        let data = r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"s","message":{"usage":{"input_tokens":1000,"output_tokens":0,"cache_read_input_tokens":9000},"model":"claude-3-5-sonnet-20241022","id":"m1"},"requestId":"r1"}"#;
+        let session_for = |exclude_cache_read_from_total| async move {
+            let config = AppConfig { exclude_cache_read_from_total, ..AppConfig::default() };
+            let (_temp_dir, data_source) = load_source_with_config(data, &config);
+            data_source.get_all_sessions(0.0).await.unwrap().remove(0)
+        };
+
+        let counted = session_for(false).await;
+        let excluded = session_for(true).await;
+        assert_eq!(counted.token_usage.total_tokens, 10_000);
+        assert_eq!(excluded.token_usage.total_tokens, 1_000);
+        assert!(excluded.token_usage.percentage < counted.token_usage.percentage);
+        assert_eq!(excluded.session_cost, counted.session_cost);
+    }
+
+    #[tokio::test]
+    async fn test_group_by_git_root_merges_repo_subdirectories() {
+        let workspace = TempDir::new().unwrap();
+        let repo = workspace.path().join("repo");
+        let other = workspace.path().join("scratch");
+        for dir in [repo.join(".git"), repo.join("src"), repo.join("tests"), other.clone()] {
+            fs::create_dir_all(dir).unwrap();
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let line = |session: &str, cwd: &std::path::Path| {
+            format!(
+                r#"{{"timestamp":"{}","sessionId":"{}","message":{{"usage":{{"input_tokens":100,"output_tokens":50}},"model":"claude-3-5-sonnet-20241022","id":"msg-{}"}},"requestId":"req-{}","cwd":"{}"}}"#,
+                now, session, session, session, cwd.display()
+            )
+        };
+        let jsonl_data = [
+            line("s1", &repo.join("src")),
+            line("s2", &repo.join("tests")),
+            line("s3", &other),
+        ]
+        .join("\n");
+
+        let (_temp_dir, data_source) = load_source(&jsonl_data);
+        assert_eq!(data_source.get_directory_groups(0.0).await.unwrap().len(), 3);
+
+        let config = AppConfig {
+            group_by: DirGroupMode::GitRoot,
+            ..AppConfig::default()
+        };
+        let (_temp_dir, data_source) = load_source_with_config(&jsonl_data, &config);
+        let groups = data_source.get_directory_groups(0.0).await.unwrap();
+        assert_eq!(groups.len(), 2);
+
+        let repo_group = groups.iter().find(|g| g.directory == repo.to_string_lossy()).unwrap();
+        assert_eq!(repo_group.sessions.len(), 2);
+        assert_eq!(repo_group.total_tokens, 300);
+        // Sessions keep their own working directory
+        assert!(repo_group.sessions.iter().any(|s| s.directory.ends_with("src")));
+
+        // Directories outside any repository are grouped as-is
+        assert!(groups.iter().any(|g| g.directory == other.to_string_lossy()));
+    }
+
+    #[tokio::test]
+    async fn test_session_token_timeline_is_cumulative() {
+        let (_temp_dir, data_source) = load_source(&create_test_jsonl_data());
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+
+        let session = sessions.iter().find(|s| s.conversation_id == "session-1").unwrap();
+        // Built only on request, not for every session in the list
+        assert!(session.token_timeline.is_empty());
+        let timeline = data_source.get_token_timeline("session-1").await.unwrap();
+        assert_eq!(timeline, vec![150, 525]);
+        assert_eq!(*timeline.last().unwrap(), session.token_usage.total_tokens);
+        assert!(data_source.get_token_timeline("no-such-session").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gzipped_archives_match_plain_jsonl() {
+        use std::io::Write;
+
+        let summarize = |sessions: Vec<crate::data::database::Session>| {
+            let mut summary: Vec<(String, u64, usize)> = sessions
+                .into_iter()
+                .map(|s| (s.conversation_id, s.token_usage.total_tokens, s.message_count))
+                .collect();
+            summary.sort();
+            summary
+        };
+        let (_plain_dir, plain) = load_source(&create_test_jsonl_data());
+        let expected = summarize(plain.get_all_sessions(0.0).await.unwrap());
+
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join("claude");
+        let project_dir = claude_dir.join("projects").join("test-project");
+        fs::create_dir_all(&project_dir).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(
+            fs::File::create(project_dir.join("archive.jsonl.gz")).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(create_test_jsonl_data().as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let load = |config: &AppConfig| {
+            let _guard = env_lock();
+            std::env::set_var("CLAUDE_CONFIG_DIR", claude_dir.to_str().unwrap());
+            let data_source = ClaudeCodeDataSource::with_config(config).unwrap();
+            std::env::remove_var("CLAUDE_CONFIG_DIR");
+            data_source
+        };
+
+        let compressed = load(&AppConfig::default());
+        assert_eq!(summarize(compressed.get_all_sessions(0.0).await.unwrap()), expected);
+
+        // The same entries in a plain file alongside the archive are not counted twice
+        fs::write(project_dir.join("usage.jsonl"), create_test_jsonl_data()).unwrap();
+        let both = load(&AppConfig::default());
+        assert_eq!(summarize(both.get_all_sessions(0.0).await.unwrap()), expected);
+
+        // Archives are skipped when disabled
+        fs::remove_file(project_dir.join("usage.jsonl")).unwrap();
+        let config = AppConfig { read_compressed: false, ..AppConfig::default() };
+        assert!(load(&config).get_all_sessions(0.0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_multi_block_content_is_kept_in_conversation() {
+        let jsonl_data = [
+            r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"blocks","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-sonnet-4","id":"msg-1","content":[{"type":"thinking","thinking":"hmm"},{"type":"text","text":"Reading the config."},{"type":"tool_use","id":"tu-1","name":"Read","input":{"path":"a.rs"}},{"type":"text","text":"Done."}]},"requestId":"req-1"}"#,
+            r#"{"timestamp":"2024-01-15T10:01:00Z","sessionId":"blocks","message":{"usage":{"input_tokens":10,"output_tokens":5},"model":"claude-sonnet-4","id":"msg-2","content":"Plain string reply"},"requestId":"req-2"}"#,
+            r#"{"timestamp":"2024-01-15T10:02:00Z","sessionId":"blocks","message":{"usage":{"input_tokens":10,"output_tokens":5},"model":"claude-sonnet-4","id":"msg-3"},"requestId":"req-3"}"#,
+        ]
+        .join("\n");
+        let (_temp_dir, data_source) = load_source(&jsonl_data);
+        assert_eq!(data_source.malformed_line_count(), 0);
+
+        let conversation = data_source.get_conversation_by_id("blocks").await.unwrap().unwrap();
+        let replies: Vec<&str> = conversation.history.iter()
+            .map(|pair| pair[1]["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(replies, ["Reading the config.\n[tool: Read]\nDone.", "Plain string reply", "..."]);
+    }
+
+    #[tokio::test]
+    async fn test_api_error_messages_are_excluded_by_default() {
+        let jsonl_data = [
+            r#"{"timestamp":"2024-01-15T10:00:00Z","sessionId":"s1","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-sonnet-4","id":"msg-1"},"costUSD":0.01,"requestId":"req-1"}"#,
+            r#"{"timestamp":"2024-01-15T10:01:00Z","sessionId":"s1","message":{"usage":{"input_tokens":400,"output_tokens":0},"model":"claude-sonnet-4","id":"msg-2"},"costUSD":0.02,"requestId":"req-2","isApiErrorMessage":true}"#,
+            r#"{"timestamp":"2024-01-15T10:02:00Z","sessionId":"s1","message":{"usage":{"input_tokens":10,"output_tokens":5},"model":"claude-sonnet-4","id":"msg-3"},"costUSD":0.001,"requestId":"req-3","isApiErrorMessage":false}"#,
+        ]
+        .join("\n");
+
+        let (_temp_dir, data_source) = load_source(&jsonl_data);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_messages, 2);
+        assert_eq!(stats.total_tokens, 165);
+        assert!((stats.total_cost_estimate - 0.011).abs() < 1e-9);
+
+        let config = AppConfig { exclude_api_errors: false, ..AppConfig::default() };
+        let (_temp_dir, data_source) = load_source_with_config(&jsonl_data, &config);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_messages, 3);
+        assert_eq!(stats.total_tokens, 565);
+        assert!((stats.total_cost_estimate - 0.031).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_session_retention_keeps_totals_of_pruned_sessions() {
+        let jsonl_data: Vec<String> = (0..10)
+            .map(|i| format!(
+                r#"{{"timestamp":"2024-01-15T10:{:02}:00Z","sessionId":"s{}","message":{{"usage":{{"input_tokens":100,"output_tokens":50}},"model":"claude-sonnet-4","id":"msg-{}"}},"costUSD":0.01,"requestId":"req-{}"}}"#,
+                i, i, i, i
+            ))
+            .collect();
+        let jsonl_data = jsonl_data.join("\n");
+
+        let config = AppConfig { max_sessions_retained: Some(3), ..AppConfig::default() };
+        let (temp_dir, data_source) = load_source_with_config(&jsonl_data, &config);
+
+        // Only the most recent sessions stay in the cache
+        let sessions = data_source.get_all_sessions(0.0).await.unwrap();
+        let mut ids: Vec<&str> = sessions.iter().map(|s| s.conversation_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["s7", "s8", "s9"]);
+
+        // Totals still cover every loaded session
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_conversations, 10);
+        assert_eq!(stats.total_messages, 10);
+        assert_eq!(stats.total_tokens, 1500);
+        assert!((stats.total_cost_estimate - 0.1).abs() < 1e-9);
+        assert_eq!(stats.tokens_by_model.get("claude-sonnet-4"), Some(&1500));
+
+        // A late line for a pruned session adds to the totals without reviving it
+        let file = temp_dir.path().join("claude/projects/test-project/usage.jsonl");
+        let mut appended = fs::read_to_string(&file).unwrap();
+        appended.push_str(r#"
+{"timestamp":"2024-01-15T09:00:00Z","sessionId":"s0","message":{"usage":{"input_tokens":100,"output_tokens":50},"model":"claude-sonnet-4","id":"msg-late"},"costUSD":0.01,"requestId":"req-late"}
+"#);
+        fs::write(&file, appended).unwrap();
+        assert_eq!(data_source.refresh_cache().await.unwrap(), 1);
+
+        assert_eq!(data_source.get_all_sessions(0.0).await.unwrap().len(), 3);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_conversations, 10);
+        assert_eq!(stats.total_messages, 11);
+        assert_eq!(stats.total_tokens, 1650);
+        assert!((stats.total_cost_estimate - 0.11).abs() < 1e-9);
+
+        // Pruned sessions' entries are dropped from memory too
+        assert_eq!(data_source.get_session_blocks().unwrap().iter().map(|b| b.entries.len()).sum::<usize>(), 3);
+
+        // Sessions idle past max_history_days are pruned as well
+        let config = AppConfig { max_history_days: Some(30), ..AppConfig::default() };
+        let (_temp_dir, data_source) = load_source_with_config(&jsonl_data, &config);
+        assert!(data_source.get_all_sessions(0.0).await.unwrap().is_empty());
+        assert_eq!(data_source.get_global_stats(0.0).await.unwrap().total_tokens, 1500);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_overlapping_roots_are_read_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join("claude");
+        let project_dir = claude_dir.join("projects").join("test-project");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(project_dir.join("usage.jsonl"), create_test_jsonl_data()).unwrap();
+        let alias = temp_dir.path().join("claude-alias");
+        std::os::unix::fs::symlink(&claude_dir, &alias).unwrap();
+
+        let guard = env_lock();
+        std::env::set_var("CLAUDE_CONFIG_DIR", format!("{},{}", claude_dir.display(), alias.display()));
+        let roots = ClaudeCodeDataSource::get_claude_paths().unwrap();
+        let data_source = ClaudeCodeDataSource::new().unwrap();
+        std::env::remove_var("CLAUDE_CONFIG_DIR");
+        drop(guard);
+
+        assert_eq!(roots, vec![claude_dir]);
+        let stats = data_source.get_global_stats(0.0).await.unwrap();
+        assert_eq!(stats.total_conversations, 2);
+        assert_eq!(stats.total_messages, 3);
+        assert_eq!(stats.total_tokens, 750);
+    }
+
+    #[test]
+    fn test_project_allowlist_and_depth_limit_scanning() {
+        let line = |session: &str, id: &str| format!(
+            r#"{{"timestamp":"2024-01-15T10:00:00Z","sessionId":"{}","message":{{"usage":{{"input_tokens":100,"output_tokens":50}},"model":"claude-sonnet-4","id":"{}"}},"requestId":"{}"}}"#,
+            session, id, id
+        );
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join("claude");
+        let projects = claude_dir.join("projects");
+        fs::create_dir_all(projects.join("-work-api").join("nested")).unwrap();
+        fs::create_dir_all(projects.join("-work-web")).unwrap();
+        fs::write(projects.join("-work-api").join("a.jsonl"), line("api", "msg-1")).unwrap();
+        fs::write(projects.join("-work-api").join("nested").join("b.jsonl"), line("api-nested", "msg-2")).unwrap();
+        fs::write(projects.join("-work-web").join("c.jsonl"), line("web", "msg-3")).unwrap();
+
+        let session_ids = |config: AppConfig| {
+            let _guard = env_lock();
+            std::env::set_var("CLAUDE_CONFIG_DIR", claude_dir.to_str().unwrap());
+            let data_source = ClaudeCodeDataSource::with_config(&config).unwrap();
+            std::env::remove_var("CLAUDE_CONFIG_DIR");
+            let sessions = futures::executor::block_on(data_source.get_all_sessions(0.0)).unwrap();
+            let mut ids: Vec<String> = sessions.into_iter().map(|s| s.conversation_id).collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(session_ids(AppConfig::default()), ["api", "api-nested", "web"]);
+
+        // Either the encoded directory name or the decoded path selects a project
+        for allowed in ["-work-api", "/work/api"] {
+            let config = AppConfig { claude_projects_allowlist: vec![allowed.to_string()], ..AppConfig::default() };
+            assert_eq!(session_ids(config), ["api", "api-nested"]);
+        }
+
+        let config = AppConfig { claude_max_glob_depth: Some(2), ..AppConfig::default() };
+        assert_eq!(session_ids(config), ["api", "web"]);
+
+        let config = AppConfig {
+            claude_projects_allowlist: vec!["-work-api".to_string()],
+            claude_max_glob_depth: Some(2),
+            ..AppConfig::default()
+        };
+        assert_eq!(session_ids(config), ["api"]);
+    }
+}
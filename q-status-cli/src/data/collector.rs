@@ -2,32 +2,107 @@
 // Runs in separate thread to avoid blocking UI
 
 use crate::app::state::{AppEvent, AppState, TokenSnapshot};
+use crate::data::claude_datasource::ClaudeCodeDataSource;
 use crate::data::database::QDatabase;
 use crate::data::datasource::DataSource;
+use crate::data::factory::{DataSourceFactory, DataSourceType};
+use crate::data::merged_datasource::MergedDataSource;
 use crate::utils::error::Result;
+use crate::utils::notifications;
 use crossbeam_channel::Sender;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::time::interval;
+use std::time::{Duration, Instant};
+use tokio::time::{interval, interval_at};
 use chrono::Local;
 
+/// Longest wait between attempts to re-open a vanished data source
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Unchanged polls in a row before the interval starts to stretch
+const IDLE_TICKS_BEFORE_BACKOFF: u32 = 5;
+
+/// Stretches the poll interval while the source keeps reporting no changes
+///
+/// After `IDLE_TICKS_BEFORE_BACKOFF` quiet polls the interval doubles on each
+/// further quiet poll, up to `max`, and drops back to `base` on any change.
+#[derive(Debug, Clone)]
+struct IdleBackoff {
+    base: Duration,
+    max: Duration,
+    idle_ticks: u32,
+    current: Duration,
+}
+
+impl IdleBackoff {
+    fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max: max.max(base), idle_ticks: 0, current: base }
+    }
+
+    /// Record one poll and return the interval to wait before the next
+    fn record(&mut self, changed: bool) -> Duration {
+        if changed {
+            self.idle_ticks = 0;
+            self.current = self.base;
+        } else {
+            self.idle_ticks = self.idle_ticks.saturating_add(1);
+            if self.idle_ticks > IDLE_TICKS_BEFORE_BACKOFF {
+                self.current = (self.current * 2).min(self.max);
+            }
+        }
+        self.current
+    }
+}
+
+/// Re-creates the data source after it becomes unavailable
+type ReopenFn = Box<dyn Fn() -> Result<Box<dyn DataSource>> + Send + Sync>;
+
 pub struct DataCollector {
     state: Arc<AppState>,
     database: Box<dyn DataSource>,
     event_tx: Sender<AppEvent>,
     _file_watcher: Option<notify::RecommendedWatcher>,  // Prefixed with _ to indicate intentionally unused
+    reopen: Option<ReopenFn>,
+    reconnect_backoff: Duration,
+    next_reconnect: Instant,
+    idle_backoff: IdleBackoff,
 }
 
 impl DataCollector {
     pub fn new(state: Arc<AppState>, database: Box<dyn DataSource>, event_tx: Sender<AppEvent>) -> Result<Self> {
+        let reconnect_backoff = Duration::from_secs(state.config.refresh_rate.max(1));
+        let idle_backoff = IdleBackoff::new(reconnect_backoff, Duration::from_secs(state.config.max_idle_interval));
         Ok(Self {
             state,
             database,
             event_tx,
             _file_watcher: None,
+            reopen: None,
+            reconnect_backoff,
+            next_reconnect: Instant::now(),
+            idle_backoff,
         })
     }
 
+    /// Re-open the data source with this function when it disappears
+    pub fn with_reopen(
+        mut self,
+        reopen: impl Fn() -> Result<Box<dyn DataSource>> + Send + Sync + 'static,
+    ) -> Self {
+        self.reopen = Some(Box::new(reopen));
+        self
+    }
+
+    /// Re-open through the factory, e.g. after Amazon Q rotates its database
+    pub fn with_factory_reopen(self, source_type: DataSourceType) -> Self {
+        let config = self.state.config.clone();
+        self.with_reopen(move || DataSourceFactory::create(source_type, &config))
+    }
+
+    /// How often to check for changes, from the configured refresh rate (at least 1s)
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.state.config.refresh_rate.max(1))
+    }
+
     pub fn start_file_watching(&mut self) -> Result<()> {
         // File watching is specific to QDatabase implementation
         // For now, we'll skip it when using the trait abstraction
@@ -38,29 +113,102 @@ impl DataCollector {
     pub async fn run(mut self) {
         // Start file watching
         if let Err(e) = self.start_file_watching() {
-            eprintln!("Failed to start file watching: {}", e);
+            self.state.log_warning(format!("Failed to start file watching: {}", e));
         }
 
-        // Polling interval
-        let mut ticker = interval(Duration::from_secs(2));
+        // Polling interval, stretched while nothing changes
+        let mut period = self.poll_interval();
+        let mut ticker = interval(period);
 
-        loop {
-            ticker.tick().await;
+        let refresh_requested = self.state.refresh_requested.clone();
 
-            // Check for database changes
-            match self.database.has_changed().await {
-                Ok(true) => {
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => self.poll().await,
+                _ = refresh_requested.notified() => {
+                    // Manual refresh bypasses change detection
                     if let Err(e) = self.collect_data().await {
-                        eprintln!("Data collection error: {}", e);
+                        self.state.log_error(format!("Data collection error: {}", e));
                     }
+                    self.idle_backoff.record(true);
                 }
-                Ok(false) => {
-                    // No changes, skip collection
+            }
+
+            let next = self.idle_backoff.current;
+            if next != period {
+                period = next;
+                ticker = interval_at(tokio::time::Instant::now() + period, period);
+            }
+        }
+    }
+
+    /// One polling step: re-open a vanished source, or collect if data changed
+    async fn poll(&mut self) {
+        if self.state.get_source_error().is_some() && self.reopen.is_some() {
+            if Instant::now() >= self.next_reconnect {
+                self.try_reconnect().await;
+            }
+            return;
+        }
+
+        // Check for database changes
+        match self.database.has_changed().await {
+            Ok(changed) => {
+                // The data is current as of this check even when nothing changed,
+                // so an idle source doesn't read as stale
+                *self.state.last_refresh.lock().unwrap() = chrono::Local::now();
+
+                // A source without a reopen function may come back by itself
+                let recovered = self.state.get_source_error().is_some();
+                if recovered {
+                    self.state.set_source_error(None);
                 }
-                Err(e) => {
-                    eprintln!("Database check error: {}", e);
-                    *self.state.is_connected.lock().unwrap() = false;
+                if changed || recovered {
+                    if let Err(e) = self.collect_data().await {
+                        self.state.log_error(format!("Data collection error: {}", e));
+                    }
                 }
+                self.idle_backoff.record(changed || recovered);
+            }
+            Err(e) if e.is_source_unavailable() => {
+                *self.state.is_connected.lock().unwrap() = false;
+                self.state.log_warning(format!("Data source unavailable: {}", e));
+                self.state.set_source_error(Some(format!("Data source unavailable: {}", e)));
+                self.next_reconnect = Instant::now() + self.reconnect_backoff;
+            }
+            Err(e) => {
+                self.state.log_error(format!("Database check error: {}", e));
+                *self.state.is_connected.lock().unwrap() = false;
+            }
+        }
+    }
+
+    /// Try to re-open the data source, backing off further on each failure
+    async fn try_reconnect(&mut self) -> bool {
+        let Some(reopen) = self.reopen.as_ref() else {
+            return false;
+        };
+
+        match reopen() {
+            Ok(database) => {
+                self.database = database;
+                self.state.set_source_error(None);
+                self.reconnect_backoff = self.poll_interval();
+                if let Err(e) = self.collect_data().await {
+                    self.state.log_error(format!("Data collection error: {}", e));
+                }
+                true
+            }
+            Err(e) => {
+                self.reconnect_backoff = (self.reconnect_backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                self.next_reconnect = Instant::now() + self.reconnect_backoff;
+                self.state.log_warning(format!("Reconnect failed: {}", e));
+                self.state.set_source_error(Some(format!(
+                    "Data source unavailable: {} (retrying in {}s)",
+                    e,
+                    self.reconnect_backoff.as_secs()
+                )));
+                false
             }
         }
     }
@@ -70,13 +218,40 @@ impl DataCollector {
         *self.state.is_connected.lock().unwrap() = true;
         
         // Collect ALL conversations for global view
-        let all_summaries = self.database.get_all_conversation_summaries().await?;
+        let all_summaries = self.database.get_all_conversation_summaries(self.state.config.cost_per_1k_tokens).await?;
         *self.state.all_conversations.lock().unwrap() = all_summaries.clone();
 
         // Collect session-level data
         let all_sessions = self.database.get_all_sessions(self.state.config.cost_per_1k_tokens).await?;
         *self.state.all_sessions.lock().unwrap() = all_sessions.clone();
 
+        // Flag sessions whose cost jumped since the last collection
+        for (conversation_id, previous, current) in self.state.record_session_costs(&all_sessions) {
+            tracing::info!("Cost spike in {}: ${:.2} -> ${:.2}", conversation_id, previous, current);
+            if self.state.config.notifications_enabled {
+                let config = &self.state.config;
+                notifications::notify_cost_spike(&conversation_id, &config.format_cost(previous), &config.format_cost(current));
+            }
+        }
+
+        // Only the session open in the detail view needs its token chart
+        let viewing = matches!(*self.state.view_mode.lock().unwrap(), crate::app::state::ViewMode::SessionDetail);
+        let selected_id = self.state.selected_session.lock().unwrap().as_ref().map(|s| s.conversation_id.clone());
+        if let (true, Some(id)) = (viewing, selected_id) {
+            // A missing chart shouldn't hold back the rest of the refresh
+            let timeline = match self.database.get_token_timeline(&id).await {
+                Ok(timeline) => timeline,
+                Err(e) => {
+                    tracing::warn!("Failed to load token timeline for {}: {}", id, e);
+                    Vec::new()
+                }
+            };
+            // The selection may have moved on while the timeline was loading
+            if let Some(session) = self.state.selected_session.lock().unwrap().as_mut().filter(|s| s.conversation_id == id) {
+                session.token_timeline = timeline;
+            }
+        }
+
         // Collect grouped sessions
         let directory_groups = self.database.get_directory_groups(self.state.config.cost_per_1k_tokens).await?;
         *self.state.directory_groups.lock().unwrap() = directory_groups.clone();
@@ -95,43 +270,78 @@ impl DataCollector {
 
         // Update active Claude session if using Claude data source
         let data_source = self.state.get_active_data_source();
-        if matches!(data_source, crate::data::DataSourceType::ClaudeCode) {
+        if matches!(data_source, crate::data::DataSourceType::ClaudeCode | crate::data::DataSourceType::Merged) {
             // Try to downcast to ClaudeCodeDataSource to get active session
-            if let Some(claude_source) = self.database.as_any().downcast_ref::<crate::data::claude_datasource::ClaudeCodeDataSource>() {
+            if let Some(claude_source) = self.claude_source() {
                 if let Ok(active_session) = claude_source.get_active_session().await {
+                    self.state.cost_analysis.lock().unwrap().cache_savings =
+                        active_session.as_ref().map(|s| claude_source.cache_savings(s));
                     self.state.set_active_claude_session(active_session);
                 }
+                if let Ok(blocks) = claude_source.get_session_blocks() {
+                    let active_block = blocks.iter().find(|b| b.is_active && !b.is_gap).cloned();
+                    self.state.set_active_claude_block(active_block);
+                    *self.state.claude_blocks.lock().unwrap() = blocks;
+                }
             }
         }
 
-        // Also get latest conversation (most recently modified)
-        let conversation = self.database.get_current_conversation(None).await?;
+        // Follow the pinned conversation if it still exists, else the latest one
+        let pinned = match self.state.get_pinned_conversation() {
+            Some(id) => self.database.get_conversation_by_id(&id).await?,
+            None => None,
+        };
+        let conversation = match pinned {
+            Some(conv) => Some(conv),
+            None => self.database.get_current_conversation(None).await?,
+        };
+
+        // Rate the burn rate is priced at; the current conversation's own when there is one
+        let cost_per_1k = self.state.config.cost_per_1k_tokens;
+        let mut burn_cost_per_1k = cost_per_1k;
 
         if let Some(conv) = conversation {
             // Get detailed token usage
-            let usage_details = self.database.get_token_usage(&conv).await.unwrap_or_else(|_| {
+            let usage_details = self.database.get_token_usage(&conv).await.unwrap_or(
                 // Fallback to empty details if there's an error
                 crate::data::database::TokenUsageDetails {
                     history_tokens: 0,
                     context_tokens: 0,
                     total_tokens: 0,
-                    context_window: 175_000,
+                    context_window: self.state.config.amazon_q_context_window(),
                     percentage: 0.0,
                     compaction_status: crate::data::database::CompactionStatus::Safe,
                     has_summary: false,
                     message_count: 0,
+                    input_tokens: 0,
+                    output_tokens: 0,
                 }
-            });
+            );
             
             // Update state with detailed information
             self.state.update_token_usage_details(usage_details.clone());
+
+            // Notify only when the status escalates, not on every tick
+            let escalated = self.state.record_compaction_status(usage_details.compaction_status.clone());
+            if escalated && self.state.config.notifications_enabled {
+                notifications::notify_threshold(
+                    &usage_details.compaction_status,
+                    usage_details.percentage,
+                    &conv.conversation_id,
+                );
+            }
             
             // Update conversation ID
             *self.state.current_conversation.lock().unwrap() = Some(conv.conversation_id.clone());
 
-            // Calculate costs
-            let cost_per_1k = self.state.config.cost_per_1k_tokens;
-            let session_cost = (usage_details.total_tokens as f64 / 1000.0) * cost_per_1k;
+            // Price the conversation the way the source prices its session list
+            let session_cost = self.database
+                .get_conversation_cost(&conv, &usage_details, cost_per_1k)
+                .await
+                .unwrap_or((usage_details.total_tokens as f64 / 1000.0) * cost_per_1k);
+            if usage_details.total_tokens > 0 {
+                burn_cost_per_1k = session_cost / usage_details.total_tokens as f64 * 1000.0;
+            }
 
             let mut cost = self.state.cost_analysis.lock().unwrap();
             cost.session_cost = session_cost;
@@ -151,31 +361,44 @@ impl DataCollector {
                 history_tokens: 0,
                 context_tokens: 0,
                 total_tokens: 0,
-                context_window: 175_000,  // Effective limit before compaction
+                context_window: self.state.config.amazon_q_context_window(),
                 percentage: 0.0,
                 compaction_status: crate::data::database::CompactionStatus::Safe,
                 has_summary: false,
                 message_count: 0,
+                input_tokens: 0,
+                output_tokens: 0,
             };
             self.state.update_token_usage_details(empty_details);
             *self.state.current_conversation.lock().unwrap() = None;
         }
 
         // Calculate burn rate
-        self.calculate_burn_rate();
+        self.calculate_burn_rate(burn_cost_per_1k);
         
         Ok(())
     }
     
-    fn calculate_burn_rate(&self) {
+    /// The Claude Code source, directly or from within a merged source
+    fn claude_source(&self) -> Option<&ClaudeCodeDataSource> {
+        let any = self.database.as_any();
+        any.downcast_ref::<ClaudeCodeDataSource>()
+            .or_else(|| any.downcast_ref::<MergedDataSource>()?.source_as::<ClaudeCodeDataSource>())
+    }
+
+    fn calculate_burn_rate(&self, cost_per_1k: f64) {
         const EMA_ALPHA: f64 = 0.3;  // Same as menubar app specification
         
-        let all_sessions = self.state.all_sessions.lock().unwrap();
-        let total_tokens: u64 = all_sessions.iter().map(|s| s.token_usage.total_tokens).sum();
+        let total_tokens = self.state.total_session_tokens();
         
         let mut burn_rate = self.state.burn_rate.lock().unwrap();
         let now = Local::now();
         
+        // After a long break the old momentum would inflate the rate until it decays
+        if burn_rate.is_stale(now, self.state.config.burn_rate_reset_minutes) {
+            burn_rate.reset(total_tokens, now);
+        }
+        
         // Calculate instant rate using time since last update
         let time_since_last = now.signed_duration_since(burn_rate.last_update);
         let minutes_elapsed = time_since_last.num_seconds() as f64 / 60.0;
@@ -198,7 +421,7 @@ impl DataCollector {
         
         // Update the displayed rate to use the smoothed EMA value
         burn_rate.tokens_per_minute = burn_rate.ema_tokens_per_minute;
-        burn_rate.cost_per_minute = (burn_rate.ema_tokens_per_minute / 1000.0) * self.state.config.cost_per_1k_tokens;
+        burn_rate.cost_per_minute = (burn_rate.ema_tokens_per_minute / 1000.0) * cost_per_1k;
         
         // Update tracking values for next calculation
         burn_rate.last_update = now;
@@ -210,8 +433,8 @@ impl DataCollector {
             total_tokens,
         });
         
-        // Keep only last 10 minutes of snapshots for sparkline
-        while burn_rate.snapshots.len() > 10 {
+        // Keep only the configured number of snapshots for the sparkline
+        while burn_rate.snapshots.len() > self.state.config.burn_rate_history_len {
             burn_rate.snapshots.pop_front();
         }
     }
@@ -225,7 +448,8 @@ pub fn spawn_collector(
     let database = QDatabase::new()?;
     let database_box: Box<dyn DataSource> = Box::new(database);
 
-    let collector = DataCollector::new(state, database_box, event_tx)?;
+    let collector = DataCollector::new(state, database_box, event_tx)?
+        .with_factory_reopen(DataSourceType::AmazonQ);
 
     Ok(tokio::spawn(async move {
         collector.run().await;
@@ -236,10 +460,159 @@ pub fn spawn_collector_with_datasource(
     state: Arc<AppState>,
     event_tx: Sender<AppEvent>,
     datasource: Box<dyn DataSource>,
+    source_type: DataSourceType,
 ) -> Result<tokio::task::JoinHandle<()>> {
-    let collector = DataCollector::new(state, datasource, event_tx)?;
+    let collector = DataCollector::new(state, datasource, event_tx)?
+        .with_factory_reopen(source_type);
 
     Ok(tokio::spawn(async move {
         collector.run().await;
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::AppConfig;
+    use crate::data::datasource::MockDataSource;
+    use crossbeam_channel::bounded;
+
+    fn collector_with_refresh_rate(refresh_rate: u64) -> DataCollector {
+        let config = AppConfig {
+            refresh_rate,
+            ..AppConfig::default()
+        };
+        let (tx, _rx) = bounded(1);
+        DataCollector::new(Arc::new(AppState::new(config)), Box::new(MockDataSource::new()), tx).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_source_disappears() {
+        use crate::utils::error::QStatusError;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (tx, _rx) = bounded(10);
+        let state = Arc::new(AppState::new(AppConfig::default()));
+        let gone = MockDataSource { unavailable: true, ..MockDataSource::new() };
+
+        // First re-open fails, the second finds the database again
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let mut collector = DataCollector::new(state.clone(), Box::new(gone), tx)
+            .unwrap()
+            .with_reopen(move || match counter.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(QStatusError::DatabaseNotFound),
+                _ => Ok(Box::new(MockDataSource::new()) as Box<dyn DataSource>),
+            });
+
+        collector.poll().await;
+        assert!(state.get_source_error().is_some());
+        assert!(!*state.is_connected.lock().unwrap());
+
+        // Backoff doubles from the 2s refresh rate
+        assert!(!collector.try_reconnect().await);
+        assert!(state.get_source_error().unwrap().contains("retrying in 4s"));
+
+        assert!(collector.try_reconnect().await);
+        assert_eq!(state.get_source_error(), None);
+        assert!(*state.is_connected.lock().unwrap());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_idle_backoff_stretches_and_snaps_back() {
+        let mut backoff = IdleBackoff::new(Duration::from_secs(2), Duration::from_secs(30));
+
+        for _ in 0..IDLE_TICKS_BEFORE_BACKOFF {
+            assert_eq!(backoff.record(false), Duration::from_secs(2));
+        }
+        assert_eq!(backoff.record(false), Duration::from_secs(4));
+        assert_eq!(backoff.record(false), Duration::from_secs(8));
+        assert_eq!(backoff.record(false), Duration::from_secs(16));
+        assert_eq!(backoff.record(false), Duration::from_secs(30));
+        assert_eq!(backoff.record(false), Duration::from_secs(30));
+
+        // Any change returns to the base interval and restarts the count
+        assert_eq!(backoff.record(true), Duration::from_secs(2));
+        assert_eq!(backoff.record(false), Duration::from_secs(2));
+
+        // A max below the base disables backoff
+        let mut fixed = IdleBackoff::new(Duration::from_secs(10), Duration::from_secs(5));
+        for _ in 0..10 {
+            assert_eq!(fixed.record(false), Duration::from_secs(10));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_current_conversation_cost_matches_blended_session_list() {
+        use crate::data::database::{QDatabase, Q_PRICING_MODEL};
+        use crate::utils::cost_calculator::CostCalculator;
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(r#"
+            CREATE TABLE conversations (key TEXT PRIMARY KEY, value TEXT);
+            INSERT INTO conversations VALUES ('/work/api', '{"conversation_id": "conv-api", "history": [[{"role": "user", "content": "Add a health check endpoint"}, {"role": "assistant", "content": "Added GET /health returning 200 with a JSON body and a test covering it"}]]}');
+        "#).unwrap();
+        let pricing = CostCalculator::new().get_model_pricing(Q_PRICING_MODEL);
+        let database = QDatabase::from_connection(conn)
+            .with_approximate_tokens(true)
+            .with_blended_pricing(Some(pricing));
+
+        let (tx, _rx) = bounded(10);
+        let state = Arc::new(AppState::new(AppConfig::default()));
+        let mut collector = DataCollector::new(state.clone(), Box::new(database), tx).unwrap();
+        collector.collect_data().await.unwrap();
+
+        let session_cost = state.all_sessions.lock().unwrap()[0].session_cost;
+        let flat = state.token_usage.lock().unwrap().used as f64 / 1000.0 * state.config.cost_per_1k_tokens;
+        assert!((state.cost_analysis.lock().unwrap().session_cost - session_cost).abs() < 1e-12);
+        assert!((session_cost - flat).abs() > 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_token_timeline_only_for_the_session_in_detail_view() {
+        use crate::app::state::ViewMode;
+        use crate::data::database::{GlobalStats, Session};
+
+        let source = MockDataSource {
+            global_stats: Some(GlobalStats::default()),
+            token_timeline: vec![150, 525],
+            ..MockDataSource::new()
+        };
+        let (tx, _rx) = bounded(10);
+        let state = Arc::new(AppState::new(AppConfig::default()));
+        let mut collector = DataCollector::new(state.clone(), Box::new(source), tx).unwrap();
+        *state.selected_session.lock().unwrap() = Some(Session::test("/work/api", 525, 0.0));
+
+        *state.view_mode.lock().unwrap() = ViewMode::SessionList;
+        collector.collect_data().await.unwrap();
+        assert!(state.selected_session.lock().unwrap().as_ref().unwrap().token_timeline.is_empty());
+
+        *state.view_mode.lock().unwrap() = ViewMode::SessionDetail;
+        collector.collect_data().await.unwrap();
+        assert_eq!(state.selected_session.lock().unwrap().as_ref().unwrap().token_timeline, vec![150, 525]);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_poll_still_counts_as_fresh() {
+        let mut collector = collector_with_refresh_rate(2);
+        let long_ago = chrono::Local::now() - chrono::Duration::hours(1);
+        *collector.state.last_refresh.lock().unwrap() = long_ago;
+
+        collector.poll().await;
+        assert!(*collector.state.last_refresh.lock().unwrap() > long_ago);
+
+        // A failed check leaves the old stamp so the stale warning shows
+        *collector.state.last_refresh.lock().unwrap() = long_ago;
+        collector.database = Box::new(MockDataSource { unavailable: true, ..MockDataSource::new() });
+        collector.poll().await;
+        assert_eq!(*collector.state.last_refresh.lock().unwrap(), long_ago);
+    }
+
+    #[test]
+    fn test_poll_interval_follows_refresh_rate() {
+        assert_eq!(collector_with_refresh_rate(10).poll_interval(), Duration::from_secs(10));
+        // Zero would spin the loop, so it is raised to one second
+        assert_eq!(collector_with_refresh_rate(0).poll_interval(), Duration::from_secs(1));
+    }
+}
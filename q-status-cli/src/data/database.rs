@@ -1,17 +1,24 @@
 // ABOUTME: Read-only interface to Amazon Q's SQLite database
 // Handles platform-specific paths and JSON conversation parsing
 
+use crate::app::config::AppConfig;
+use crate::utils::date_range::DateRange;
 use crate::utils::error::{QStatusError, Result};
+use crate::utils::cost_calculator::ModelPricing;
+use crate::utils::timezone::DisplayZone;
+use crate::utils::tokenizer;
 use rusqlite::{Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Local, Duration, TimeZone};
 use async_trait::async_trait;
 use super::datasource::DataSource;
+use super::factory::DataSourceType;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CompactionStatus {
     Safe,      // < 70%
     Warning,   // 70-90%
@@ -19,6 +26,83 @@ pub enum CompactionStatus {
     Imminent,  // > 95%
 }
 
+impl CompactionStatus {
+    /// Status marker: colored emoji, or an ASCII tag when color is disabled
+    pub fn indicator(&self, color_enabled: bool) -> &'static str {
+        match (self, color_enabled) {
+            (CompactionStatus::Safe, true) => "🟢",
+            (CompactionStatus::Warning, true) => "🟡",
+            (CompactionStatus::Critical, true) => "🟠",
+            (CompactionStatus::Imminent, true) => "🔴",
+            (CompactionStatus::Safe, false) => "[OK]",
+            (CompactionStatus::Warning, false) => "[WARN]",
+            (CompactionStatus::Critical, false) => "[CRIT]",
+            (CompactionStatus::Imminent, false) => "[!!!]",
+        }
+    }
+}
+
+/// Context-usage percentages at which each compaction status begins
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompactionThresholds {
+    pub warning: f64,
+    pub critical: f64,
+    pub imminent: f64,
+}
+
+impl Default for CompactionThresholds {
+    fn default() -> Self {
+        Self {
+            warning: 70.0,
+            critical: 90.0,
+            imminent: 95.0,
+        }
+    }
+}
+
+/// Who wrote a message in a Q conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageRole {
+    User,
+    Assistant,
+}
+
+/// Role of a history message: an explicit `role`, else Q's own message shape
+/// (prompts and tool results come from the user, responses and tool calls from
+/// the assistant), else its position in the `[user, assistant]` pair
+fn message_role(message: &Value, index_in_pair: usize) -> MessageRole {
+    match message.get("role").and_then(Value::as_str) {
+        Some("user" | "human") => return MessageRole::User,
+        Some("assistant") => return MessageRole::Assistant,
+        _ => {}
+    }
+
+    if message.get("Response").is_some() || message.get("ToolUse").is_some() {
+        return MessageRole::Assistant;
+    }
+    if let Some(content) = message.get("content") {
+        if content.get("Prompt").is_some() || content.get("ToolUseResults").is_some() {
+            return MessageRole::User;
+        }
+    }
+
+    if index_in_pair == 0 {
+        MessageRole::User
+    } else {
+        MessageRole::Assistant
+    }
+}
+
+/// Classify a context-usage percentage against the thresholds
+pub fn compaction_status_for(percentage: f64, thresholds: &CompactionThresholds) -> CompactionStatus {
+    match percentage {
+        p if p < thresholds.warning => CompactionStatus::Safe,
+        p if p < thresholds.critical => CompactionStatus::Warning,
+        p if p < thresholds.imminent => CompactionStatus::Critical,
+        _ => CompactionStatus::Imminent,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TokenUsageDetails {
     pub history_tokens: u64,
@@ -29,6 +113,10 @@ pub struct TokenUsageDetails {
     pub compaction_status: CompactionStatus,
     pub has_summary: bool,
     pub message_count: usize,
+    /// Tokens sent to the model: user prompts for Amazon Q, all input for Claude
+    pub input_tokens: u64,
+    /// Tokens the model generated: assistant replies
+    pub output_tokens: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +126,8 @@ pub struct ConversationSummary {
     pub token_usage: TokenUsageDetails,
     pub last_updated: Option<DateTime<Local>>,
     pub json_size_bytes: usize,
+    pub cost_estimate: f64,
+    pub provider: Option<DataSourceType>,  // Set only when sources are merged
 }
 
 #[derive(Debug, Clone)]
@@ -48,8 +138,130 @@ pub struct Session {
     pub last_activity: DateTime<Local>,
     pub message_count: usize,
     pub session_cost: f64,
-    pub is_active: bool,  // Within last 7 days
+    pub is_active: bool,  // Within the configured active window (default 7 days)
     pub has_active_context: bool,  // Has context files loaded
+    pub invocations: Option<usize>,  // `q` runs in this directory (Amazon Q only)
+    pub model_usage: Vec<ModelUsage>,  // Per-model split, most expensive first (Claude only)
+    pub token_timeline: Vec<u64>,  // Running token total per message, only for the session in detail view (Claude only)
+    pub context_files: Vec<String>,  // Files attached through Q's context manager
+    pub provider: Option<DataSourceType>,  // Set only when sources are merged
+}
+
+#[cfg(test)]
+impl Session {
+    /// Active session fixture with one message, named after its directory
+    pub fn test(directory: &str, tokens: u64, cost: f64) -> Self {
+        Self {
+            conversation_id: directory.to_string(),
+            directory: directory.to_string(),
+            token_usage: TokenUsageDetails {
+                history_tokens: tokens,
+                context_tokens: 0,
+                total_tokens: tokens,
+                context_window: 200_000,
+                percentage: 0.0,
+                compaction_status: CompactionStatus::Safe,
+                has_summary: false,
+                message_count: 1,
+                input_tokens: 0,
+                output_tokens: 0,
+            },
+            last_activity: Local::now(),
+            message_count: 1,
+            session_cost: cost,
+            is_active: true,
+            has_active_context: false,
+            invocations: None,
+            model_usage: Vec::new(),
+            token_timeline: Vec::new(),
+            context_files: Vec::new(),
+            provider: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl ConversationSummary {
+    /// Conversation list fixture with no timestamp or cost
+    pub fn test(conversation_id: &str, path: &str, tokens: u64) -> Self {
+        Self {
+            path: path.to_string(),
+            conversation_id: conversation_id.to_string(),
+            token_usage: TokenUsageDetails {
+                history_tokens: tokens,
+                context_tokens: 0,
+                total_tokens: tokens,
+                context_window: 200_000,
+                percentage: 0.0,
+                compaction_status: CompactionStatus::Safe,
+                has_summary: false,
+                message_count: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+            },
+            last_updated: None,
+            json_size_bytes: 0,
+            cost_estimate: 0.0,
+            provider: None,
+        }
+    }
+}
+
+/// Tokens and cost one model contributed to a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelUsage {
+    pub model: String,
+    pub tokens: u64,
+    pub cost: f64,
+    pub output_cost: f64,  // Part of `cost` spent on output tokens
+}
+
+/// Input vs output tokens and how much of the cost the output accounts for
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IoSplit {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+    pub output_cost: f64,
+}
+
+impl IoSplit {
+    /// Split for one session. Claude prices output per model; Amazon Q charges a
+    /// flat rate, so output's share of the cost is its share of the tokens.
+    pub fn for_session(session: &Session) -> Self {
+        let input_tokens = session.token_usage.input_tokens;
+        let output_tokens = session.token_usage.output_tokens;
+        let output_cost = if session.model_usage.is_empty() {
+            let total = input_tokens + output_tokens;
+            if total == 0 { 0.0 } else { session.session_cost * output_tokens as f64 / total as f64 }
+        } else {
+            session.model_usage.iter().map(|usage| usage.output_cost).sum()
+        };
+        Self { input_tokens, output_tokens, cost: session.session_cost, output_cost }
+    }
+
+    /// Combined split across sessions
+    pub fn total<'a>(sessions: impl IntoIterator<Item = &'a Session>) -> Self {
+        sessions.into_iter().map(Self::for_session).fold(Self::default(), |acc, split| Self {
+            input_tokens: acc.input_tokens + split.input_tokens,
+            output_tokens: acc.output_tokens + split.output_tokens,
+            cost: acc.cost + split.cost,
+            output_cost: acc.output_cost + split.output_cost,
+        })
+    }
+
+    /// Output tokens per input token, None without input
+    pub fn ratio(&self) -> Option<f64> {
+        (self.input_tokens > 0).then(|| self.output_tokens as f64 / self.input_tokens as f64)
+    }
+
+    /// Percentage of the cost spent on output tokens
+    pub fn output_cost_percentage(&self) -> f64 {
+        if self.cost <= 0.0 {
+            return 0.0;
+        }
+        (self.output_cost / self.cost * 100.0).min(100.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -61,7 +273,7 @@ pub struct DirectoryGroup {
     pub active_session_count: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GlobalStats {
     pub total_conversations: usize,
     pub total_tokens: u64,
@@ -73,6 +285,21 @@ pub struct GlobalStats {
     pub total_messages: usize,
     pub message_quota_used: usize,
     pub message_quota_limit: usize,  // 5000 per month
+    pub cost_by_model: HashMap<String, f64>,  // Empty when the source has no model data
+    pub tokens_by_model: HashMap<String, u64>,
+    /// Conversations skipped because their JSON didn't match the expected schema
+    pub parse_failures: usize,
+}
+
+impl GlobalStats {
+    /// Share of the monthly message quota used, capped so it never reads 100% early
+    pub fn message_quota_percentage(&self) -> f64 {
+        if self.message_quota_limit == 0 {
+            return 0.0;
+        }
+        let pct = (self.message_quota_used as f64 / self.message_quota_limit as f64) * 100.0;
+        if pct >= 100.0 { 100.0 } else if pct > 99.9 { 99.9 } else { pct }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -141,15 +368,153 @@ pub struct QConversation {
     pub latest_summary: Option<String>,  // For compaction tracking
 }
 
+impl QConversation {
+    /// Context file paths attached to the conversation, in order, without duplicates
+    ///
+    /// Q's context manager keeps `paths` lists under its global and profile
+    /// configs; older versions put them at the top level or in `context_files`
+    /// (as paths or `[path, content]` pairs). Unknown shapes yield no files.
+    pub fn context_files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        if let Some(manager) = &self.context_manager {
+            collect_context_paths(manager, &mut files);
+        }
+        files
+    }
+}
+
+fn collect_context_paths(value: &Value, files: &mut Vec<String>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+
+    for (key, child) in map {
+        match (key.as_str(), child) {
+            ("paths" | "context_files", Value::Array(items)) => {
+                for item in items {
+                    let path = match item {
+                        Value::String(path) => Some(path.as_str()),
+                        Value::Array(pair) => pair.first().and_then(Value::as_str),
+                        _ => None,
+                    };
+                    if let Some(path) = path {
+                        if !files.iter().any(|f| f == path) {
+                            files.push(path.to_string());
+                        }
+                    }
+                }
+            }
+            (_, Value::Object(_)) => collect_context_paths(child, files),
+            _ => {}
+        }
+    }
+}
+
+/// Effective context window before Q compacts a conversation
+/// (the actual window is larger, see `AppConfig::amazon_q_actual_limit`)
+pub const Q_CONTEXT_WINDOW: u64 = 175_000;
+
+/// `context_message_length` above this cannot all be loaded at once, so it is
+/// assumed to be a cumulative count rather than the current context size.
+/// Anything that fits in the context window is taken at face value.
+const CUMULATIVE_CONTEXT_THRESHOLD: u64 = Q_CONTEXT_WINDOW;
+
+/// Model whose rates price Amazon Q usage when the blended rate is auto-derived
+pub const Q_PRICING_MODEL: &str = "claude-sonnet-4";
+
+/// Per-1k rate for a mix of input and output tokens at the model's rates
+///
+/// None when there are no tokens to weigh or the pricing lacks either rate.
+pub fn blended_cost_per_1k(pricing: &ModelPricing, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+    let total = input_tokens + output_tokens;
+    if total == 0 {
+        return None;
+    }
+    let input_rate = pricing.input_cost_per_token?;
+    let output_rate = pricing.output_cost_per_token?;
+    let cost = input_tokens as f64 * input_rate + output_tokens as f64 * output_rate;
+    Some(cost / total as f64 * 1000.0)
+}
+
+/// Current context estimate used when the reported length is cumulative
+/// (roughly a handful of loaded files)
+const CUMULATIVE_CONTEXT_FALLBACK: u64 = 20_000;
+
+/// Context tokens currently loaded, given Q's reported context length
+fn effective_context_tokens(raw_context_tokens: u64) -> u64 {
+    if raw_context_tokens > CUMULATIVE_CONTEXT_THRESHOLD {
+        CUMULATIVE_CONTEXT_FALLBACK
+    } else {
+        raw_context_tokens
+    }
+}
+
+/// Deserialize one stored conversation, logging rather than failing on schema drift
+fn parse_conversation(key: &str, json_str: &str) -> Option<QConversation> {
+    match serde_json::from_str::<QConversation>(json_str) {
+        Ok(conversation) => Some(conversation),
+        Err(e) => {
+            tracing::debug!("Skipping unreadable conversation {}: {}", key, e);
+            None
+        }
+    }
+}
+
+/// Modification time of a conversation's directory, if it still exists
+fn directory_modified(path: &str) -> Option<DateTime<Local>> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let duration = modified.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    Some(Local.timestamp_opt(duration.as_secs() as i64, 0).single().unwrap_or_else(Local::now))
+}
+
+/// Row count, highest rowid and total size of the conversations table
+///
+/// Q appends to a conversation's JSON as it grows, so any new message changes
+/// the total length; new conversations also move the count and rowid.
+type ConversationsFingerprint = (i64, i64, i64);
+
+fn conversations_fingerprint(conn: &Connection) -> rusqlite::Result<ConversationsFingerprint> {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(MAX(rowid), 0), COALESCE(SUM(LENGTH(CAST(value AS BLOB))), 0) FROM conversations",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+}
+
 pub struct QDatabase {
     conn: Arc<Mutex<Connection>>,
     pub db_path: PathBuf,
     last_data_version: Arc<Mutex<Option<i32>>>,
+    /// Shape of the conversations table at the last check, see `conversations_fingerprint`
+    last_conversations_fingerprint: Arc<Mutex<Option<ConversationsFingerprint>>>,
+    /// Use the 4:1 char-to-token heuristic instead of the BPE tokenizer
+    use_approximate_tokens: bool,
+    /// Window applied to dated period metrics
+    date_range: DateRange,
+    /// Monthly message quota reported in global stats
+    message_quota_limit: usize,
+    /// Directories modified within this many days are marked active
+    active_session_days: i64,
+    compaction_thresholds: CompactionThresholds,
+    /// Window token usage is measured against
+    context_window: u64,
+    /// Zone that decides which day activity falls on
+    timezone: DisplayZone,
+    /// Conversations that failed to deserialize on the last full read
+    parse_failures: Arc<Mutex<usize>>,
+    /// Rates used to derive each conversation's per-1k cost from its input/output
+    /// mix; None prices everything at the flat `cost_per_1k`
+    blended_pricing: Option<ModelPricing>,
 }
 
 impl QDatabase {
     pub fn new() -> Result<Self> {
-        let db_path = Self::find_database()?;
+        Self::with_db_paths(&[])
+    }
+
+    /// Open the database, trying the configured paths before the built-in locations
+    pub fn with_db_paths(configured_paths: &[String]) -> Result<Self> {
+        let db_path = Self::find_database(configured_paths)?;
 
         let conn = Connection::open_with_flags(
             &db_path,
@@ -159,36 +524,122 @@ impl QDatabase {
         )?;
 
         Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
             db_path,
-            last_data_version: Arc::new(Mutex::new(None)),
+            ..Self::from_connection(conn)
         })
     }
 
-    fn find_database() -> Result<PathBuf> {
+    /// Wrap an already-open connection, such as an in-memory database seeded by tests
+    ///
+    /// The database has no path, so `has_changed` can't detect it disappearing.
+    pub fn from_connection(conn: Connection) -> Self {
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+            db_path: PathBuf::new(),
+            last_data_version: Arc::new(Mutex::new(None)),
+            last_conversations_fingerprint: Arc::new(Mutex::new(None)),
+            use_approximate_tokens: false,
+            date_range: DateRange::default(),
+            message_quota_limit: AppConfig::default().effective_message_quota_limit(),
+            active_session_days: AppConfig::default().active_session_days,
+            compaction_thresholds: CompactionThresholds::default(),
+            context_window: Q_CONTEXT_WINDOW,
+            timezone: DisplayZone::default(),
+            parse_failures: Arc::new(Mutex::new(0)),
+            blended_pricing: None,
+        }
+    }
+
+    /// Force the approximate 4:1 token estimate instead of the BPE tokenizer
+    pub fn with_approximate_tokens(mut self, enabled: bool) -> Self {
+        self.use_approximate_tokens = enabled;
+        self
+    }
+
+    /// Set the monthly message quota for the user's plan
+    pub fn with_message_quota_limit(mut self, limit: usize) -> Self {
+        self.message_quota_limit = limit;
+        self
+    }
+
+    /// Set how many days since last activity a session still counts as active
+    pub fn with_active_session_days(mut self, days: i64) -> Self {
+        self.active_session_days = days;
+        self
+    }
+
+    /// Set the context-usage percentages for warning, critical and imminent status
+    pub fn with_compaction_thresholds(mut self, thresholds: CompactionThresholds) -> Self {
+        self.compaction_thresholds = thresholds;
+        self
+    }
+
+    /// Measure usage against this window instead of the effective limit
+    pub fn with_context_window(mut self, context_window: u64) -> Self {
+        self.context_window = context_window;
+        self
+    }
+
+    /// Bucket "today" by calendar day in this zone
+    pub fn with_timezone(mut self, timezone: DisplayZone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Derive each conversation's per-1k rate from its input/output mix at
+    /// these rates instead of using the flat configured rate
+    pub fn with_blended_pricing(mut self, pricing: Option<ModelPricing>) -> Self {
+        self.blended_pricing = pricing;
+        self
+    }
+
+    /// Cost of a conversation: flat, or at the rate blended from its own mix
+    ///
+    /// Context tokens are priced at the blended rate too, and conversations with
+    /// no history to weigh fall back to the flat rate.
+    fn conversation_cost(&self, usage: &TokenUsageDetails, cost_per_1k: f64) -> f64 {
+        let rate = self
+            .blended_pricing
+            .as_ref()
+            .and_then(|pricing| blended_cost_per_1k(pricing, usage.input_tokens, usage.output_tokens))
+            .unwrap_or(cost_per_1k);
+        (usage.total_tokens as f64 / 1000.0) * rate
+    }
+
+    /// Restrict period metrics to conversations last active within the range
+    pub fn with_date_range(mut self, date_range: DateRange) -> Self {
+        self.date_range = date_range;
+        self
+    }
+
+    pub(crate) fn find_database(configured_paths: &[String]) -> Result<PathBuf> {
+        for configured in configured_paths {
+            let path = PathBuf::from(configured);
+            if path.exists() {
+                return Ok(path);
+            }
+            tracing::debug!("Configured Amazon Q database {} not found, trying defaults", configured);
+        }
+
         let possible_paths = vec![
             // macOS
-            directories::BaseDirs::new().and_then(|dirs| {
-                Some(
-                    dirs.home_dir()
-                        .join("Library")
-                        .join("Application Support")
-                        .join("amazon-q")
-                        .join("data.sqlite3"),
-                )
+            directories::BaseDirs::new().map(|dirs| {
+                dirs.home_dir()
+                    .join("Library")
+                    .join("Application Support")
+                    .join("amazon-q")
+                    .join("data.sqlite3")
             }),
             // Linux
             directories::BaseDirs::new()
-                .and_then(|dirs| Some(dirs.data_local_dir().join("amazon-q").join("data.sqlite3"))),
+                .map(|dirs| dirs.data_local_dir().join("amazon-q").join("data.sqlite3")),
             // Legacy location
-            directories::BaseDirs::new().and_then(|dirs| {
-                Some(
-                    dirs.home_dir()
-                        .join(".aws")
-                        .join("q")
-                        .join("db")
-                        .join("q.db"),
-                )
+            directories::BaseDirs::new().map(|dirs| {
+                dirs.home_dir()
+                    .join(".aws")
+                    .join("q")
+                    .join("db")
+                    .join("q.db")
             }),
         ];
 
@@ -202,6 +653,11 @@ impl QDatabase {
     }
 
     pub fn has_changed(&mut self) -> Result<bool> {
+        // An open connection keeps reading a deleted file, so check the path too
+        if !self.db_path.as_os_str().is_empty() && !self.db_path.exists() {
+            return Err(QStatusError::DatabaseNotFound);
+        }
+
         let conn = self.conn.lock().unwrap();
         let version: i32 = conn
             .query_row("PRAGMA data_version", [], |row| row.get(0))?;
@@ -212,7 +668,19 @@ impl QDatabase {
             .unwrap_or(true);
 
         *last_version = Some(version);
-        Ok(changed)
+        if !changed {
+            return Ok(false);
+        }
+
+        // data_version bumps on a write to any table; only re-collect when
+        // the conversations themselves moved. Without the table, trust the version.
+        let Ok(fingerprint) = conversations_fingerprint(&conn) else {
+            return Ok(true);
+        };
+        let mut last_fingerprint = self.last_conversations_fingerprint.lock().unwrap();
+        let moved = last_fingerprint.as_ref() != Some(&fingerprint);
+        *last_fingerprint = Some(fingerprint);
+        Ok(moved)
     }
 
     pub fn get_current_conversation(&self, _cwd: Option<&str>) -> Result<Option<QConversation>> {
@@ -240,32 +708,34 @@ impl QDatabase {
         // Get context tokens - this might be cumulative, so we need to be careful
         let raw_context_tokens = conversation.context_message_length.unwrap_or(0);
         
-        // Calculate actual tokens from conversation history using 4:1 char-to-token ratio
-        let mut history_chars = 0u64;
+        // Count tokens per message, falling back to the 4:1 heuristic when configured
+        // or when the tokenizer can't be initialized
+        let approximate = self.use_approximate_tokens || !tokenizer::is_available();
+        let mut user_tokens = 0u64;
+        let mut assistant_tokens = 0u64;
         for message_pair in &conversation.history {
-            for message in message_pair {
+            for (index, message) in message_pair.iter().enumerate() {
                 let message_str = serde_json::to_string(message).unwrap_or_default();
-                history_chars += message_str.len() as u64;
+                let tokens = if approximate {
+                    tokenizer::approximate_tokens(&message_str) as u64
+                } else {
+                    tokenizer::count_tokens(&message_str) as u64
+                };
+                match message_role(message, index) {
+                    MessageRole::User => user_tokens += tokens,
+                    MessageRole::Assistant => assistant_tokens += tokens,
+                }
             }
         }
-        
-        // Q uses 4:1 character to token ratio
-        let history_tokens = history_chars / 4;
+        let history_tokens = user_tokens + assistant_tokens;
         
         // For active context, we should only count what's currently loaded
-        // If context_tokens seems unreasonably high (>100K), it's likely cumulative
-        // In that case, estimate based on typical context size
-        let context_tokens = if raw_context_tokens > 100_000 {
-            // Likely cumulative - estimate current context as ~20K (typical for a few files)
-            20_000
-        } else {
-            raw_context_tokens
-        };
+        let context_tokens = effective_context_tokens(raw_context_tokens);
         
         let total_tokens = history_tokens + context_tokens;
         
         // Cap total tokens at context window to prevent >100% issues
-        let context_window = 175_000u64;
+        let context_window = self.context_window;
         let total_tokens = total_tokens.min(context_window);
         
         // Cap percentage at 99.9% unless truly at 100%
@@ -279,12 +749,7 @@ impl QDatabase {
         };
         
         // Determine compaction status based on thresholds
-        let compaction_status = match percentage {
-            p if p < 70.0 => CompactionStatus::Safe,
-            p if p < 90.0 => CompactionStatus::Warning,
-            p if p < 95.0 => CompactionStatus::Critical,
-            _ => CompactionStatus::Imminent,
-        };
+        let compaction_status = compaction_status_for(percentage, &self.compaction_thresholds);
         
         TokenUsageDetails {
             history_tokens,
@@ -295,6 +760,8 @@ impl QDatabase {
             compaction_status,
             has_summary: conversation.latest_summary.is_some(),
             message_count: conversation.history.len(),
+            input_tokens: user_tokens,
+            output_tokens: assistant_tokens,
         }
     }
 
@@ -309,18 +776,27 @@ impl QDatabase {
                 let json_str: String = row.get(1)?;
                 Ok((key, json_str))
             })?
-            .filter_map(|r| r.ok())
-            .filter_map(|(key, json_str)| {
-                serde_json::from_str::<QConversation>(&json_str)
-                    .ok()
-                    .map(|conv| (key, conv))
-            })
-            .collect();
+            .filter_map(|r| r.ok());
+
+        let mut parsed = Vec::new();
+        let mut failures = 0;
+        for (key, json_str) in conversations {
+            match parse_conversation(&key, &json_str) {
+                Some(conv) => parsed.push((key, conv)),
+                None => failures += 1,
+            }
+        }
+        *self.parse_failures.lock().unwrap() = failures;
+
+        Ok(parsed)
+    }
 
-        Ok(conversations)
+    /// Conversations skipped as unreadable by the last full read
+    pub fn parse_failure_count(&self) -> usize {
+        *self.parse_failures.lock().unwrap()
     }
     
-    pub fn get_all_conversation_summaries(&self) -> Result<Vec<ConversationSummary>> {
+    pub fn get_all_conversation_summaries(&self, cost_per_1k: f64) -> Result<Vec<ConversationSummary>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn
             .prepare("SELECT key, value, LENGTH(value) as size FROM conversations ORDER BY size DESC")?;
@@ -334,27 +810,32 @@ impl QDatabase {
             Ok((key, json_str, size))
         })?;
 
-        for row in rows {
-            if let Ok((path, json_str, json_size_bytes)) = row {
-                if let Ok(conv) = serde_json::from_str::<QConversation>(&json_str) {
-                    let token_usage = self.get_token_usage(&conv);
-                    
-                    summaries.push(ConversationSummary {
-                        path: path.clone(),
-                        conversation_id: conv.conversation_id,
-                        token_usage,
-                        last_updated: None, // Could parse from conversation if timestamp available
-                        json_size_bytes,
-                    });
-                }
-            }
+        let mut failures = 0;
+        for (path, json_str, json_size_bytes) in rows.flatten() {
+            let Some(conv) = parse_conversation(&path, &json_str) else {
+                failures += 1;
+                continue;
+            };
+            let token_usage = self.get_token_usage(&conv);
+            let cost_estimate = self.conversation_cost(&token_usage, cost_per_1k);
+
+            summaries.push(ConversationSummary {
+                path: path.clone(),
+                conversation_id: conv.conversation_id,
+                token_usage,
+                last_updated: None, // Could parse from conversation if timestamp available
+                json_size_bytes,
+                cost_estimate,
+                provider: None,
+            });
         }
+        *self.parse_failures.lock().unwrap() = failures;
 
         Ok(summaries)
     }
     
     pub fn get_global_stats(&self, cost_per_1k: f64) -> Result<GlobalStats> {
-        let summaries = self.get_all_conversation_summaries()?;
+        let summaries = self.get_all_conversation_summaries(cost_per_1k)?;
         
         let total_conversations = summaries.len();
         let total_tokens: u64 = summaries.iter().map(|s| s.token_usage.total_tokens).sum();
@@ -377,14 +858,14 @@ impl QDatabase {
             .max_by_key(|s| s.token_usage.total_tokens)
             .cloned();
             
-        let total_cost_estimate = (total_tokens as f64 / 1000.0) * cost_per_1k;
+        let total_cost_estimate = summaries.iter().map(|s| s.cost_estimate).sum();
         
         // Calculate total messages across all conversations
         let total_messages: usize = summaries.iter().map(|s| s.token_usage.message_count).sum();
         
         // For now, assume all messages are from current month (will need actual timestamp parsing)
         let message_quota_used = total_messages;
-        let message_quota_limit = 5000;
+        let message_quota_limit = self.message_quota_limit;
         
         Ok(GlobalStats {
             total_conversations,
@@ -397,6 +878,10 @@ impl QDatabase {
             total_messages,
             message_quota_used,
             message_quota_limit,
+            // Amazon Q conversations don't record which model served them
+            cost_by_model: HashMap::new(),
+            tokens_by_model: HashMap::new(),
+            parse_failures: self.parse_failure_count(),
         })
     }
     
@@ -419,7 +904,37 @@ impl QDatabase {
         }
     }
     
+    pub fn get_conversation_by_id(&self, conversation_id: &str) -> Result<Option<QConversation>> {
+        match self.conversation_path(conversation_id)? {
+            Some(path) => self.get_conversation_by_path(&path),
+            None => Ok(None),
+        }
+    }
+
+    /// Modification time of the conversation's directory, Q's only activity signal
+    pub fn get_last_activity(&self, conversation_id: &str) -> Result<Option<DateTime<Local>>> {
+        Ok(self.conversation_path(conversation_id)?.and_then(|path| directory_modified(&path)))
+    }
+
+    /// Path a conversation is stored under
+    fn conversation_path(&self, conversation_id: &str) -> Result<Option<String>> {
+        // Conversations are keyed by path, so let SQLite find the path holding
+        // this ID rather than deserializing every row; invalid JSON never matches
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.query_row(
+            "SELECT key FROM conversations
+             WHERE CASE WHEN json_valid(CAST(value AS TEXT))
+                        THEN json_extract(CAST(value AS TEXT), '$.conversation_id') END = ?1
+             LIMIT 1",
+            [conversation_id],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?)
+    }
+
     pub fn get_all_sessions(&self, cost_per_1k: f64) -> Result<Vec<Session>> {
+        let activity = self.get_directory_activity_all();
+
         // Query with LENGTH to get data size as proxy for recent activity
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn
@@ -427,7 +942,7 @@ impl QDatabase {
 
         let mut sessions = Vec::new();
         let now = Local::now();
-        let seven_days_ago = now - Duration::days(7);
+        let active_cutoff = now - Duration::days(self.active_session_days);
         
         let rows = stmt.query_map([], |row| {
             let key: String = row.get(0)?;
@@ -436,53 +951,48 @@ impl QDatabase {
             Ok((key, json_str, size))
         })?;
 
-        for row in rows {
-            if let Ok((path, json_str, _size)) = row {
-                if let Ok(conv) = serde_json::from_str::<QConversation>(&json_str) {
-                    let token_usage = self.get_token_usage(&conv);
-                    let session_cost = (token_usage.total_tokens as f64 / 1000.0) * cost_per_1k;
-                    
-                    // Try to use directory modification time as proxy for last activity
-                    let dir_path = std::path::Path::new(&path);
-                    let last_activity = if dir_path.exists() {
-                        match dir_path.metadata() {
-                            Ok(metadata) => {
-                                match metadata.modified() {
-                                    Ok(modified) => {
-                                        // Convert system time to chrono DateTime
-                                        let duration = modified.duration_since(std::time::UNIX_EPOCH)
-                                            .unwrap_or_default();
-                                        Local.timestamp_opt(duration.as_secs() as i64, 0).single()
-                                            .unwrap_or(now)
-                                    }
-                                    Err(_) => now - Duration::days(30)
-                                }
-                            }
-                            Err(_) => now - Duration::days(30)
-                        }
-                    } else {
-                        now - Duration::days(30)
-                    };
-                    
-                    // Mark as active if directory was modified in last 7 days
-                    let is_active = last_activity > seven_days_ago;
-                    
-                    // Check if has active context (context_tokens > 0 means files are loaded)
-                    let has_active_context = token_usage.context_tokens > 0;
-                    
-                    sessions.push(Session {
-                        conversation_id: conv.conversation_id,
-                        directory: path,
-                        token_usage,
-                        last_activity,
-                        message_count: conv.history.len(),
-                        session_cost,
-                        is_active,
-                        has_active_context,
-                    });
-                }
+        let mut failures = 0;
+        for (path, json_str, _size) in rows.flatten() {
+            let Some(conv) = parse_conversation(&path, &json_str) else {
+                failures += 1;
+                continue;
+            };
+            let token_usage = self.get_token_usage(&conv);
+            let session_cost = self.conversation_cost(&token_usage, cost_per_1k);
+            
+            // Try to use directory modification time as proxy for last activity
+            let last_activity = directory_modified(&path).unwrap_or_else(|| now - Duration::days(30));
+            if !self.date_range.contains(&last_activity) {
+                continue;
             }
+            
+            // Mark as active if directory was modified within the active window
+            let is_active = last_activity > active_cutoff;
+            
+            // Has active context when files are listed or context tokens are loaded
+            let context_files = conv.context_files();
+            let has_active_context = !context_files.is_empty() || token_usage.context_tokens > 0;
+
+            // Directories with no recorded `q` runs still get a count of zero
+            let invocations = activity.get(&path).map_or(0, |a| a.q_invocations);
+            
+            sessions.push(Session {
+                conversation_id: conv.conversation_id,
+                directory: path,
+                token_usage,
+                last_activity,
+                message_count: conv.history.len(),
+                session_cost,
+                is_active,
+                has_active_context,
+                invocations: Some(invocations),
+                model_usage: Vec::new(),
+                token_timeline: Vec::new(),
+                context_files,
+                provider: None,
+            });
         }
+        *self.parse_failures.lock().unwrap() = failures;
 
         Ok(sessions)
     }
@@ -512,43 +1022,112 @@ impl QDatabase {
     }
     
     pub fn get_period_metrics(&self, cost_per_1k: f64) -> Result<PeriodMetrics> {
-        // Since history table is unused by Q CLI, we cannot determine time-based metrics accurately
-        // We'll show all conversations for all periods as that's the only data we have
-        
-        // Get all conversations
-        let all_conversations = self.get_all_conversation_summaries()?;
-        let total_tokens: u64 = all_conversations.iter()
-            .map(|c| c.token_usage.total_tokens)
-            .sum();
-        
-        // Calculate cost
-        let total_cost = (total_tokens as f64 / 1000.0) * cost_per_1k;
-        
-        // Since we can't determine when conversations were active,
-        // we show the same totals for all periods
-        // This is transparent and honest about our data limitations
-        let today_tokens = total_tokens;
-        let week_tokens = total_tokens;
-        let month_tokens = total_tokens;
-        let year_tokens = total_tokens;
-        
-        let today_cost = total_cost;
-        let week_cost = total_cost;
-        let month_cost = total_cost;
-        let year_cost = total_cost;
-        
-        Ok(PeriodMetrics {
-            today_tokens,
-            today_cost,
-            week_tokens,
-            week_cost,
-            month_tokens,
-            month_cost,
-            year_tokens,
-            year_cost,
-        })
+        // Conversations are keyed by directory, so the latest `q` invocation recorded
+        // for that directory in the history table is the best timestamp we have
+        let all_conversations = self.get_all_conversation_summaries(cost_per_1k)?;
+        let last_activity = self.get_last_activity_by_directory();
+
+        let dated: Vec<(&ConversationSummary, Option<DateTime<Local>>)> = all_conversations.iter()
+            .map(|c| (c, last_activity.get(&c.path).copied()))
+            .filter(|(_, ts)| self.date_range.is_unbounded() || ts.is_some_and(|t| self.date_range.contains(&t)))
+            .collect();
+
+        // Periods are bucketed at one rate, so a blended cost uses the mix of the whole window
+        let tokens: u64 = dated.iter().map(|(c, _)| c.token_usage.total_tokens).sum();
+        let rate = if self.blended_pricing.is_some() && tokens > 0 {
+            dated.iter().map(|(c, _)| c.cost_estimate).sum::<f64>() / tokens as f64 * 1000.0
+        } else {
+            cost_per_1k
+        };
+
+        let dated: Vec<(u64, Option<DateTime<Local>>)> = dated.into_iter()
+            .map(|(c, ts)| (c.token_usage.total_tokens, ts))
+            .collect();
+        Ok(bucket_period_metrics(&dated, Local::now(), self.timezone, rate))
+    }
+
+    /// Latest `q` invocation time per directory from the history table
+    ///
+    /// Returns an empty map if the history table is missing or unreadable,
+    /// so callers treat every conversation as undated rather than failing.
+    pub fn get_last_activity_by_directory(&self) -> HashMap<String, DateTime<Local>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = match conn.prepare("
+            SELECT cwd, MAX(start_time) as last_time
+            FROM history
+            WHERE cwd IS NOT NULL
+            AND (command LIKE '%q %' OR command = 'q')
+            GROUP BY cwd
+        ") {
+            Ok(stmt) => stmt,
+            Err(_) => return HashMap::new(),
+        };
+
+        let rows = match stmt.query_map([], |row| {
+            let cwd: String = row.get(0)?;
+            let last_timestamp: Option<i64> = row.get(1)?;
+            Ok((cwd, last_timestamp))
+        }) {
+            Ok(rows) => rows,
+            Err(_) => return HashMap::new(),
+        };
+
+        rows.flatten()
+            .filter_map(|(cwd, ts)| {
+                ts.and_then(|ts| Local.timestamp_opt(ts, 0).single())
+                    .map(|dt| (cwd, dt))
+            })
+            .collect()
     }
     
+    /// Invocation counts and first/last `q` run for every directory in the history table
+    ///
+    /// Like `get_last_activity_by_directory`, a missing or unreadable history
+    /// table yields an empty map so the session list still renders.
+    pub fn get_directory_activity_all(&self) -> HashMap<String, DirectoryActivity> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = match conn.prepare("
+            SELECT
+                cwd,
+                MIN(start_time) as first_time,
+                MAX(start_time) as last_time,
+                COUNT(*) as invocations
+            FROM history
+            WHERE cwd IS NOT NULL
+            AND (command LIKE '%q %' OR command = 'q')
+            GROUP BY cwd
+        ") {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                tracing::debug!("History table unavailable: {}", e);
+                return HashMap::new();
+            }
+        };
+
+        let rows = match stmt.query_map([], |row| {
+            let cwd: String = row.get(0)?;
+            let first_timestamp: Option<i64> = row.get(1)?;
+            let last_timestamp: Option<i64> = row.get(2)?;
+            let invocations: usize = row.get(3)?;
+            Ok((cwd, first_timestamp, last_timestamp, invocations))
+        }) {
+            Ok(rows) => rows,
+            Err(_) => return HashMap::new(),
+        };
+
+        rows.flatten()
+            .map(|(cwd, first, last, invocations)| {
+                let activity = DirectoryActivity {
+                    directory: cwd.clone(),
+                    first_activity: first.and_then(|ts| Local.timestamp_opt(ts, 0).single()),
+                    last_activity: last.and_then(|ts| Local.timestamp_opt(ts, 0).single()),
+                    q_invocations: invocations,
+                };
+                (cwd, activity)
+            })
+            .collect()
+    }
+
     pub fn get_directory_activity(&self, directory: &str) -> Result<DirectoryActivity> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare("
@@ -585,6 +1164,59 @@ impl QDatabase {
     }
 }
 
+/// Bucket token counts into today/7d/30d/365d periods by their activity time
+///
+/// Entries without a timestamp are excluded from every bucket instead of
+/// inflating all of them.
+pub fn bucket_period_metrics(
+    entries: &[(u64, Option<DateTime<Local>>)],
+    now: DateTime<Local>,
+    timezone: DisplayZone,
+    cost_per_1k: f64,
+) -> PeriodMetrics {
+    let today = timezone.date_of(&now);
+    let week_start = now - Duration::days(7);
+    let month_start = now - Duration::days(30);
+    let year_start = now - Duration::days(365);
+
+    let mut today_tokens = 0u64;
+    let mut week_tokens = 0u64;
+    let mut month_tokens = 0u64;
+    let mut year_tokens = 0u64;
+
+    for (tokens, timestamp) in entries {
+        let Some(timestamp) = timestamp else {
+            continue;
+        };
+
+        if timezone.date_of(timestamp) == today {
+            today_tokens += tokens;
+        }
+        if *timestamp >= week_start {
+            week_tokens += tokens;
+        }
+        if *timestamp >= month_start {
+            month_tokens += tokens;
+        }
+        if *timestamp >= year_start {
+            year_tokens += tokens;
+        }
+    }
+
+    let cost = |tokens: u64| (tokens as f64 / 1000.0) * cost_per_1k;
+
+    PeriodMetrics {
+        today_tokens,
+        today_cost: cost(today_tokens),
+        week_tokens,
+        week_cost: cost(week_tokens),
+        month_tokens,
+        month_cost: cost(month_tokens),
+        year_tokens,
+        year_cost: cost(year_tokens),
+    }
+}
+
 // Implement DataSource trait for QDatabase
 #[async_trait]
 impl DataSource for QDatabase {
@@ -602,9 +1234,14 @@ impl DataSource for QDatabase {
         self.get_current_conversation(cwd)
     }
 
-    async fn get_all_conversation_summaries(&self) -> Result<Vec<ConversationSummary>> {
+    async fn get_conversation_by_id(&self, conversation_id: &str) -> Result<Option<QConversation>> {
         // Delegate to the existing synchronous method
-        self.get_all_conversation_summaries()
+        self.get_conversation_by_id(conversation_id)
+    }
+
+    async fn get_all_conversation_summaries(&self, cost_per_1k: f64) -> Result<Vec<ConversationSummary>> {
+        // Delegate to the existing synchronous method
+        self.get_all_conversation_summaries(cost_per_1k)
     }
 
     async fn get_all_sessions(&self, cost_per_1k: f64) -> Result<Vec<Session>> {
@@ -633,4 +1270,467 @@ impl DataSource for QDatabase {
         // We need to wrap it in Ok()
         Ok(self.get_token_usage(conversation))
     }
+
+    async fn get_conversation_cost(
+        &self,
+        _conversation: &QConversation,
+        usage: &TokenUsageDetails,
+        cost_per_1k: f64,
+    ) -> Result<f64> {
+        Ok(self.conversation_cost(usage, cost_per_1k))
+    }
+
+    async fn get_last_activity(&self, conversation_id: &str) -> Result<Option<DateTime<Local>>> {
+        // Delegate to the existing synchronous method
+        self.get_last_activity(conversation_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::cost_calculator::CostCalculator;
+    use chrono::Utc;
+
+    /// In-memory Amazon Q database with two conversations and their `q` history
+    fn seeded_database(api_last_run: DateTime<Local>, web_last_run: DateTime<Local>) -> QDatabase {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("
+            CREATE TABLE conversations (key TEXT PRIMARY KEY, value TEXT);
+            CREATE TABLE history (command TEXT, cwd TEXT, start_time INTEGER);
+        ").unwrap();
+
+        let api = serde_json::json!({
+            "conversation_id": "conv-api",
+            "history": [
+                [{"role": "user", "content": "Add a health check endpoint"},
+                 {"role": "assistant", "content": "Added GET /health returning 200"}],
+                [{"role": "user", "content": "Now add a test"},
+                 {"role": "assistant", "content": "Added tests/health.rs"}]
+            ],
+            "context_message_length": 5000,
+            "context_manager": {"global_config": {"paths": ["README.md"]}}
+        });
+        let web = serde_json::json!({
+            "conversation_id": "conv-web",
+            "history": [[{"role": "user", "content": "hi"}, {"role": "assistant", "content": "hello"}]]
+        });
+        for (key, value) in [("/work/api", api), ("/work/web", web)] {
+            conn.execute("INSERT INTO conversations VALUES (?1, ?2)", (key, value.to_string())).unwrap();
+        }
+        for (cwd, time) in [("/work/api", api_last_run), ("/work/api", api_last_run), ("/work/web", web_last_run)] {
+            conn.execute("INSERT INTO history VALUES ('q chat', ?1, ?2)", (cwd, time.timestamp())).unwrap();
+        }
+
+        QDatabase::from_connection(conn).with_approximate_tokens(true)
+    }
+
+    #[test]
+    fn test_in_memory_token_usage_and_sessions() {
+        let now = Local::now();
+        let mut db = seeded_database(now, now);
+        assert!(db.has_changed().unwrap());
+
+        let conversation = db.get_conversation_by_id("conv-api").unwrap().unwrap();
+        let usage = db.get_token_usage(&conversation);
+        assert_eq!(usage.context_tokens, 5000);
+        assert!(usage.history_tokens > 0);
+        assert_eq!(usage.total_tokens, usage.history_tokens + 5000);
+        assert_eq!(usage.message_count, 2);
+
+        let sessions = db.get_all_sessions(1.0).unwrap();
+        assert_eq!(sessions.len(), 2);
+        let api = sessions.iter().find(|s| s.directory == "/work/api").unwrap();
+        assert_eq!(api.invocations, Some(2));
+        assert_eq!(api.context_files, vec!["README.md"]);
+        assert!(api.has_active_context);
+        assert!((api.session_cost - usage.total_tokens as f64 / 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sessions_follow_date_range() {
+        // Seeded directories don't exist, so their last activity falls back to 30 days ago
+        let now = Local::now();
+        let last_week = seeded_database(now, now).with_date_range(DateRange::new(Some(now - Duration::days(7)), None));
+        assert!(last_week.get_all_sessions(1.0).unwrap().is_empty());
+        assert!(last_week.get_sessions_grouped_by_directory(1.0).unwrap().is_empty());
+
+        let before = seeded_database(now, now).with_date_range(DateRange::new(None, Some(now - Duration::days(7))));
+        assert_eq!(before.get_all_sessions(1.0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_malformed_conversation_is_counted_not_dropped_silently() {
+        let now = Local::now();
+        let db = seeded_database(now, now);
+        db.conn.lock().unwrap()
+            .execute("INSERT INTO conversations VALUES ('/work/broken', '{\"conversation_id\": 42}')", [])
+            .unwrap();
+
+        let conversations = db.get_all_conversations().unwrap();
+        assert_eq!(conversations.len(), 2);
+        assert_eq!(db.parse_failure_count(), 1);
+
+        let stats = db.get_global_stats(1.0).unwrap();
+        assert_eq!(stats.total_conversations, 2);
+        assert_eq!(stats.parse_failures, 1);
+
+        // Lookups by id skip rows that aren't valid JSON instead of failing
+        db.conn.lock().unwrap()
+            .execute("INSERT INTO conversations VALUES ('/work/garbage', 'not json')", [])
+            .unwrap();
+        assert_eq!(db.get_conversation_by_id("conv-web").unwrap().unwrap().conversation_id, "conv-web");
+        assert!(db.get_conversation_by_id("conv-missing").unwrap().is_none());
+
+        // The session list counts them too
+        db.get_all_sessions(1.0).unwrap();
+        assert_eq!(db.parse_failure_count(), 2);
+    }
+
+    #[test]
+    fn test_auto_blended_rate_follows_input_output_mix() {
+        // Sonnet rates: $3/M input, $15/M output, so a 3:1 mix blends to $6/M
+        let pricing = CostCalculator::new().get_model_pricing(Q_PRICING_MODEL);
+        let rate = blended_cost_per_1k(&pricing, 3_000, 1_000).unwrap();
+        assert!((rate - 0.006).abs() < 1e-12);
+        assert_eq!(blended_cost_per_1k(&pricing, 0, 0), None);
+
+        let now = Local::now();
+        let flat = seeded_database(now, now);
+        let auto = seeded_database(now, now).with_blended_pricing(Some(CostCalculator::new().get_model_pricing(Q_PRICING_MODEL)));
+        let flat_sessions = flat.get_all_sessions(0.0066).unwrap();
+        let auto_sessions = auto.get_all_sessions(0.0066).unwrap();
+        for (flat_session, auto_session) in flat_sessions.iter().zip(&auto_sessions) {
+            let usage = &auto_session.token_usage;
+            let blended = blended_cost_per_1k(&pricing, usage.input_tokens, usage.output_tokens).unwrap();
+            assert!((flat_session.session_cost - usage.total_tokens as f64 / 1000.0 * 0.0066).abs() < 1e-12);
+            assert!((auto_session.session_cost - usage.total_tokens as f64 / 1000.0 * blended).abs() < 1e-12);
+        }
+
+        let stats = auto.get_global_stats(0.0066).unwrap();
+        let expected: f64 = auto_sessions.iter().map(|s| s.session_cost).sum();
+        assert!((stats.total_cost_estimate - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_has_changed_ignores_writes_to_other_tables() {
+        use rusqlite::OpenFlags;
+
+        let uri = "file:has_changed_test?mode=memory&cache=shared";
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_URI;
+        let writer = Connection::open_with_flags(uri, flags).unwrap();
+        writer.execute_batch("
+            CREATE TABLE conversations (key TEXT PRIMARY KEY, value TEXT);
+            CREATE TABLE state (key TEXT PRIMARY KEY, value TEXT);
+            INSERT INTO conversations VALUES ('/work/api', '{\"history\": []}');
+        ").unwrap();
+        let mut db = QDatabase::from_connection(Connection::open_with_flags(uri, flags).unwrap());
+        assert!(db.has_changed().unwrap());
+        assert!(!db.has_changed().unwrap());
+
+        // Settings churn bumps data_version but leaves conversations alone
+        let version = |db: &QDatabase| -> i32 {
+            db.conn.lock().unwrap().query_row("PRAGMA data_version", [], |row| row.get(0)).unwrap()
+        };
+        let before = version(&db);
+        writer.execute("INSERT INTO state VALUES ('theme', 'dark')", []).unwrap();
+        assert_ne!(version(&db), before);
+        assert!(!db.has_changed().unwrap());
+
+        // A conversation growing in place is picked up
+        writer.execute(
+            "UPDATE conversations SET value = '{\"history\": [[]]}' WHERE key = '/work/api'",
+            [],
+        ).unwrap();
+        assert!(db.has_changed().unwrap());
+
+        writer.execute("INSERT INTO conversations VALUES ('/work/web', '{}')", []).unwrap();
+        assert!(db.has_changed().unwrap());
+        assert!(!db.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_token_usage_splits_user_and_assistant() {
+        let db = QDatabase::from_connection(Connection::open_in_memory().unwrap()).with_approximate_tokens(true);
+        let user = serde_json::json!({"role": "user", "content": "u".repeat(400)});
+        let assistant = serde_json::json!({"role": "assistant", "content": "a".repeat(1200)});
+        // Q's native shape has no role field
+        let prompt = serde_json::json!({"content": {"Prompt": {"prompt": "p".repeat(80)}}});
+        let response = serde_json::json!({"Response": {"content": "r".repeat(160)}});
+        let conversation: QConversation = serde_json::from_value(serde_json::json!({
+            "conversation_id": "split",
+            "history": [[user, assistant], [prompt, response]]
+        }))
+        .unwrap();
+
+        let tokens = |message: &Value| tokenizer::approximate_tokens(&message.to_string()) as u64;
+        let history = &conversation.history;
+        let usage = db.get_token_usage(&conversation);
+        assert_eq!(usage.input_tokens, tokens(&history[0][0]) + tokens(&history[1][0]));
+        assert_eq!(usage.output_tokens, tokens(&history[0][1]) + tokens(&history[1][1]));
+        assert!(usage.output_tokens > usage.input_tokens);
+        assert_eq!(usage.history_tokens, usage.input_tokens + usage.output_tokens);
+
+        assert_eq!(message_role(&serde_json::json!({"ToolUse": {}}), 0), MessageRole::Assistant);
+        assert_eq!(message_role(&serde_json::json!({"text": "?"}), 0), MessageRole::User);
+        assert_eq!(message_role(&serde_json::json!({"text": "?"}), 1), MessageRole::Assistant);
+    }
+
+    #[test]
+    fn test_effective_and_actual_context_window() {
+        let now = Local::now();
+        let config = AppConfig::default();
+        assert_eq!(config.amazon_q_context_window(), 175_000);
+        let effective = seeded_database(now, now).with_context_window(config.amazon_q_context_window());
+
+        let config = AppConfig { amazon_q_use_effective_limit: false, ..AppConfig::default() };
+        assert_eq!(config.amazon_q_context_window(), 200_000);
+        let actual = seeded_database(now, now).with_context_window(config.amazon_q_context_window());
+
+        let conversation = effective.get_conversation_by_id("conv-api").unwrap().unwrap();
+        let effective_usage = effective.get_token_usage(&conversation);
+        let actual_usage = actual.get_token_usage(&conversation);
+        assert_eq!(effective_usage.total_tokens, actual_usage.total_tokens);
+        assert_eq!(effective_usage.context_window, 175_000);
+        assert_eq!(actual_usage.context_window, 200_000);
+        let expected = effective_usage.total_tokens as f64 / 200_000.0 * 100.0;
+        assert!((actual_usage.percentage - expected).abs() < 1e-9);
+        assert!(actual_usage.percentage < effective_usage.percentage);
+    }
+
+    #[test]
+    fn test_in_memory_period_metrics() {
+        let now = Local::now();
+        let db = seeded_database(now, now - Duration::days(60));
+
+        let tokens_for = |path: &str| {
+            let summaries = db.get_all_conversation_summaries(0.0).unwrap();
+            summaries.iter().find(|c| c.path == path).unwrap().token_usage.total_tokens
+        };
+        let metrics = db.get_period_metrics(0.0).unwrap();
+        assert_eq!(metrics.today_tokens, tokens_for("/work/api"));
+        assert_eq!(metrics.month_tokens, tokens_for("/work/api"));
+        assert_eq!(metrics.year_tokens, tokens_for("/work/api") + tokens_for("/work/web"));
+    }
+
+    #[test]
+    fn test_indicator_is_ascii_without_color() {
+        assert_eq!(CompactionStatus::Safe.indicator(true), "🟢");
+        assert_eq!(CompactionStatus::Safe.indicator(false), "[OK]");
+        assert_eq!(CompactionStatus::Imminent.indicator(false), "[!!!]");
+        for status in [CompactionStatus::Safe, CompactionStatus::Warning, CompactionStatus::Critical, CompactionStatus::Imminent] {
+            assert!(status.indicator(false).is_ascii());
+        }
+    }
+
+    #[test]
+    fn test_compaction_status_follows_thresholds() {
+        let defaults = CompactionThresholds::default();
+        assert_eq!(compaction_status_for(65.0, &defaults), CompactionStatus::Safe);
+        assert_eq!(compaction_status_for(92.0, &defaults), CompactionStatus::Critical);
+        assert_eq!(compaction_status_for(99.0, &defaults), CompactionStatus::Imminent);
+
+        let early_warning = CompactionThresholds { warning: 60.0, ..defaults };
+        assert_eq!(compaction_status_for(65.0, &early_warning), CompactionStatus::Warning);
+    }
+
+    #[test]
+    fn test_context_files_from_context_manager() {
+        let conversation: QConversation = serde_json::from_value(serde_json::json!({
+            "conversation_id": "abc",
+            "context_manager": {
+                "max_context_files_size": 150000,
+                "current_profile": "default",
+                "global_config": {
+                    "paths": [".amazonq/rules/**/*.md", "README.md", "AmazonQ.md"],
+                    "hooks": {}
+                },
+                "profile_config": { "paths": ["src/main.rs", "README.md"], "hooks": {} },
+                "context_files": [["docs/notes.md", "file contents"]]
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            conversation.context_files(),
+            vec!["docs/notes.md", ".amazonq/rules/**/*.md", "README.md", "AmazonQ.md", "src/main.rs"]
+        );
+
+        // Unknown shapes and missing managers are not errors
+        let odd: QConversation = serde_json::from_value(serde_json::json!({
+            "conversation_id": "def",
+            "context_manager": { "paths": "README.md", "profiles": [1, 2] }
+        }))
+        .unwrap();
+        assert!(odd.context_files().is_empty());
+    }
+
+    #[test]
+    fn test_directory_activity_all_counts_q_runs() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_string_lossy().to_string();
+        {
+            let conn = Connection::open(file.path()).unwrap();
+            conn.execute_batch("
+                CREATE TABLE history (command TEXT, cwd TEXT, start_time INTEGER);
+                INSERT INTO history VALUES ('q chat', '/work/api', 100);
+                INSERT INTO history VALUES ('q', '/work/api', 300);
+                INSERT INTO history VALUES ('ls', '/work/api', 400);
+                INSERT INTO history VALUES ('q chat', '/work/web', 200);
+            ").unwrap();
+        }
+
+        let db = QDatabase::with_db_paths(&[path]).unwrap();
+        let activity = db.get_directory_activity_all();
+        assert_eq!(activity.len(), 2);
+        let api = &activity["/work/api"];
+        assert_eq!(api.q_invocations, 2);
+        assert_eq!(api.first_activity.unwrap().timestamp(), 100);
+        assert_eq!(api.last_activity.unwrap().timestamp(), 300);
+    }
+
+    #[test]
+    fn test_directory_activity_all_without_history_table() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_string_lossy().to_string();
+        Connection::open(file.path()).unwrap()
+            .execute_batch("CREATE TABLE conversations (key TEXT, value TEXT);").unwrap();
+
+        let db = QDatabase::with_db_paths(&[path]).unwrap();
+        assert!(db.get_directory_activity_all().is_empty());
+        assert!(db.get_all_sessions(0.0066).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_database_prefers_configured_path() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let configured = vec![
+            "/nonexistent/amazon-q/data.sqlite3".to_string(),
+            file.path().to_string_lossy().to_string(),
+        ];
+
+        assert_eq!(QDatabase::find_database(&configured).unwrap(), file.path());
+    }
+
+    #[test]
+    fn test_bucket_period_metrics_distinct_periods() {
+        let now = Local::now();
+        let entries = vec![
+            (1000, Some(now)),
+            (2000, Some(now - Duration::days(3))),
+            (4000, Some(now - Duration::days(20))),
+            (8000, Some(now - Duration::days(200))),
+        ];
+
+        let metrics = bucket_period_metrics(&entries, now, DisplayZone::Local, 1.0);
+        assert_eq!(metrics.today_tokens, 1000);
+        assert_eq!(metrics.week_tokens, 3000);
+        assert_eq!(metrics.month_tokens, 7000);
+        assert_eq!(metrics.year_tokens, 15000);
+        assert!((metrics.week_cost - 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_large_context_is_not_treated_as_cumulative() {
+        // A genuinely large context that still fits the window is kept as-is
+        assert_eq!(effective_context_tokens(150_000), 150_000);
+        assert_eq!(effective_context_tokens(Q_CONTEXT_WINDOW), Q_CONTEXT_WINDOW);
+        assert_eq!(effective_context_tokens(5_000), 5_000);
+
+        // Only lengths that could never be loaded at once fall back
+        assert_eq!(effective_context_tokens(Q_CONTEXT_WINDOW + 1), CUMULATIVE_CONTEXT_FALLBACK);
+    }
+
+    #[test]
+    fn test_bucket_period_metrics_excludes_undated() {
+        let now = Local::now();
+        let entries = vec![(1000, Some(now)), (50_000, None)];
+
+        let metrics = bucket_period_metrics(&entries, now, DisplayZone::Local, 0.0);
+        assert_eq!(metrics.today_tokens, 1000);
+        assert_eq!(metrics.year_tokens, 1000);
+    }
+
+    #[test]
+    fn test_bucket_today_near_midnight_uses_configured_zone() {
+        // 01:00 on the 11th in Tokyo
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 16, 0, 0).unwrap().with_timezone(&Local);
+        let entries = vec![
+            // 00:30 on the 11th in Tokyo, though still the 10th in UTC
+            (1000, Some(Utc.with_ymd_and_hms(2024, 3, 10, 15, 30, 0).unwrap().with_timezone(&Local))),
+            // 23:30 on the 10th in Tokyo
+            (2000, Some(Utc.with_ymd_and_hms(2024, 3, 10, 14, 30, 0).unwrap().with_timezone(&Local))),
+        ];
+
+        let tokyo = DisplayZone::parse("Asia/Tokyo").unwrap();
+        let metrics = bucket_period_metrics(&entries, now, tokyo, 0.0);
+        assert_eq!(metrics.today_tokens, 1000);
+        assert_eq!(metrics.week_tokens, 3000);
+
+        let utc = DisplayZone::parse("UTC").unwrap();
+        let metrics = bucket_period_metrics(&entries, now, utc, 0.0);
+        assert_eq!(metrics.today_tokens, 3000);
+    }
+
+    fn stats_with_quota(used: usize, limit: usize) -> GlobalStats {
+        GlobalStats {
+            total_messages: used,
+            message_quota_used: used,
+            message_quota_limit: limit,
+            ..GlobalStats::default()
+        }
+    }
+
+    #[test]
+    fn test_pro_plan_raises_message_quota() {
+        let free = AppConfig::default();
+        let pro = AppConfig {
+            amazon_q_plan: crate::app::config::AmazonQPlan::Pro,
+            ..AppConfig::default()
+        };
+        assert_eq!(free.effective_message_quota_limit(), 5000);
+        assert!(pro.effective_message_quota_limit() > free.effective_message_quota_limit());
+
+        let free_pct = stats_with_quota(2500, free.effective_message_quota_limit()).message_quota_percentage();
+        let pro_pct = stats_with_quota(2500, pro.effective_message_quota_limit()).message_quota_percentage();
+        assert!((free_pct - 50.0).abs() < f64::EPSILON);
+        assert!(pro_pct < free_pct);
+
+        // An explicit limit overrides the plan default
+        let custom = AppConfig { message_quota_limit: Some(1000), ..pro };
+        assert_eq!(custom.effective_message_quota_limit(), 1000);
+    }
+
+    fn io_session(input: u64, output: u64, cost: f64, model_usage: Vec<ModelUsage>) -> Session {
+        let mut session = Session::test("/work", input + output, cost);
+        session.token_usage.input_tokens = input;
+        session.token_usage.output_tokens = output;
+        session.model_usage = model_usage;
+        session
+    }
+
+    #[test]
+    fn test_io_split_ratio_and_output_cost_share() {
+        // Flat rate (Amazon Q): output's cost share follows its token share
+        let q = IoSplit::for_session(&io_session(3_000, 1_000, 2.0, Vec::new()));
+        assert_eq!(q.ratio(), Some(1_000.0 / 3_000.0));
+        assert!((q.output_cost_percentage() - 25.0).abs() < 1e-9);
+
+        // Per-model pricing (Claude): output is few tokens but most of the cost
+        let claude = io_session(10_000, 2_000, 1.0, vec![ModelUsage {
+            model: "claude-sonnet-4".to_string(),
+            tokens: 12_000,
+            cost: 1.0,
+            output_cost: 0.6,
+        }]);
+        let split = IoSplit::for_session(&claude);
+        assert_eq!(split.ratio(), Some(0.2));
+        assert!((split.output_cost_percentage() - 60.0).abs() < 1e-9);
+
+        let total = IoSplit::total([&io_session(3_000, 1_000, 2.0, Vec::new()), &claude]);
+        assert_eq!((total.input_tokens, total.output_tokens), (13_000, 3_000));
+        assert!((total.output_cost - 1.1).abs() < 1e-9);
+
+        assert_eq!(IoSplit::for_session(&io_session(0, 0, 0.0, Vec::new())).ratio(), None);
+    }
 }
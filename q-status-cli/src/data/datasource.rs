@@ -7,6 +7,7 @@ use crate::data::database::{
     PeriodMetrics, QConversation, Session, TokenUsageDetails
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Local};
 use std::any::Any;
 
 /// Trait for abstracting data source access in q-status-cli
@@ -30,11 +31,18 @@ pub trait DataSource: Send + Sync {
     /// Returns None if no conversation exists.
     async fn get_current_conversation(&self, cwd: Option<&str>) -> Result<Option<QConversation>>;
 
+    /// Look up a conversation by its ID
+    ///
+    /// Used to follow a pinned conversation instead of the latest one.
+    /// Returns None if no conversation has that ID.
+    async fn get_conversation_by_id(&self, conversation_id: &str) -> Result<Option<QConversation>>;
+
     /// Get summaries of all conversations
     ///
     /// Returns a vector of conversation summaries ordered by size (largest first).
-    /// Includes token usage, compaction status, and metadata for each conversation.
-    async fn get_all_conversation_summaries(&self) -> Result<Vec<ConversationSummary>>;
+    /// Includes token usage, compaction status, cost, and metadata for each conversation.
+    /// `cost_per_1k` specifies the cost per 1000 tokens where costs are estimated.
+    async fn get_all_conversation_summaries(&self, cost_per_1k: f64) -> Result<Vec<ConversationSummary>>;
 
     /// Get all sessions with cost calculation
     ///
@@ -65,21 +73,52 @@ pub trait DataSource: Send + Sync {
     /// Analyzes a conversation to determine token usage, compaction status,
     /// and other metrics. This is typically used for the current active conversation.
     async fn get_token_usage(&self, conversation: &QConversation) -> Result<TokenUsageDetails>;
+
+    /// Estimate the cost of a conversation with the given token usage
+    ///
+    /// Defaults to the flat `cost_per_1k` rate; sources that know the real
+    /// price of a conversation (e.g. per-model costs) return that instead.
+    async fn get_conversation_cost(
+        &self,
+        _conversation: &QConversation,
+        usage: &TokenUsageDetails,
+        cost_per_1k: f64,
+    ) -> Result<f64> {
+        Ok((usage.total_tokens as f64 / 1000.0) * cost_per_1k)
+    }
+
+    /// Running token total after each message of a session
+    ///
+    /// Computed on demand for the session being inspected rather than for
+    /// every session on every refresh. Empty when the source has no per-message data.
+    async fn get_token_timeline(&self, _conversation_id: &str) -> Result<Vec<u64>> {
+        Ok(Vec::new())
+    }
+
+    /// When a conversation last saw activity, if the source can tell
+    ///
+    /// Lets a merged source pick the most recent of its sources' current conversations.
+    async fn get_last_activity(&self, _conversation_id: &str) -> Result<Option<DateTime<Local>>> {
+        Ok(None)
+    }
 }
 
 /// Mock implementation of DataSource for testing
 #[cfg(test)]
-use crate::data::database::CompactionStatus;
+use crate::data::database::compaction_status_for;
 
 #[cfg(test)]
 pub struct MockDataSource {
     pub has_changed_response: bool,
+    /// Fail change checks as if the database had been deleted
+    pub unavailable: bool,
     pub conversations: Vec<QConversation>,
     pub summaries: Vec<ConversationSummary>,
     pub sessions: Vec<Session>,
     pub global_stats: Option<GlobalStats>,
     pub period_metrics: Option<PeriodMetrics>,
     pub directory_groups: Vec<DirectoryGroup>,
+    pub token_timeline: Vec<u64>,
 }
 
 #[cfg(test)]
@@ -87,20 +126,36 @@ impl MockDataSource {
     pub fn new() -> Self {
         Self {
             has_changed_response: false,
+            unavailable: false,
             conversations: Vec::new(),
             summaries: Vec::new(),
             sessions: Vec::new(),
             global_stats: None,
             period_metrics: None,
             directory_groups: Vec::new(),
+            token_timeline: Vec::new(),
         }
     }
 }
 
+#[cfg(test)]
+impl Default for MockDataSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 #[async_trait]
 impl DataSource for MockDataSource {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     async fn has_changed(&mut self) -> Result<bool> {
+        if self.unavailable {
+            return Err(crate::utils::error::QStatusError::DatabaseNotFound);
+        }
         Ok(self.has_changed_response)
     }
 
@@ -108,7 +163,11 @@ impl DataSource for MockDataSource {
         Ok(self.conversations.first().cloned())
     }
 
-    async fn get_all_conversation_summaries(&self) -> Result<Vec<ConversationSummary>> {
+    async fn get_conversation_by_id(&self, conversation_id: &str) -> Result<Option<QConversation>> {
+        Ok(self.conversations.iter().find(|c| c.conversation_id == conversation_id).cloned())
+    }
+
+    async fn get_all_conversation_summaries(&self, _cost_per_1k: f64) -> Result<Vec<ConversationSummary>> {
         Ok(self.summaries.clone())
     }
 
@@ -130,6 +189,16 @@ impl DataSource for MockDataSource {
         Ok(self.directory_groups.clone())
     }
 
+    async fn get_token_timeline(&self, _conversation_id: &str) -> Result<Vec<u64>> {
+        Ok(self.token_timeline.clone())
+    }
+
+    async fn get_last_activity(&self, conversation_id: &str) -> Result<Option<DateTime<Local>>> {
+        Ok(self.sessions.iter()
+            .find(|s| s.conversation_id == conversation_id)
+            .map(|s| s.last_activity))
+    }
+
     async fn get_token_usage(&self, conversation: &QConversation) -> Result<TokenUsageDetails> {
         // Simple mock implementation - would be more sophisticated in real tests
         let history_tokens = conversation.history.len() as u64 * 100;
@@ -138,12 +207,7 @@ impl DataSource for MockDataSource {
         let context_window = 175_000u64;
         let percentage = (total_tokens as f64 / context_window as f64) * 100.0;
 
-        let compaction_status = match percentage {
-            p if p < 70.0 => CompactionStatus::Safe,
-            p if p < 90.0 => CompactionStatus::Warning,
-            p if p < 95.0 => CompactionStatus::Critical,
-            _ => CompactionStatus::Imminent,
-        };
+        let compaction_status = compaction_status_for(percentage, &Default::default());
 
         Ok(TokenUsageDetails {
             history_tokens,
@@ -154,6 +218,8 @@ impl DataSource for MockDataSource {
             compaction_status,
             has_summary: conversation.latest_summary.is_some(),
             message_count: conversation.history.len(),
+            input_tokens: 0,
+            output_tokens: 0,
         })
     }
 }
\ No newline at end of file
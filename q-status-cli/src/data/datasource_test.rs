@@ -43,7 +43,7 @@ mod tests {
 
             // Call trait methods through the trait object
             let _changed = datasource.has_changed().await;
-            let _summaries = datasource.get_all_conversation_summaries().await;
+            let _summaries = datasource.get_all_conversation_summaries(0.0).await;
 
             // Again, we're just verifying compilation and that methods can be called
         }
@@ -1,20 +1,29 @@
 // ABOUTME: Factory for creating different data source implementations
 // Supports switching between Amazon Q and Claude Code data sources
 
-use super::{datasource::DataSource, database::QDatabase, claude_datasource::ClaudeCodeDataSource};
+use super::{
+    datasource::DataSource, database::{QDatabase, Q_PRICING_MODEL}, claude_datasource::ClaudeCodeDataSource,
+    merged_datasource::MergedDataSource,
+};
+use crate::app::config::AppConfig;
+use crate::utils::cost_calculator::CostCalculator;
 use crate::utils::error::{Result, QStatusError};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataSourceType {
     AmazonQ,
     ClaudeCode,
+    Merged,
 }
 
 impl DataSourceType {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "amazon-q" | "amazonq" | "q" => Some(Self::AmazonQ),
             "claude-code" | "claudecode" | "claude" => Some(Self::ClaudeCode),
+            "merged" | "all" => Some(Self::Merged),
             _ => None,
         }
     }
@@ -23,6 +32,7 @@ impl DataSourceType {
         match self {
             Self::AmazonQ => "amazon-q",
             Self::ClaudeCode => "claude-code",
+            Self::Merged => "merged",
         }
     }
 
@@ -30,8 +40,23 @@ impl DataSourceType {
         match self {
             Self::AmazonQ => "Amazon Q",
             Self::ClaudeCode => "Claude Code",
+            Self::Merged => "Merged",
         }
     }
+
+    /// Short provider tag used to label entries in the merged view
+    pub fn tag(&self) -> &str {
+        match self {
+            Self::AmazonQ => "Q",
+            Self::ClaudeCode => "CC",
+            Self::Merged => "ALL",
+        }
+    }
+
+    /// Every source, in picker order
+    pub fn all() -> [Self; 3] {
+        [Self::AmazonQ, Self::ClaudeCode, Self::Merged]
+    }
 }
 
 impl std::fmt::Display for DataSourceType {
@@ -44,23 +69,69 @@ pub struct DataSourceFactory;
 
 impl DataSourceFactory {
     /// Create a data source based on the specified type
-    pub fn create(source_type: DataSourceType, _cost_per_1k: f64) -> Result<Box<dyn DataSource>> {
+    pub fn create(source_type: DataSourceType, config: &AppConfig) -> Result<Box<dyn DataSource>> {
         match source_type {
             DataSourceType::AmazonQ => {
-                let db = QDatabase::new()?;
+                let db = QDatabase::with_db_paths(&config.amazon_q_db_paths)?
+                    .with_approximate_tokens(config.use_approximate_tokens)
+                    .with_date_range(config.date_range())
+                    .with_message_quota_limit(config.effective_message_quota_limit())
+                    .with_active_session_days(config.active_session_days)
+                    .with_compaction_thresholds(config.compaction_thresholds())
+                    .with_context_window(config.amazon_q_context_window())
+                    .with_timezone(config.timezone())
+                    .with_blended_pricing(config.amazon_q_auto_blended_rate.then(|| {
+                        CostCalculator::for_config(config).get_model_pricing(Q_PRICING_MODEL)
+                    }));
                 Ok(Box::new(db))
             }
             DataSourceType::ClaudeCode => {
-                let ds = ClaudeCodeDataSource::new()?;
+                let ds = ClaudeCodeDataSource::with_config(config)?;
                 Ok(Box::new(ds))
             }
+            DataSourceType::Merged => {
+                // Merge whichever sources are available
+                let sources: Vec<_> = [DataSourceType::AmazonQ, DataSourceType::ClaudeCode]
+                    .into_iter()
+                    .filter_map(|t| Self::create(t, config).ok().map(|s| (t, s)))
+                    .collect();
+
+                if sources.is_empty() {
+                    return Err(QStatusError::Config("No data sources available to merge".to_string()));
+                }
+                Ok(Box::new(MergedDataSource::new(sources)))
+            }
         }
     }
 
+    /// Where the source's data lives on disk, without loading it
+    pub fn locate(source_type: DataSourceType, config: &AppConfig) -> Result<Vec<PathBuf>> {
+        match source_type {
+            DataSourceType::AmazonQ => QDatabase::find_database(&config.amazon_q_db_paths).map(|path| vec![path]),
+            DataSourceType::ClaudeCode => ClaudeCodeDataSource::get_claude_paths(),
+            DataSourceType::Merged => {
+                let paths: Vec<PathBuf> = [DataSourceType::AmazonQ, DataSourceType::ClaudeCode]
+                    .into_iter()
+                    .filter_map(|t| Self::locate(t, config).ok())
+                    .flatten()
+                    .collect();
+                if paths.is_empty() {
+                    return Err(QStatusError::Config("No data sources available to merge".to_string()));
+                }
+                Ok(paths)
+            }
+        }
+    }
+
+    /// Whether the source's data exists on disk, without loading it
+    pub fn is_detected(source_type: DataSourceType, config: &AppConfig) -> bool {
+        Self::locate(source_type, config).is_ok()
+    }
+
     /// Try to create any available data source, preferring the specified type
-    pub fn create_with_fallback(preferred: DataSourceType, cost_per_1k: f64) -> Result<(Box<dyn DataSource>, DataSourceType)> {
+    pub fn create_with_fallback(preferred: DataSourceType, config: &AppConfig) -> Result<(Box<dyn DataSource>, DataSourceType)> {
         // Try preferred source first
-        if let Ok(source) = Self::create(preferred, cost_per_1k) {
+        if let Ok(source) = Self::create(preferred, config) {
             return Ok((source, preferred));
         }
 
@@ -68,9 +139,13 @@ impl DataSourceFactory {
         let fallback = match preferred {
             DataSourceType::AmazonQ => DataSourceType::ClaudeCode,
             DataSourceType::ClaudeCode => DataSourceType::AmazonQ,
+            // Merged already uses whatever is available
+            DataSourceType::Merged => {
+                return Err(QStatusError::Config("No data source available. Ensure either Amazon Q or Claude Code is installed and has been used.".to_string()));
+            }
         };
 
-        if let Ok(source) = Self::create(fallback, cost_per_1k) {
+        if let Ok(source) = Self::create(fallback, config) {
             eprintln!("Note: {} not available, using {} instead", preferred, fallback);
             return Ok((source, fallback));
         }
@@ -0,0 +1,352 @@
+// ABOUTME: MergedDataSource combining several data sources into one view
+// Fans out each DataSource call and aggregates the results, tagging entries by provider
+
+use crate::data::database::{
+    ConversationSummary, DirectoryGroup, GlobalStats, PeriodMetrics, QConversation, Session,
+    TokenUsageDetails,
+};
+use crate::data::datasource::DataSource;
+use crate::data::factory::DataSourceType;
+use crate::utils::error::{QStatusError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+
+pub struct MergedDataSource {
+    sources: Vec<(DataSourceType, Box<dyn DataSource>)>,
+}
+
+impl MergedDataSource {
+    pub fn new(sources: Vec<(DataSourceType, Box<dyn DataSource>)>) -> Self {
+        Self { sources }
+    }
+
+    /// Find an underlying source of a concrete type
+    pub fn source_as<T: 'static>(&self) -> Option<&T> {
+        self.sources
+            .iter()
+            .find_map(|(_, source)| source.as_any().downcast_ref::<T>())
+    }
+
+    fn tag_session(source_type: DataSourceType, mut session: Session) -> Session {
+        session.provider = Some(source_type);
+        session
+    }
+}
+
+#[async_trait]
+impl DataSource for MergedDataSource {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn has_changed(&mut self) -> Result<bool> {
+        let mut changed = false;
+        let mut first_error = None;
+        let mut any_ok = false;
+
+        for (_, source) in self.sources.iter_mut() {
+            match source.has_changed().await {
+                Ok(c) => {
+                    any_ok = true;
+                    changed |= c;
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        // Only fail when no source could be checked
+        match first_error {
+            Some(e) if !any_ok => Err(e),
+            _ => Ok(changed),
+        }
+    }
+
+    async fn get_current_conversation(&self, cwd: Option<&str>) -> Result<Option<QConversation>> {
+        // Each source has its own idea of current; the most recently active one wins
+        let mut latest: Option<(Option<DateTime<Local>>, QConversation)> = None;
+        for (_, source) in &self.sources {
+            if let Some(conversation) = source.get_current_conversation(cwd).await? {
+                let activity = source.get_last_activity(&conversation.conversation_id).await?;
+                if latest.as_ref().is_none_or(|(best, _)| activity > *best) {
+                    latest = Some((activity, conversation));
+                }
+            }
+        }
+        Ok(latest.map(|(_, conversation)| conversation))
+    }
+
+    async fn get_conversation_by_id(&self, conversation_id: &str) -> Result<Option<QConversation>> {
+        for (_, source) in &self.sources {
+            if let Some(conversation) = source.get_conversation_by_id(conversation_id).await? {
+                return Ok(Some(conversation));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn get_all_conversation_summaries(&self, cost_per_1k: f64) -> Result<Vec<ConversationSummary>> {
+        let mut summaries = Vec::new();
+        for (source_type, source) in &self.sources {
+            for mut summary in source.get_all_conversation_summaries(cost_per_1k).await? {
+                summary.provider = Some(*source_type);
+                summaries.push(summary);
+            }
+        }
+
+        // Keep the largest-first ordering of the individual sources
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.token_usage.total_tokens));
+        Ok(summaries)
+    }
+
+    async fn get_all_sessions(&self, cost_per_1k: f64) -> Result<Vec<Session>> {
+        let mut sessions = Vec::new();
+        for (source_type, source) in &self.sources {
+            sessions.extend(
+                source.get_all_sessions(cost_per_1k).await?
+                    .into_iter()
+                    .map(|s| Self::tag_session(*source_type, s)),
+            );
+        }
+        Ok(sessions)
+    }
+
+    async fn get_global_stats(&self, cost_per_1k: f64) -> Result<GlobalStats> {
+        let mut merged: Option<GlobalStats> = None;
+
+        for (source_type, source) in &self.sources {
+            let mut stats = source.get_global_stats(cost_per_1k).await?;
+            if let Some(largest) = stats.largest_conversation.as_mut() {
+                largest.provider = Some(*source_type);
+            }
+
+            let Some(total) = merged.as_mut() else {
+                merged = Some(stats);
+                continue;
+            };
+
+            total.total_conversations += stats.total_conversations;
+            total.total_tokens += stats.total_tokens;
+            total.conversations_warning += stats.conversations_warning;
+            total.conversations_critical += stats.conversations_critical;
+            total.total_cost_estimate += stats.total_cost_estimate;
+            total.total_messages += stats.total_messages;
+            total.message_quota_used += stats.message_quota_used;
+            total.parse_failures += stats.parse_failures;
+            total.message_quota_limit = total.message_quota_limit.max(stats.message_quota_limit);
+
+            let larger = match (&total.largest_conversation, &stats.largest_conversation) {
+                (Some(a), Some(b)) => b.token_usage.total_tokens > a.token_usage.total_tokens,
+                (None, Some(_)) => true,
+                _ => false,
+            };
+            if larger {
+                total.largest_conversation = stats.largest_conversation;
+            }
+
+            for (model, cost) in stats.cost_by_model {
+                *total.cost_by_model.entry(model).or_insert(0.0) += cost;
+            }
+            for (model, tokens) in stats.tokens_by_model {
+                *total.tokens_by_model.entry(model).or_insert(0) += tokens;
+            }
+        }
+
+        let mut merged = merged
+            .ok_or_else(|| QStatusError::Config("No data sources to merge".to_string()))?;
+        merged.average_tokens = if merged.total_conversations > 0 {
+            merged.total_tokens / merged.total_conversations as u64
+        } else {
+            0
+        };
+        Ok(merged)
+    }
+
+    async fn get_period_metrics(&self, cost_per_1k: f64) -> Result<PeriodMetrics> {
+        let mut merged = PeriodMetrics {
+            today_tokens: 0,
+            today_cost: 0.0,
+            week_tokens: 0,
+            week_cost: 0.0,
+            month_tokens: 0,
+            month_cost: 0.0,
+            year_tokens: 0,
+            year_cost: 0.0,
+        };
+
+        for (_, source) in &self.sources {
+            let metrics = source.get_period_metrics(cost_per_1k).await?;
+            merged.today_tokens += metrics.today_tokens;
+            merged.today_cost += metrics.today_cost;
+            merged.week_tokens += metrics.week_tokens;
+            merged.week_cost += metrics.week_cost;
+            merged.month_tokens += metrics.month_tokens;
+            merged.month_cost += metrics.month_cost;
+            merged.year_tokens += metrics.year_tokens;
+            merged.year_cost += metrics.year_cost;
+        }
+
+        Ok(merged)
+    }
+
+    async fn get_directory_groups(&self, cost_per_1k: f64) -> Result<Vec<DirectoryGroup>> {
+        let mut groups = Vec::new();
+        for (source_type, source) in &self.sources {
+            for mut group in source.get_directory_groups(cost_per_1k).await? {
+                group.sessions = group.sessions
+                    .into_iter()
+                    .map(|s| Self::tag_session(*source_type, s))
+                    .collect();
+                groups.push(group);
+            }
+        }
+        Ok(groups)
+    }
+
+    async fn get_token_usage(&self, conversation: &QConversation) -> Result<TokenUsageDetails> {
+        // Conversation IDs are unique per provider, so ask the source that owns it
+        for (_, source) in &self.sources {
+            if source.get_conversation_by_id(&conversation.conversation_id).await?.is_some() {
+                return source.get_token_usage(conversation).await;
+            }
+        }
+
+        match self.sources.first() {
+            Some((_, source)) => source.get_token_usage(conversation).await,
+            None => Err(QStatusError::Config("No data sources to merge".to_string())),
+        }
+    }
+
+    async fn get_conversation_cost(
+        &self,
+        conversation: &QConversation,
+        usage: &TokenUsageDetails,
+        cost_per_1k: f64,
+    ) -> Result<f64> {
+        for (_, source) in &self.sources {
+            if source.get_conversation_by_id(&conversation.conversation_id).await?.is_some() {
+                return source.get_conversation_cost(conversation, usage, cost_per_1k).await;
+            }
+        }
+        Ok((usage.total_tokens as f64 / 1000.0) * cost_per_1k)
+    }
+
+    async fn get_token_timeline(&self, conversation_id: &str) -> Result<Vec<u64>> {
+        for (_, source) in &self.sources {
+            let timeline = source.get_token_timeline(conversation_id).await?;
+            if !timeline.is_empty() {
+                return Ok(timeline);
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    async fn get_last_activity(&self, conversation_id: &str) -> Result<Option<DateTime<Local>>> {
+        for (_, source) in &self.sources {
+            if let Some(activity) = source.get_last_activity(conversation_id).await? {
+                return Ok(Some(activity));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::datasource::MockDataSource;
+    use std::collections::HashMap;
+
+    fn stats(conversations: usize, tokens: u64, cost: f64, quota_limit: usize) -> GlobalStats {
+        GlobalStats {
+            total_conversations: conversations,
+            total_tokens: tokens,
+            conversations_warning: 1,
+            total_cost_estimate: cost,
+            total_messages: 10,
+            message_quota_used: 10,
+            message_quota_limit: quota_limit,
+            cost_by_model: HashMap::from([("claude-sonnet-4".to_string(), cost)]),
+            ..GlobalStats::default()
+        }
+    }
+
+    fn merged() -> MergedDataSource {
+        let q = MockDataSource {
+            global_stats: Some(stats(2, 1_000, 1.0, 5_000)),
+            sessions: vec![Session::test("/work/api", 100, 0.0)],
+            directory_groups: vec![DirectoryGroup {
+                directory: "/work/api".to_string(),
+                sessions: vec![Session::test("/work/api", 100, 0.0)],
+                total_tokens: 100,
+                total_cost: 0.0,
+                active_session_count: 1,
+            }],
+            ..MockDataSource::new()
+        };
+        let claude = MockDataSource {
+            global_stats: Some(stats(3, 2_000, 2.5, 10_000)),
+            sessions: vec![Session::test("/work/web", 100, 0.0)],
+            ..MockDataSource::new()
+        };
+
+        MergedDataSource::new(vec![
+            (DataSourceType::AmazonQ, Box::new(q)),
+            (DataSourceType::ClaudeCode, Box::new(claude)),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_current_conversation_is_the_most_recently_active() {
+        let source = |id: &str, minutes_ago| {
+            let conversation = serde_json::from_value(serde_json::json!({ "conversation_id": id })).unwrap();
+            let session = Session {
+                last_activity: Local::now() - chrono::Duration::minutes(minutes_ago),
+                ..Session::test(id, 100, 0.0)
+            };
+            MockDataSource { conversations: vec![conversation], sessions: vec![session], ..MockDataSource::new() }
+        };
+        let merged = |q_minutes_ago, claude_minutes_ago| MergedDataSource::new(vec![
+            (DataSourceType::AmazonQ, Box::new(source("q-conv", q_minutes_ago))),
+            (DataSourceType::ClaudeCode, Box::new(source("claude-conv", claude_minutes_ago))),
+        ]);
+
+        let current = merged(30, 5).get_current_conversation(None).await.unwrap().unwrap();
+        assert_eq!(current.conversation_id, "claude-conv");
+        let current = merged(5, 30).get_current_conversation(None).await.unwrap().unwrap();
+        assert_eq!(current.conversation_id, "q-conv");
+    }
+
+    #[tokio::test]
+    async fn test_global_stats_are_summed() {
+        let stats = merged().get_global_stats(0.0).await.unwrap();
+
+        assert_eq!(stats.total_conversations, 5);
+        assert_eq!(stats.total_tokens, 3_000);
+        assert_eq!(stats.average_tokens, 600);
+        assert_eq!(stats.conversations_warning, 2);
+        assert!((stats.total_cost_estimate - 3.5).abs() < 1e-9);
+        assert_eq!(stats.message_quota_used, 20);
+        assert_eq!(stats.message_quota_limit, 10_000);
+        assert!((stats.cost_by_model["claude-sonnet-4"] - 3.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_tagged_by_provider() {
+        let sessions = merged().get_all_sessions(0.0).await.unwrap();
+        let tagged: Vec<(&str, Option<DataSourceType>)> = sessions.iter()
+            .map(|s| (s.directory.as_str(), s.provider))
+            .collect();
+
+        assert_eq!(tagged, vec![
+            ("/work/api", Some(DataSourceType::AmazonQ)),
+            ("/work/web", Some(DataSourceType::ClaudeCode)),
+        ]);
+
+        // Directories stay real paths so they still match the working directory
+        let groups = merged().get_directory_groups(0.0).await.unwrap();
+        assert_eq!(groups[0].directory, "/work/api");
+        assert_eq!(groups[0].sessions[0].provider, Some(DataSourceType::AmazonQ));
+    }
+}
@@ -3,6 +3,7 @@ pub mod database;
 pub mod datasource;
 pub mod claude_datasource;
 pub mod factory;
+pub mod merged_datasource;
 
 #[cfg(test)]
 mod datasource_test;
@@ -14,3 +15,4 @@ pub use database::{QConversation, QDatabase};
 pub use datasource::DataSource;
 pub use claude_datasource::ClaudeCodeDataSource;
 pub use factory::{DataSourceFactory, DataSourceType};
+pub use merged_datasource::MergedDataSource;
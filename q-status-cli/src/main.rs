@@ -2,22 +2,28 @@
 // Sets up terminal, event loop, and coordinates all components
 
 use anyhow::Result;
+use chrono::{DateTime, Local};
 use clap::{Arg, ArgAction, Command};
 use crossbeam_channel::{bounded, Receiver, Sender};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+    event::{self, EnableMouseCapture, Event},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{enable_raw_mode, EnterAlternateScreen},
 };
 use q_status::{
     app::{
-        config::AppConfig,
+        config::{parse_token_limit, AppConfig},
         state::{AppEvent, AppState},
     },
-    ui::dashboard::Dashboard,
+    ui::{
+        dashboard::Dashboard,
+        terminal::{install_panic_hook, spawn_signal_handler, terminal_guard},
+    },
+    utils::date_range::parse_date_bound,
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::task::JoinHandle;
@@ -27,25 +33,109 @@ async fn main() -> Result<()> {
     // Parse CLI arguments
     let mut config = parse_args();
 
+    if config.dump_config {
+        print!("{}", config.dump()?);
+        return Ok(());
+    }
+
+    // Refresh cached remote pricing before any data source reads it;
+    // on failure the last cached copy (or built-in pricing) is used
+    if let Some(url) = &config.pricing_url {
+        if let Err(e) = q_status::utils::CostCalculator::load_from_url(url).await {
+            eprintln!("Warning: Could not fetch pricing from {}: {}", url, e);
+        }
+    }
+
+    // Diagnostics only; non-zero exit when the chosen source can't be used
+    if config.doctor {
+        let report = q_status::app::doctor::run_doctor(&config).await;
+        print!("{}", report.render());
+        if !report.preferred_available {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Validate the parse pipeline; non-zero exit when nothing could be loaded
+    if config.check {
+        match q_status::app::check::run_check(&config).await {
+            Ok(summary) => {
+                print!("{}", summary.render(&config));
+                if !summary.loaded() {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // One-shot CSV export, no dashboard
+    if let Some(path) = config.daily_csv_path.clone() {
+        return export_daily_csv(&config, &path);
+    }
+
+    // One-shot report export, no dashboard
+    if let Some(path) = config.export_path.clone() {
+        return export_report(&config, &path).await;
+    }
+
+    // One status line for tmux or a shell prompt
+    if let Some(template) = &config.oneline_format {
+        let status = q_status::app::oneline::collect_oneline(&config).await?;
+        println!("{}", status.render(template, &config));
+        return Ok(());
+    }
+
+    // Most expensive sessions, independent of the token-based top list
+    if let Some(count) = config.top_cost {
+        let sessions = q_status::app::top_cost::collect_top_cost(&config, count).await?;
+        print!("{}", q_status::app::top_cost::render_top_cost(&sessions, &config));
+        return Ok(());
+    }
+
     // Create app state
     let state = Arc::new(AppState::new(config.clone()));
 
-    // Initialize logging if debug mode
-    if config.debug {
-        tracing_subscriber::fmt()
-            .with_env_filter("q_status=debug")
-            .init();
+    // Log to a file when asked, since stderr is hidden behind the dashboard;
+    // otherwise to stderr in debug mode. The guard flushes the file on exit.
+    let _log_guard = match &config.log_file {
+        Some(path) => {
+            let (writer, guard) = q_status::utils::logging::file_writer(path)?;
+            let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(q_status::utils::logging::DEFAULT_LOG_FILTER));
+            tracing_subscriber::fmt()
+                .with_env_filter(filter)
+                .with_writer(writer)
+                .with_ansi(false)
+                .init();
+            Some(guard)
+        }
+        None => {
+            if config.debug {
+                tracing_subscriber::fmt()
+                    .with_env_filter("q_status=debug")
+                    .init();
+            }
+            None
+        }
+    };
+
+    // Settings read every frame fall back quietly, so report a bad one once here
+    if let Some(warning) = config.timezone_warning() {
+        tracing::warn!("{}", warning);
+        state.log_warning(warning);
     }
 
-    // Check if we're in a TTY
-    if !atty::is(atty::Stream::Stdout) {
+    // Check if we're in a TTY; --stream is meant to be piped, so it skips this
+    if !config.stream && !atty::is(atty::Stream::Stdout) {
         // Non-interactive mode - just print status and exit
         return run_status_check(state.clone()).await;
     }
 
-    // Setup terminal
-    let mut terminal = setup_terminal()?;
-
     // Create event channels
     let (event_tx, event_rx) = bounded::<AppEvent>(100);
 
@@ -60,7 +150,7 @@ async fn main() -> Result<()> {
     // Try to spawn data collector with appropriate data source
     let collector_handle = match q_status::data::DataSourceFactory::create_with_fallback(
         source_type,
-        config.cost_per_1k_tokens,
+        &config,
     ) {
         Ok((data_source, actual_type)) => {
             // Update state with actual data source used
@@ -68,7 +158,7 @@ async fn main() -> Result<()> {
                 state.set_active_data_source(actual_type);
             }
 
-            match spawn_collector_with_source(state.clone(), event_tx.clone(), data_source) {
+            match spawn_collector_with_source(state.clone(), event_tx.clone(), data_source, actual_type) {
                 Ok(handle) => Some(handle),
                 Err(e) => {
                     eprintln!("Warning: Could not start data collector: {}", e);
@@ -84,6 +174,38 @@ async fn main() -> Result<()> {
         }
     };
 
+    // Serve metrics alongside the dashboard
+    let metrics_handle = match config.metrics_port {
+        Some(port) => match q_status::app::metrics::spawn_metrics_server(state.clone(), port).await {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                eprintln!("Warning: Could not start metrics server on port {}: {}", port, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Headless mode: print a JSON line per refresh instead of drawing the dashboard
+    if config.stream {
+        let result = q_status::app::stream::run_stream(state.clone(), event_rx).await;
+
+        if let Some(handle) = collector_handle {
+            handle.abort();
+        }
+        if let Some(handle) = metrics_handle {
+            handle.abort();
+        }
+
+        return Ok(result?);
+    }
+
+    // Setup terminal; from here on it is restored however the loop ends
+    install_panic_hook();
+    let signal_handle = spawn_signal_handler();
+    let terminal_guard = terminal_guard();
+    let mut terminal = setup_terminal()?;
+
     // Spawn input handler
     spawn_input_handler(event_tx.clone());
 
@@ -97,12 +219,26 @@ async fn main() -> Result<()> {
     let result = run_event_loop(&mut terminal, &mut dashboard, event_rx, event_tx, state.clone(), collector_handle.clone()).await;
 
     // Cleanup
-    restore_terminal(&mut terminal)?;
+    drop(terminal_guard);
+    signal_handle.abort();
+
+    // Ids copied without a clipboard, now that the terminal is readable again
+    for text in dashboard.take_uncopied() {
+        println!("{}", text);
+    }
+
+    // Remember view, sort order and filters for next launch
+    if let Err(e) = state.config.save_ui_state(&state.ui_state()) {
+        eprintln!("Warning: Could not save UI state: {}", e);
+    }
 
     // Abort background tasks if they exist
     if let Some(handle) = collector_handle.lock().unwrap().take() {
         handle.abort();
     }
+    if let Some(handle) = metrics_handle {
+        handle.abort();
+    }
 
     result
 }
@@ -117,8 +253,8 @@ fn parse_args() -> AppConfig {
                 .short('r')
                 .long("refresh-rate")
                 .value_name("SECONDS")
-                .help("Refresh rate in seconds")
-                .default_value("2"),
+                .help("Refresh rate in seconds (minimum 1)")
+                .value_parser(clap::value_parser!(u64).range(1..)),
         )
         .arg(
             Arg::new("config")
@@ -132,8 +268,131 @@ fn parse_args() -> AppConfig {
                 .short('s')
                 .long("data-source")
                 .value_name("SOURCE")
-                .help("Data source to use (amazon-q, claude-code)")
-                .value_parser(["amazon-q", "claude-code", "claude", "q"]),
+                .help("Data source to use (amazon-q, claude-code, merged)")
+                .value_parser(["amazon-q", "claude-code", "claude", "q", "merged"]),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("DATE")
+                .help("Only include usage on or after DATE (YYYY-MM-DD or relative, e.g. 7d)")
+                .value_parser(|v: &str| parse_date_bound(v, false, Local::now())),
+        )
+        .arg(
+            Arg::new("until")
+                .long("until")
+                .value_name("DATE")
+                .help("Only include usage on or before DATE (YYYY-MM-DD or relative, e.g. 1d)")
+                .value_parser(|v: &str| parse_date_bound(v, true, Local::now())),
+        )
+        .arg(
+            Arg::new("daily-csv")
+                .long("daily-csv")
+                .value_name("PATH")
+                .help("Write per-day Claude Code usage as CSV to PATH and exit"),
+        )
+        .arg(
+            Arg::new("export")
+                .long("export")
+                .value_name("PATH")
+                .help("Write a usage report to PATH and exit (format from the extension, e.g. report.html)"),
+        )
+        .arg(
+            Arg::new("anonymize")
+                .long("anonymize")
+                .help("Replace directories and conversation ids in exports with stable hashed labels")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("claude-token-limit")
+                .long("claude-token-limit")
+                .value_name("TOKENS|auto")
+                .help("Claude per-block token limit, or 'auto' to detect it from past usage")
+                .value_parser(parse_token_limit),
+        )
+        .arg(
+            Arg::new("cost-mode")
+                .long("cost-mode")
+                .value_name("MODE")
+                .help("How Claude costs are derived: auto (JSONL cost, else tokens), calculate (tokens), display (JSONL cost only)")
+                .value_parser(["auto", "calculate", "display"]),
+        )
+        .arg(
+            Arg::new("model")
+                .long("model")
+                .value_name("SUBSTRING")
+                .help("Only include Claude usage for models containing SUBSTRING (repeatable)")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("metrics-port")
+                .long("metrics-port")
+                .value_name("PORT")
+                .help("Serve Prometheus metrics at http://127.0.0.1:PORT/metrics")
+                .value_parser(clap::value_parser!(u16)),
+        )
+        .arg(
+            Arg::new("top-cost")
+                .long("top-cost")
+                .value_name("N")
+                .help("Print the N most expensive sessions (respects --since/--until) and exit")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_name("N")
+                .help("Top conversations in the non-interactive status output (0 = all; default 5)")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("no-color")
+                .long("no-color")
+                .help("Monochrome, ASCII-only output (also enabled by NO_COLOR)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("stream")
+                .long("stream")
+                .help("Run headless, printing one JSON object per refresh to stdout")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("page-size")
+                .long("page-size")
+                .value_name("ROWS")
+                .help("Rows per page in the session list (default: fit the terminal)")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("dump-config")
+                .long("dump-config")
+                .help("Print the effective configuration, with where each value came from, and exit")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("doctor")
+                .long("doctor")
+                .help("Check data sources, pricing and config file, print PASS/FAIL for each and exit")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Load the chosen data source once, print counts and parse warnings, and exit (non-zero if nothing loaded)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("oneline")
+                .long("oneline")
+                .help("Print a single status line (e.g. for tmux) and exit")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("TEMPLATE")
+                .help("Template for --oneline with {provider} {pct} {used} {limit} {cost}; implies --oneline"),
         )
         .arg(
             Arg::new("debug")
@@ -142,25 +401,85 @@ fn parse_args() -> AppConfig {
                 .help("Enable debug logging")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("log-file")
+                .long("log-file")
+                .value_name("PATH")
+                .help("Write debug logs to a daily-rolled file at PATH (readable while the dashboard runs)"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Show collector warnings in the log pane as well as errors")
+                .action(ArgAction::SetTrue),
+        )
         .get_matches();
 
-    // Load config from file and environment variables first
-    let mut config = AppConfig::load();
-
-    if let Some(rate) = matches.get_one::<String>("refresh-rate") {
-        if let Ok(parsed) = rate.parse() {
-            config.refresh_rate = parsed;
-        }
-    }
+    // Load config from file (--config or the default location) and environment variables first
+    let config_file = matches.get_one::<String>("config").map(PathBuf::from);
+    let mut config = AppConfig::load_from(config_file.as_deref());
 
-    if let Some(config_path) = matches.get_one::<String>("config") {
-        config.config_path = Some(config_path.into());
+    // Only override the config file / env value when given explicitly
+    if let Some(rate) = matches.get_one::<u64>("refresh-rate") {
+        config.refresh_rate = *rate;
+        config.set_override("refresh_rate", "--refresh-rate");
     }
 
     config.debug = matches.get_flag("debug");
+    config.log_file = matches.get_one::<String>("log-file").map(PathBuf::from);
+    config.verbose = matches.get_flag("verbose");
 
     if let Some(source) = matches.get_one::<String>("data-source") {
         config.data_source = source.clone();
+        config.set_override("data_source", "--data-source");
+    }
+
+    config.since = matches.get_one::<DateTime<Local>>("since").copied();
+    config.until = matches.get_one::<DateTime<Local>>("until").copied();
+    config.daily_csv_path = matches.get_one::<String>("daily-csv").map(PathBuf::from);
+    config.stream = matches.get_flag("stream");
+    config.oneline_format = match matches.get_one::<String>("format") {
+        Some(template) => Some(template.clone()),
+        None if matches.get_flag("oneline") => Some(q_status::app::oneline::DEFAULT_ONELINE_FORMAT.to_string()),
+        None => None,
+    };
+    config.no_color |= matches.get_flag("no-color");
+    config.top_cost = matches.get_one::<usize>("top-cost").copied();
+    config.dump_config = matches.get_flag("dump-config");
+    config.doctor = matches.get_flag("doctor");
+    config.check = matches.get_flag("check");
+    config.export_path = matches.get_one::<String>("export").map(PathBuf::from);
+    config.anonymize = matches.get_flag("anonymize");
+
+    if let Some(top) = matches.get_one::<usize>("top") {
+        config.top_conversations = *top;
+        config.set_override("top_conversations", "--top");
+    }
+
+    if let Some(limit) = matches.get_one::<usize>("claude-token-limit") {
+        config.claude_token_limit = *limit;
+        config.set_override("claude_token_limit", "--claude-token-limit");
+    }
+
+    if let Some(mode) = matches.get_one::<String>("cost-mode") {
+        config.cost_mode = mode.clone();
+        config.set_override("cost_mode", "--cost-mode");
+    }
+
+    if let Some(models) = matches.get_many::<String>("model") {
+        config.model_filters = models.cloned().collect();
+        config.set_override("model_filters", "--model");
+    }
+
+    if let Some(rows) = matches.get_one::<usize>("page-size") {
+        config.session_page_size = Some(*rows);
+        config.set_override("session_page_size", "--page-size");
+    }
+
+    if let Some(port) = matches.get_one::<u16>("metrics-port") {
+        config.metrics_port = Some(*port);
+        config.set_override("metrics_port", "--metrics-port");
     }
 
     config
@@ -174,17 +493,6 @@ fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     Ok(Terminal::new(backend)?)
 }
 
-fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-    Ok(())
-}
-
 fn spawn_input_handler(tx: Sender<AppEvent>) {
     std::thread::spawn(move || loop {
         if event::poll(Duration::from_millis(100)).unwrap() {
@@ -215,14 +523,13 @@ async fn run_event_loop(
                         break; // Quit requested
                     }
 
+                    if dashboard.take_refresh_request() {
+                        let _ = event_tx.send(AppEvent::ForceRefresh);
+                    }
+
                     // Check if provider switch was requested
-                    if dashboard.is_switching_provider() {
+                    if let Some(new_source) = dashboard.requested_source() {
                         // Handle provider switching
-                        let current_source = state.get_active_data_source();
-                        let new_source = match current_source {
-                            q_status::data::DataSourceType::AmazonQ => q_status::data::DataSourceType::ClaudeCode,
-                            q_status::data::DataSourceType::ClaudeCode => q_status::data::DataSourceType::AmazonQ,
-                        };
 
                         // Abort the current collector
                         if let Some(handle) = collector_handle.lock().unwrap().take() {
@@ -230,13 +537,14 @@ async fn run_event_loop(
                         }
 
                         // Try to create new data source
-                        match q_status::data::DataSourceFactory::create(new_source, state.config.cost_per_1k_tokens) {
+                        match q_status::data::DataSourceFactory::create(new_source, &state.config) {
                             Ok(data_source) => {
                                 // Spawn new collector with new data source
                                 match q_status::data::spawn_collector_with_datasource(
                                     state.clone(),
                                     event_tx.clone(),
                                     data_source,
+                                    new_source,
                                 ) {
                                     Ok(new_handle) => {
                                         // Update state with new data source
@@ -268,6 +576,10 @@ async fn run_event_loop(
                 AppEvent::Resize(_, _) => {
                     // Terminal will handle resize automatically
                 }
+                AppEvent::ForceRefresh => {
+                    // Wake the collector to re-collect immediately
+                    state.request_refresh();
+                }
                 AppEvent::Quit => break,
                 _ => {}
             }
@@ -282,32 +594,93 @@ fn spawn_collector_with_source(
     state: Arc<AppState>,
     event_tx: Sender<AppEvent>,
     data_source: Box<dyn q_status::data::DataSource>,
+    source_type: q_status::data::DataSourceType,
 ) -> Result<tokio::task::JoinHandle<()>> {
     use q_status::data::DataCollector;
 
-    let collector = DataCollector::new(state, data_source, event_tx)?;
+    let collector = DataCollector::new(state, data_source, event_tx)?
+        .with_factory_reopen(source_type);
     let handle = tokio::spawn(async move {
         collector.run().await;
     });
     Ok(handle)
 }
 
+fn export_daily_csv(config: &AppConfig, path: &std::path::Path) -> Result<()> {
+    use q_status::data::{claude_datasource::ClaudeCodeDataSource, DataSourceType};
+    use q_status::utils::daily_usage::{aggregate_daily_usage, write_daily_csv};
+
+    let source_type = DataSourceType::from_str(&config.data_source)
+        .unwrap_or(DataSourceType::AmazonQ);
+    if source_type != DataSourceType::ClaudeCode {
+        anyhow::bail!("--daily-csv needs per-message timestamps, which only the Claude Code data source provides (use --data-source claude-code)");
+    }
+
+    let data_source = ClaudeCodeDataSource::with_config(config)?;
+    let days = aggregate_daily_usage(&data_source.get_session_blocks()?, config.timezone());
+
+    let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+    write_daily_csv(&mut file, &days)?;
+    println!("Wrote {} days of usage to {}", days.len(), path.display());
+
+    Ok(())
+}
+
+async fn export_report(config: &AppConfig, path: &std::path::Path) -> Result<()> {
+    use q_status::app::config::ExportFormat;
+    use q_status::data::{DataSourceFactory, DataSourceType};
+    use q_status::utils::html_report::ReportData;
+    use q_status::utils::report::render_report;
+
+    let format = ExportFormat::from_path(path).unwrap_or_else(|| config.export_format.clone());
+
+    let source_type = DataSourceType::from_str(&config.data_source)
+        .unwrap_or(DataSourceType::AmazonQ);
+    let (data_source, actual_type) = DataSourceFactory::create_with_fallback(source_type, config)?;
+
+    let mut data = ReportData {
+        source: actual_type.display_name().to_string(),
+        generated_at: Local::now(),
+        stats: data_source.get_global_stats(config.cost_per_1k_tokens).await?,
+        sessions: data_source.get_all_sessions(config.cost_per_1k_tokens).await?,
+        cost_decimals: config.cost_decimals,
+        display_currency: config.display_currency.clone(),
+        usd_exchange_rate: config.usd_exchange_rate,
+    };
+
+    // The mapping back to real paths goes to the terminal only, never the report
+    if config.anonymize {
+        let mut anonymizer = q_status::utils::anonymize::Anonymizer::default();
+        anonymizer.anonymize_report(&mut data);
+        eprint!("Anonymized labels:\n{}", anonymizer.mapping());
+    }
+
+    std::fs::write(path, render_report(&data, &format))?;
+    println!("Wrote report for {} sessions to {}", data.sessions.len(), path.display());
+
+    Ok(())
+}
+
 async fn run_status_check(state: Arc<AppState>) -> Result<()> {
     use q_status::data::{DataSourceFactory, DataSourceType};
 
     let source_type = DataSourceType::from_str(&state.config.data_source)
         .unwrap_or(DataSourceType::AmazonQ);
 
+    // Emoji and symbols degrade to ASCII with --no-color / NO_COLOR
+    let color = state.config.color_enabled();
+    let icon = |emoji: &'static str, ascii: &'static str| if color { emoji } else { ascii };
+
     println!("Q-Status Monitor - {} System Overview", source_type.display_name());
     println!("==========================================");
 
     // Try to connect to appropriate data source
-    match DataSourceFactory::create_with_fallback(source_type, state.config.cost_per_1k_tokens) {
+    match DataSourceFactory::create_with_fallback(source_type, &state.config) {
         Ok((mut data_source, actual_type)) => {
             if actual_type != source_type {
                 println!("Note: Using {} (requested {} not available)", actual_type, source_type);
             } else {
-                println!("✓ Connected to {} data source", actual_type);
+                println!("{} Connected to {} data source", icon("✓", "+"), actual_type);
             }
             println!();
             
@@ -315,27 +688,29 @@ async fn run_status_check(state: Arc<AppState>) -> Result<()> {
             let global_stats = futures::executor::block_on(
                 data_source.get_global_stats(state.config.cost_per_1k_tokens)
             )?;
-            println!("📊 System-Wide Statistics:");
+            println!("{}System-Wide Statistics:", icon("📊 ", ""));
             println!("  - Total Conversations: {}", global_stats.total_conversations);
             println!("  - Total Tokens Used: {}", global_stats.total_tokens);
             println!("  - Average per Conversation: {} tokens", global_stats.average_tokens);
-            println!("  - Conversations at Warning: {} (70-90%)", global_stats.conversations_warning);
-            println!("  - Conversations Critical: {} (90%+)", global_stats.conversations_critical);
-            println!("  - Total Estimated Cost: ${:.2}", global_stats.total_cost_estimate);
+            let thresholds = state.config.compaction_thresholds();
+            println!("  - Conversations at Warning: {} ({:.0}-{:.0}%)",
+                global_stats.conversations_warning, thresholds.warning, thresholds.critical);
+            println!("  - Conversations Critical: {} ({:.0}%+)",
+                global_stats.conversations_critical, thresholds.critical);
+            println!("  - Total Estimated Cost: {}", state.config.format_cost(global_stats.total_cost_estimate));
             println!();
             
             // Get all conversation summaries
             let summaries = futures::executor::block_on(
-                data_source.get_all_conversation_summaries()
+                data_source.get_all_conversation_summaries(state.config.cost_per_1k_tokens)
             )?;
-            println!("🔝 Top Conversations by Token Usage:");
-            for (idx, conv) in summaries.iter().take(5).enumerate() {
-                let status_emoji = match conv.token_usage.compaction_status {
-                    q_status::data::database::CompactionStatus::Safe => "🟢",
-                    q_status::data::database::CompactionStatus::Warning => "🟡",
-                    q_status::data::database::CompactionStatus::Critical => "🟠",
-                    q_status::data::database::CompactionStatus::Imminent => "🔴",
-                };
+            println!("{}Top Conversations by Token Usage:", icon("🔝 ", ""));
+            let top = match state.config.top_conversations {
+                0 => summaries.len(),
+                n => n,
+            };
+            for (idx, conv) in summaries.iter().take(top).enumerate() {
+                let status_emoji = conv.token_usage.compaction_status.indicator(color);
                 let path_display = if conv.path.len() > 50 {
                     format!("...{}", &conv.path[conv.path.len()-47..])
                 } else {
@@ -352,7 +727,7 @@ async fn run_status_check(state: Arc<AppState>) -> Result<()> {
             println!();
             
             // Get latest conversation (most recently modified)
-            println!("📍 Latest Conversation:");
+            println!("{}Latest Conversation:", icon("📍 ", ""));
             match futures::executor::block_on(
                 data_source.get_current_conversation(None)
             ) {
@@ -360,7 +735,7 @@ async fn run_status_check(state: Arc<AppState>) -> Result<()> {
                     let usage_details = futures::executor::block_on(
                         data_source.get_token_usage(&conv)
                     )?;
-                    println!("✓ Active conversation found");
+                    println!("{} Active conversation found", icon("✓", "+"));
                     println!("  - Conversation ID: {}", conv.conversation_id);
                     println!("  - Message count: {} exchanges", usage_details.message_count);
                     println!("  - Conversation tokens: {}", usage_details.history_tokens);
@@ -369,28 +744,29 @@ async fn run_status_check(state: Arc<AppState>) -> Result<()> {
                         usage_details.total_tokens, 
                         usage_details.context_window, 
                         usage_details.percentage);
-                    println!("  - Note: Using 175K effective limit (safer than 200K actual)");
+                    if state.config.amazon_q_use_effective_limit {
+                        println!("  - Note: Using {}K effective limit (safer than {}K actual)",
+                            state.config.amazon_q_context_window() / 1_000,
+                            state.config.amazon_q_actual_limit / 1_000);
+                    }
                     
                     // Show compaction status
-                    let status_emoji = match usage_details.compaction_status {
-                        q_status::data::database::CompactionStatus::Safe => "🟢",
-                        q_status::data::database::CompactionStatus::Warning => "🟡",
-                        q_status::data::database::CompactionStatus::Critical => "🟠",
-                        q_status::data::database::CompactionStatus::Imminent => "🔴",
-                    };
+                    let status_emoji = usage_details.compaction_status.indicator(color);
                     println!("  - Compaction status: {} {:?}", status_emoji, usage_details.compaction_status);
                     
                     // Calculate costs
                     let cost_per_1k = state.config.cost_per_1k_tokens;
-                    let session_cost = (usage_details.total_tokens as f64 / 1000.0) * cost_per_1k;
-                    println!("  - Estimated session cost: ${:.4}", session_cost);
+                    let session_cost = futures::executor::block_on(
+                        data_source.get_conversation_cost(&conv, &usage_details, cost_per_1k)
+                    )?;
+                    println!("  - Estimated session cost: {}", state.config.format_cost(session_cost));
                 }
                 Ok(None) => {
                     println!("! No conversations found in the database");
                     println!("  Start using Q to see activity here");
                 }
                 Err(e) => {
-                    println!("✗ Error reading conversation: {}", e);
+                    println!("{} Error reading conversation: {}", icon("✗", "x"), e);
                 }
             }
             
@@ -400,18 +776,18 @@ async fn run_status_check(state: Arc<AppState>) -> Result<()> {
             ) {
                 Ok(changed) => {
                     if changed {
-                        println!("✓ Data source has recent activity");
+                        println!("{} Data source has recent activity", icon("✓", "+"));
                     } else {
                         println!("  Data source is idle");
                     }
                 }
                 Err(e) => {
-                    println!("✗ Error checking data source status: {}", e);
+                    println!("{} Error checking data source status: {}", icon("✗", "x"), e);
                 }
             }
         }
         Err(e) => {
-            println!("✗ Data source not available: {}", e);
+            println!("{} Data source not available: {}", icon("✗", "x"), e);
             println!();
             println!("Expected locations:");
             if source_type == DataSourceType::AmazonQ {
@@ -1,52 +1,313 @@
 // ABOUTME: Main dashboard layout and rendering logic
 // Implements the primary UI following Ratatui best practices
 
-use crate::app::state::AppState;
+use crate::app::state::{AppState, BurnRateUnit, LogLevel};
+use crate::data::{DataSourceFactory, DataSourceType};
+use crate::ui::theme::Palette;
+use crate::utils::budget::BudgetStatus;
+use crate::utils::session_blocks::{display_blocks, BlockStatus};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph, List, ListItem, ListState},
+    symbols,
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, Gauge, GraphType, Paragraph, List, ListItem, ListState, Sparkline, Wrap},
     Frame,
 };
+use std::cell::Cell;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Page size assumed before any list has been rendered
+const DEFAULT_PAGE_SIZE: usize = 10;
+/// How long a footer confirmation stays visible
+const FLASH_DURATION: Duration = Duration::from_secs(3);
+/// How long a new collector error shows in the footer
+const ERROR_TOAST_SECONDS: i64 = 10;
+/// Entries listed in the log pane
+const LOG_PANE_ENTRIES: usize = 20;
+/// Minutes before a block reset when the countdown is highlighted
+const BLOCK_RESET_SOON_MINUTES: i64 = 10;
+
+/// Data-source picker popup: each source with whether it was found on disk
+#[derive(Debug, Clone)]
+struct SourcePicker {
+    options: Vec<(DataSourceType, bool)>,
+    selected: usize,
+}
+
+impl SourcePicker {
+    /// Detect every source, starting on the active one
+    fn detect(current: DataSourceType, config: &crate::app::config::AppConfig) -> Self {
+        let options = DataSourceType::all()
+            .into_iter()
+            .map(|source| (source, DataSourceFactory::is_detected(source, config)))
+            .collect();
+        Self::new(options, current)
+    }
+
+    fn new(options: Vec<(DataSourceType, bool)>, current: DataSourceType) -> Self {
+        let selected = options.iter().position(|(source, _)| *source == current).unwrap_or(0);
+        Self { options, selected }
+    }
+}
 
 pub struct Dashboard {
     state: Arc<AppState>,
     show_help: bool,
+    /// First help line shown, clamped to the overlay height when rendered
+    help_scroll: Cell<usize>,
+    /// Collector log pane, toggled with `!`
+    show_log: bool,
+    source_picker: Option<SourcePicker>,
+    /// Source chosen in the picker, waiting for the main loop to restart the collector
+    requested_source: Option<DataSourceType>,
     switching_provider: bool,
+    refresh_requested: bool,
+    /// Rows visible in the last rendered list, used for PageUp/PageDown
+    page_size: Cell<usize>,
+    /// First session-list row shown, kept between frames so scrolling is stable
+    session_list_offset: Cell<usize>,
+    /// Transient footer confirmation and when it was shown
+    flash: Option<(String, Instant)>,
+    /// False renders monochrome with ASCII status markers
+    color_enabled: bool,
+    /// Foreground colors for the terminal's background
+    palette: Palette,
+    /// Keystrokes go to the conversation search query
+    search_editing: bool,
+    /// Text that couldn't reach the clipboard, printed once the terminal is restored
+    uncopied: Vec<String>,
 }
 
 impl Dashboard {
     pub fn new(state: Arc<AppState>) -> Self {
         Self {
+            color_enabled: state.config.color_enabled(),
+            palette: Palette::for_theme(&state.config.theme),
             state,
             show_help: false,
+            help_scroll: Cell::new(0),
+            show_log: false,
+            source_picker: None,
+            requested_source: None,
             switching_provider: false,
+            refresh_requested: false,
+            page_size: Cell::new(DEFAULT_PAGE_SIZE),
+            session_list_offset: Cell::new(0),
+            flash: None,
+            search_editing: false,
+            uncopied: Vec::new(),
+        }
+    }
+
+    /// Change the search query, moving the selection back to the first match
+    fn edit_search(&mut self, edit: impl FnOnce(&mut String)) {
+        edit(self.state.search_query.lock().unwrap().get_or_insert_with(String::new));
+        *self.state.selected_conversation_index.lock().unwrap() = 0;
+    }
+
+    fn clear_search(&mut self) {
+        self.search_editing = false;
+        *self.state.search_query.lock().unwrap() = None;
+        *self.state.selected_conversation_index.lock().unwrap() = 0;
+    }
+
+    /// Footer confirmation, while it is still fresh
+    fn active_flash(&self) -> Option<&str> {
+        self.flash
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < FLASH_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// The newest collector error, while it is recent enough to toast
+    fn error_toast(&self) -> Option<String> {
+        let entry = self.state.recent_log(1).pop()?;
+        let fresh = (chrono::Local::now() - entry.at).num_seconds() < ERROR_TOAST_SECONDS;
+        (fresh && entry.level == LogLevel::Error).then_some(entry.message)
+    }
+
+    /// First row of the session or conversation list for `directory`
+    fn row_for_directory(&self, view_mode: &crate::app::state::ViewMode, directory: &str) -> Option<usize> {
+        match view_mode {
+            crate::app::state::ViewMode::SessionList => self.state.visible_sessions()
+                .iter()
+                .position(|session| session.directory == directory),
+            crate::app::state::ViewMode::ConversationList => self.state.visible_conversations()
+                .iter()
+                .position(|conv| conv.path == directory),
+            _ => None,
+        }
+    }
+
+    /// The session under the cursor in the list, or the one open in detail view
+    fn selected_session_for(&self, view_mode: &crate::app::state::ViewMode) -> Option<crate::data::database::Session> {
+        match view_mode {
+            crate::app::state::ViewMode::SessionDetail => self.state.selected_session.lock().unwrap().clone(),
+            crate::app::state::ViewMode::SessionList => {
+                let selected_idx = *self.state.selected_conversation_index.lock().unwrap();
+                self.state.visible_sessions().get(selected_idx).cloned()
+            }
+            _ => None,
+        }
+    }
+
+    /// Sessions shown by the view, with its filters and sort applied
+    fn sessions_for_export(&self, view_mode: &crate::app::state::ViewMode) -> Vec<crate::data::database::Session> {
+        match view_mode {
+            crate::app::state::ViewMode::SessionDetail => self.selected_session_for(view_mode).into_iter().collect(),
+            crate::app::state::ViewMode::ConversationList => {
+                let sessions = self.state.all_sessions.lock().unwrap();
+                self.state.visible_conversations()
+                    .iter()
+                    .filter_map(|conv| sessions.iter().find(|s| s.conversation_id == conv.conversation_id).cloned())
+                    .collect()
+            }
+            _ => self.state.visible_sessions(),
+        }
+    }
+
+    /// Write the current view to a timestamped file in `export_dir`, in the
+    /// configured export format, returning the path, the sessions written and,
+    /// when anonymizing, the file holding the label mapping
+    fn export_current_view(
+        &self,
+        view_mode: &crate::app::state::ViewMode,
+    ) -> crate::utils::error::Result<(std::path::PathBuf, usize, Option<std::path::PathBuf>)> {
+        use std::io::Write as _;
+
+        let stats = self.state.global_stats.lock().unwrap().clone().ok_or_else(|| {
+            crate::utils::error::QStatusError::Config("No data collected yet".to_string())
+        })?;
+        let config = &self.state.config;
+        let mut data = crate::utils::html_report::ReportData {
+            source: self.state.get_active_data_source().display_name().to_string(),
+            generated_at: chrono::Local::now(),
+            stats,
+            sessions: self.sessions_for_export(view_mode),
+            cost_decimals: config.cost_decimals,
+            display_currency: config.display_currency.clone(),
+            usd_exchange_rate: config.usd_exchange_rate,
+        };
+
+        // Stderr is hidden behind the dashboard, so the mapping goes to a file
+        // kept apart from the reports it decodes
+        let anonymizer = config.anonymize.then(|| {
+            let mut anonymizer = crate::utils::anonymize::Anonymizer::default();
+            anonymizer.anonymize_report(&mut data);
+            anonymizer
+        });
+        let mapping_path = match anonymizer {
+            Some(_) => Some(config.anonymize_mapping_path().ok_or_else(|| {
+                crate::utils::error::QStatusError::Config("No config directory for the label mapping".to_string())
+            })?),
+            None => None,
+        };
+
+        let dir = config.export_dir.clone().unwrap_or_else(|| std::path::PathBuf::from("."));
+        std::fs::create_dir_all(&dir)?;
+        let stem = format!("q-status-{}", data.generated_at.format("%Y%m%d-%H%M%S"));
+        let (path, mut file) = create_export_file(&dir, &stem, config.export_format.extension())?;
+
+        if let (Some(anonymizer), Some(labels)) = (&anonymizer, &mapping_path) {
+            let written = labels
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::OpenOptions::new().create(true).append(true).open(labels))
+                .and_then(|mut mapping| write!(mapping, "# {}\n{}", path.display(), anonymizer.mapping()));
+            // A report nobody can decode is worse than none
+            if let Err(e) = written {
+                let _ = std::fs::remove_file(&path);
+                return Err(e.into());
+            }
         }
+
+        file.write_all(crate::utils::report::render_report(&data, &config.export_format).as_bytes())?;
+        Ok((path, data.sessions.len(), mapping_path))
     }
 
     pub fn render(&self, frame: &mut Frame) {
         let size = frame.size();
 
+        // Below the minimum the fixed-height panels can't fit, so say so instead
+        if size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT {
+            let message = Paragraph::new(format!(
+                "Terminal too small (need {}×{}, have {}×{})",
+                MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT, size.width, size.height
+            ))
+            .wrap(Wrap { trim: true })
+            .alignment(Alignment::Center);
+            let row = Rect { y: size.y + size.height / 2, height: size.height - size.height / 2, ..size };
+            frame.render_widget(message, row);
+            return;
+        }
+
         // Main layout: header, body, footer
+        let header_height = if self.state.get_source_error().is_some() { 4 } else { 3 };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(3), // Header
+                Constraint::Length(header_height), // Header, plus a line for the error banner
                 Constraint::Min(0),    // Body
                 Constraint::Length(3), // Footer
             ])
             .split(size);
 
         self.render_header(frame, chunks[0]);
-        self.render_body(frame, chunks[1]);
+
+        // Limit warning takes a row above the body while it applies
+        if let Some(warning) = self.limit_warning() {
+            let body_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(chunks[1]);
+            let banner = Paragraph::new(warning)
+                .style(Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD))
+                .alignment(Alignment::Center);
+            frame.render_widget(banner, body_chunks[0]);
+            self.render_body(frame, body_chunks[1]);
+        } else {
+            self.render_body(frame, chunks[1]);
+        }
+
         self.render_footer(frame, chunks[2]);
 
         // Overlay help if requested
         if self.show_help {
             self.render_help_overlay(frame, size);
         }
+        if self.show_log {
+            self.render_log_pane(frame, size);
+        }
+        if let Some(picker) = &self.source_picker {
+            self.render_source_picker(frame, size, picker);
+        }
+
+        // Monochrome: drop every color but keep bold/underline for emphasis
+        if !self.color_enabled {
+            for cell in frame.buffer_mut().content.iter_mut() {
+                cell.set_fg(Color::Reset).set_bg(Color::Reset);
+            }
+        } else {
+            self.palette.apply(frame.buffer_mut());
+        }
+    }
+
+    /// `icon` in the dashboard's color mode
+    fn icon(&self, icon: Icon) -> &'static str {
+        icon.glyph(self.color_enabled)
+    }
+
+    /// Banner text when the active Claude block will run out before it resets
+    fn limit_warning(&self) -> Option<String> {
+        let block = self.state.get_active_claude_block()?;
+        let minutes = crate::utils::session_blocks::minutes_until_limit(&block, self.state.claude_token_limit())?;
+        Some(if minutes == 0 {
+            "⚠ Block token limit reached".to_string()
+        } else {
+            format!("⚠ Block will hit limit in ~{}m at current rate", minutes)
+        })
     }
 
     fn render_header(&self, frame: &mut Frame, area: Rect) {
@@ -68,7 +329,7 @@ impl Dashboard {
             Color::Red
         };
 
-        let header_text = vec![
+        let mut header_text = vec![
             Span::styled(
                 "Q-Status Monitor",
                 Style::default().add_modifier(Modifier::BOLD),
@@ -82,8 +343,21 @@ impl Dashboard {
             Span::styled(status, Style::default().fg(status_color)),
             Span::raw("]"),
         ];
+        let last_refresh = *self.state.last_refresh.lock().unwrap();
+        if let Some(stale) = stale_label(last_refresh, chrono::Local::now(), self.state.config.stale_after_secs) {
+            header_text.push(Span::raw(" "));
+            header_text.push(Span::styled(stale, Style::default().fg(Color::Yellow)));
+        }
+
+        let mut lines = vec![Line::from(header_text)];
+        if let Some(error) = self.state.get_source_error() {
+            lines.push(Line::from(Span::styled(
+                format!("⚠ {}", error),
+                Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            )));
+        }
 
-        let header = Paragraph::new(Line::from(header_text))
+        let header = Paragraph::new(lines)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
@@ -122,6 +396,22 @@ impl Dashboard {
                         self.render_token_gauge(frame, chunks[1]);
                         self.render_cost_panel(frame, chunks[2]);
                         self.render_usage_info(frame, chunks[3]);
+                    } else if let BlockStatus::Idle { last_activity } = self.state.claude_block_status() {
+                        // Outside any billing block: say so instead of dropping the panel
+                        let chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([
+                                Constraint::Length(3),  // Idle notice
+                                Constraint::Length(6),  // Token usage gauge
+                                Constraint::Length(4),  // Cost analysis
+                                Constraint::Min(10),    // Session details
+                            ])
+                            .split(area);
+
+                        self.render_idle_session(frame, chunks[0], last_activity);
+                        self.render_token_gauge(frame, chunks[1]);
+                        self.render_cost_panel(frame, chunks[2]);
+                        self.render_usage_info(frame, chunks[3]);
                     } else {
                         // No active session - standard layout
                         let chunks = Layout::default()
@@ -162,52 +452,184 @@ impl Dashboard {
             crate::app::state::ViewMode::SessionDetail => {
                 self.render_session_detail(frame, area);
             }
+            crate::app::state::ViewMode::UsageGraph => {
+                self.render_usage_graph(frame, area);
+            }
+            crate::app::state::ViewMode::Timeline => {
+                self.render_timeline(frame, area);
+            }
+        }
+    }
+
+    fn render_timeline(&self, frame: &mut Frame, area: Rect) {
+        let all_blocks = self.state.claude_blocks.lock().unwrap();
+        let blocks = display_blocks(&all_blocks, self.state.config.show_gap_blocks);
+        let block = Block::default()
+            .title("Billing Timeline (newest first)")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+
+        if blocks.is_empty() {
+            let msg = Paragraph::new("No billing blocks yet - the timeline is built from Claude Code usage")
+                .block(block)
+                .alignment(Alignment::Center);
+            frame.render_widget(msg, area);
+            return;
         }
+
+        let items: Vec<ListItem> = blocks
+            .iter()
+            .rev()
+            .map(|b| {
+                if b.is_gap {
+                    // Idle time explains why the next block started fresh
+                    ListItem::new(Line::from(Span::styled(
+                        format!("   ⏸ idle {}", format_duration(b.end_time - b.start_time)),
+                        Style::default().fg(Color::DarkGray),
+                    )))
+                } else {
+                    let zone = self.state.config.timezone();
+                    let end = b.actual_end_time.unwrap_or(b.end_time);
+                    let (marker, color) = if b.is_active { ("▶", Color::Green) } else { ("●", Color::Cyan) };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{} ", marker), Style::default().fg(color)),
+                        Span::raw(format!(
+                            "{} {}–{}  ",
+                            zone.format(&b.start_time, "%Y-%m-%d"),
+                            zone.format(&b.start_time, "%H:%M"),
+                            zone.format(&end, "%H:%M")
+                        )),
+                        Span::styled(
+                            format!("{:>7} tokens", format_token_count(b.token_counts.total_tokens())),
+                            Style::default().fg(Color::Yellow),
+                        ),
+                        Span::styled(format!("  {}", self.state.config.format_cost(b.cost_usd)), Style::default().fg(Color::Green)),
+                        Span::raw(if b.is_active { "  [active]" } else { "" }),
+                    ]))
+                }
+            })
+            .collect();
+
+        frame.render_widget(List::new(items).block(block), area);
+    }
+
+    fn render_usage_graph(&self, frame: &mut Frame, area: Rect) {
+        let history = self.state.usage_history.lock().unwrap();
+        let burn_rate = self.state.burn_rate.lock().unwrap();
+        let retention_hours = self.state.config.history_retention_hours;
+
+        let title = format!(
+            "Token Usage - Last {}h | {} {:.1} tokens/min",
+            retention_hours, self.icon(Icon::Burn), burn_rate.ema_tokens_per_minute
+        );
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+
+        let (Some(first), Some(last)) = (history.first(), history.last()) else {
+            let msg = Paragraph::new("No usage history yet - samples are recorded on each refresh")
+                .block(block)
+                .alignment(Alignment::Center);
+            frame.render_widget(msg, area);
+            return;
+        };
+
+        // x = seconds since the oldest retained sample, y = tokens
+        let points: Vec<(f64, f64)> = history
+            .iter()
+            .map(|(timestamp, tokens)| ((*timestamp - first.0).num_seconds() as f64, *tokens as f64))
+            .collect();
+        let x_max = ((last.0 - first.0).num_seconds() as f64).max(1.0);
+        let y_max = (history.iter().map(|(_, tokens)| *tokens).max().unwrap_or(0) as f64 * 1.1).max(1.0);
+        let midpoint = first.0 + (last.0 - first.0) / 2;
+        let zone = self.state.config.timezone();
+
+        let dataset = Dataset::default()
+            .name("tokens")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&points);
+
+        let chart = Chart::new(vec![dataset])
+            .block(block)
+            .x_axis(
+                Axis::default()
+                    .title("Time")
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([0.0, x_max])
+                    .labels(vec![
+                        Span::raw(zone.format(&first.0, "%H:%M")),
+                        Span::raw(zone.format(&midpoint, "%H:%M")),
+                        Span::raw(zone.format(&last.0, "%H:%M")),
+                    ]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title("Tokens")
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([0.0, y_max])
+                    .labels(vec![
+                        Span::raw("0"),
+                        Span::raw(format_token_count((y_max / 2.0) as u64)),
+                        Span::raw(format_token_count(y_max as u64)),
+                    ]),
+            );
+
+        frame.render_widget(chart, area);
     }
 
     fn render_token_gauge(&self, frame: &mut Frame, area: Rect) {
         let usage = self.state.token_usage.lock().unwrap();
-        let percentage = usage.percentage;  // Already capped in database.rs
-        let color = self.get_usage_color(percentage);
-
         let data_source = self.state.get_active_data_source();
 
-        // Get compaction status indicator
-        let status_indicator = match usage.compaction_status {
-            crate::data::database::CompactionStatus::Safe => "🟢",
-            crate::data::database::CompactionStatus::Warning => "🟡",
-            crate::data::database::CompactionStatus::Critical => "🟠",
-            crate::data::database::CompactionStatus::Imminent => "🔴",
+        // Like ccusage, an idle Claude account has nothing counting against a block
+        let idle = matches!(data_source, crate::data::DataSourceType::ClaudeCode)
+            && matches!(self.state.claude_block_status(), BlockStatus::Idle { .. });
+        let (used, percentage) = if idle {
+            (0, 0.0)
+        } else {
+            (usage.used, usage.percentage)  // Already capped in database.rs
         };
+        let color = self.get_usage_color(percentage);
+
+        // Get compaction status indicator
+        let status_indicator = usage.compaction_status.indicator(self.color_enabled);
 
         // Adjust title based on data source
-        let title = if matches!(data_source, crate::data::DataSourceType::ClaudeCode) {
-            let limit = self.state.config.claude_token_limit;
-            format!("Token Usage - {} Limit {}",
-                if limit >= 1_000_000 {
-                    format!("{}M", limit / 1_000_000)
-                } else if limit >= 1_000 {
-                    format!("{}K", limit / 1_000)
-                } else {
-                    format!("{}", limit)
-                },
-                status_indicator
-            )
+        let (limit, kind) = if matches!(data_source, crate::data::DataSourceType::ClaudeCode) {
+            (self.state.claude_token_limit(), "")
+        } else if self.state.config.amazon_q_use_effective_limit {
+            (self.state.config.amazon_q_context_window(), " Effective")
         } else {
-            format!("Token Usage - 175K Effective Limit {}", status_indicator)
+            (self.state.config.amazon_q_context_window(), " Actual")
         };
+        let title = format!("Token Usage - {}{} Limit {}",
+            if limit >= 1_000_000 {
+                format!("{}M", limit / 1_000_000)
+            } else if limit >= 1_000 {
+                format!("{}K", limit / 1_000)
+            } else {
+                format!("{}", limit)
+            },
+            kind,
+            status_indicator
+        );
 
         // Add warning emoji if over threshold for Claude
         let mut label = format!(
             "{} / {} tokens ({:.1}%)",
-            usage.used, usage.context_window, percentage
+            used, usage.context_window, percentage
         );
 
         if matches!(data_source, crate::data::DataSourceType::ClaudeCode) {
             let warning_threshold = self.state.config.claude_warning_threshold * 100.0;
-            if percentage >= warning_threshold {
-                label = format!("⚠️  {} / {} tokens ({:.1}%)",
-                    usage.used, usage.context_window, percentage);
+            if idle {
+                label = format!("Idle - {}", label);
+            } else if percentage >= warning_threshold {
+                label = format!("{} {} / {} tokens ({:.1}%)",
+                    self.icon(Icon::Warning), used, usage.context_window, percentage);
             }
         }
 
@@ -236,21 +658,22 @@ impl Dashboard {
             let duration = (session.end_time - session.start_time).num_minutes();
 
             // Get context tokens (current memory) and total tokens (cumulative)
+            let exclude_cache_read = self.state.config.exclude_cache_read_from_total;
             let context_tokens = session.context_tokens.as_ref()
-                .map(|ct| ct.total())
-                .unwrap_or_else(|| session.total_tokens.total());
+                .map(|ct| ct.display_total(exclude_cache_read))
+                .unwrap_or_else(|| session.total_tokens.display_total(exclude_cache_read));
             let cumulative_tokens = session.total_tokens.total();
 
             // Show actual cost from cost_usd when available
             let cost_text = if session.cost_breakdown.percent_actual > 0.0 {
-                format!("${:.4} ({}% actual)", session.total_cost, session.cost_breakdown.percent_actual as u32)
+                format!("{} ({}% actual)", self.state.config.format_cost(session.total_cost), session.cost_breakdown.percent_actual as u32)
             } else {
-                format!("${:.4} (estimated)", session.total_cost)
+                format!("{} (estimated)", self.state.config.format_cost(session.total_cost))
             };
 
-            let text = vec![
+            let mut text = vec![
                 Line::from(vec![
-                    Span::styled("🔴 Active Session: ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                    Span::styled(format!("{} Active Session: ", self.icon(Icon::Live)), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
                     Span::raw(&session.id[..8.min(session.id.len())]),
                     Span::raw(" | "),
                     Span::styled("Context: ", Style::default().fg(Color::Cyan)),
@@ -265,6 +688,38 @@ impl Dashboard {
                 ]),
             ];
 
+            // When the current billing block hands over to a fresh one
+            let reset = self.state.get_active_claude_block()
+                .and_then(|block| block_reset_label(block.end_time, chrono::Utc::now()));
+            if let Some((label, soon)) = reset {
+                let style = if soon {
+                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                text[0].spans.push(Span::raw(" | "));
+                text[0].spans.push(Span::styled(label, style));
+            }
+
+            // Projected usage by the end of the current billing block
+            if let Some(projection) = self.state.get_active_claude_block()
+                .as_ref()
+                .and_then(crate::utils::session_blocks::project_block_usage)
+            {
+                let over_limit = projection.total_tokens > self.state.claude_token_limit();
+                let projected_style = if over_limit {
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Yellow)
+                };
+
+                text.push(Line::from(vec![
+                    Span::styled("Projected: ", Style::default().fg(Color::Cyan)),
+                    Span::styled(format!("{} tokens", format_token_count(projection.total_tokens)), projected_style),
+                    Span::raw(format!(" / {} by block end ({}m left)", self.state.config.format_cost(projection.total_cost), projection.remaining_minutes)),
+                ]));
+            }
+
             let active_panel = Paragraph::new(text)
                 .block(
                     Block::default()
@@ -278,13 +733,48 @@ impl Dashboard {
         }
     }
 
+    fn render_idle_session(&self, frame: &mut Frame, area: Rect, last_activity: chrono::DateTime<chrono::Utc>) {
+        let idle_for = chrono::Utc::now() - last_activity;
+        let notice = Paragraph::new(Line::from(Span::styled(
+            format!("No active session block (idle {})", format_duration(idle_for)),
+            Style::default().fg(Color::DarkGray),
+        )))
+        .block(
+            Block::default()
+                .title("Claude Code - Active Session (Last 5 Hours)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        )
+        .alignment(Alignment::Center);
+
+        frame.render_widget(notice, area);
+    }
+
     fn render_cost_panel(&self, frame: &mut Frame, area: Rect) {
         let cost = self.state.cost_analysis.lock().unwrap();
 
-        let cost_text = format!(
-            "Session: ${:.2} | Today: ${:.2} | Month: ${:.2}",
-            cost.session_cost, cost.daily_cost, cost.monthly_cost
-        );
+        let config = &self.state.config;
+        let mut cost_text = vec![Line::from(format!(
+            "Session: {} | Today: {} | Month: {}",
+            config.format_cost(cost.session_cost),
+            config.format_cost(cost.daily_cost),
+            config.format_cost(cost.monthly_cost)
+        ))];
+
+        // Claude only: what prompt caching saved, and how much input it served
+        if let (Some(savings), Some(session)) = (cost.cache_savings, self.state.get_active_claude_session()) {
+            let tokens = &session.total_tokens;
+            let cache_reads = tokens.cache_read_input_tokens.unwrap_or(0) as f64;
+            let all_input = tokens.input_tokens as f64
+                + tokens.cache_creation_input_tokens.unwrap_or(0) as f64
+                + cache_reads;
+            let share = share(cache_reads, all_input);
+
+            cost_text.push(Line::from(Span::styled(
+                format!("Cache savings: {} ({:.0}% of input from cache)", config.format_cost(savings), share),
+                Style::default().fg(Color::Green),
+            )));
+        }
 
         let cost_panel = Paragraph::new(cost_text)
             .block(
@@ -307,7 +797,11 @@ impl Dashboard {
 
         // Show conversation ID if present
         if let Some(ref id) = *conversation_id {
-            text.push(Line::from(format!("Session ID: {}", id)));
+            if self.state.get_pinned_conversation().as_deref() == Some(id.as_str()) {
+                text.push(Line::from(format!("Session ID: {} {} pinned", id, self.icon(Icon::Pin))));
+            } else {
+                text.push(Line::from(format!("Session ID: {}", id)));
+            }
         } else {
             text.push(Line::from("No active conversation in this directory"));
         }
@@ -354,24 +848,33 @@ impl Dashboard {
             Style::default().add_modifier(Modifier::BOLD),
         )));
         
-        let compaction_text = match usage.compaction_status {
-            crate::data::database::CompactionStatus::Safe => {
-                format!("Safe - {:.0} tokens until warning", 
-                    (usage.context_window as f64 * 0.7) - usage.used as f64)
-            },
-            crate::data::database::CompactionStatus::Warning => {
-                format!("Warning - {:.0} tokens until critical",
-                    (usage.context_window as f64 * 0.9) - usage.used as f64)
-            },
-            crate::data::database::CompactionStatus::Critical => {
-                format!("Critical - {:.0} tokens until compaction",
-                    (usage.context_window as f64 * 0.95) - usage.used as f64)
-            },
-            crate::data::database::CompactionStatus::Imminent => {
-                "Imminent - Compaction will trigger soon".to_string()
-            },
+        // Next threshold as a share of the context window, and what crossing it means
+        let thresholds = self.state.config.compaction_thresholds();
+        let next = match usage.compaction_status {
+            crate::data::database::CompactionStatus::Safe => Some(("Safe", "warning", "Warning", thresholds.warning)),
+            crate::data::database::CompactionStatus::Warning => Some(("Warning", "critical", "Critical", thresholds.critical)),
+            crate::data::database::CompactionStatus::Critical => Some(("Critical", "compaction", "compaction", thresholds.imminent)),
+            crate::data::database::CompactionStatus::Imminent => None,
         };
-        text.push(Line::from(format!("  {}", compaction_text)));
+        match next {
+            Some((status, until, target, threshold_pct)) => {
+                let threshold_tokens = usage.context_window as f64 * threshold_pct / 100.0;
+                text.push(Line::from(format!(
+                    "  {} - {:.0} tokens until {}",
+                    status,
+                    threshold_tokens - usage.used as f64,
+                    until
+                )));
+                let rate = self.state.burn_rate.lock().unwrap().tokens_per_minute;
+                if let Some(eta) = time_to_threshold(usage.used, threshold_tokens, rate) {
+                    text.push(Line::from(Span::styled(
+                        format!("  ~{} to {} at current rate", format_duration(eta), target),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                }
+            }
+            None => text.push(Line::from("  Imminent - Compaction will trigger soon")),
+        }
         
         if usage.has_summary {
             text.push(Line::from("  ℹ️  Previous compaction detected"));
@@ -386,7 +889,7 @@ impl Dashboard {
         if usage.message_count > 0 {
             text.push(Line::from(format!(
                 "Avg per message: {} tokens",
-                usage.used / usage.message_count as u64
+                safe_div(usage.used as f64, usage.message_count as f64) as u64
             )));
         }
 
@@ -400,10 +903,16 @@ impl Dashboard {
     }
 
     fn render_global_overview(&self, frame: &mut Frame, area: Rect) {
+        // Grow the stats panel to fit the per-model breakdown (header + one line per model)
+        let model_lines = self.state.global_stats.lock().unwrap()
+            .as_ref()
+            .map(|stats| if stats.cost_by_model.is_empty() { 0 } else { stats.cost_by_model.len() as u16 + 1 })
+            .unwrap_or(0);
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(8),  // Global stats
+                Constraint::Length(9 + model_lines),  // Global stats
                 Constraint::Min(10),    // Top conversations
             ])
             .split(area);
@@ -420,13 +929,17 @@ impl Dashboard {
         let mut text = vec![];
         
         if let Some(ref stats) = *global_stats {
-            text.push(Line::from(vec![
+            let mut total_line = vec![
                 Span::raw("Total Conversations: "),
                 Span::styled(
                     format!("{}", stats.total_conversations),
                     Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
                 ),
-            ]));
+            ];
+            if let Some(label) = unreadable_label(stats.parse_failures) {
+                total_line.push(Span::styled(format!(" {}", label), Style::default().fg(Color::Red)));
+            }
+            text.push(Line::from(total_line));
             
             text.push(Line::from(vec![
                 Span::raw("Total Tokens: "),
@@ -457,10 +970,36 @@ impl Dashboard {
             text.push(Line::from(vec![
                 Span::raw("Total Estimated Cost: "),
                 Span::styled(
-                    format!("${:.2}", stats.total_cost_estimate),
+                    self.state.config.format_cost(stats.total_cost_estimate),
                     Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
                 ),
             ]));
+            text.extend(period_disclaimer(self.state.get_active_data_source()));
+
+            let io = crate::data::database::IoSplit::total(self.state.all_sessions.lock().unwrap().iter());
+            if let Some(line) = io_ratio_line(&io) {
+                text.push(line);
+            }
+
+            // Per-model breakdown, most expensive first (omitted when the source has no model data)
+            if !stats.cost_by_model.is_empty() {
+                let mut models: Vec<(&String, &f64)> = stats.cost_by_model.iter().collect();
+                models.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+                text.push(Line::from(Span::styled(
+                    "By Model:",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                for (model, cost) in models {
+                    let tokens = stats.tokens_by_model.get(model).copied().unwrap_or(0);
+                    text.push(Line::from(vec![
+                        Span::raw(format!("  {}: ", model)),
+                        Span::styled(self.state.config.format_cost(*cost), Style::default().fg(Color::Green)),
+                        Span::raw(" | "),
+                        Span::styled(format!("{} tokens", format_token_count(tokens)), Style::default().fg(Color::Yellow)),
+                    ]));
+                }
+            }
         } else {
             text.push(Line::from("Loading global statistics..."));
         }
@@ -494,7 +1033,12 @@ impl Dashboard {
                 "Tokens",
                 Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
             ),
-            Span::raw("     "),
+            Span::raw("            "),
+            Span::styled(
+                "Cost",
+                Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            ),
+            Span::raw("    "),
             Span::styled(
                 "Status",
                 Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
@@ -505,19 +1049,15 @@ impl Dashboard {
         
         // Show top 10 conversations
         for (idx, conv) in conversations.iter().take(10).enumerate() {
-            let path_display = if conv.path.len() > 40 {
-                format!("...{}", &conv.path[conv.path.len()-37..])
+            let tag = provider_tag(conv.provider);
+            let path_display = if tag.len() + conv.path.len() > 40 {
+                format!("{}...{}", tag, &conv.path[conv.path.len() + tag.len() - 37..])
             } else {
-                conv.path.clone()
+                format!("{}{}", tag, conv.path)
             };
             
             let is_current = conv.path == current_dir_str;
-            let status_emoji = match conv.token_usage.compaction_status {
-                crate::data::database::CompactionStatus::Safe => "🟢",
-                crate::data::database::CompactionStatus::Warning => "🟡",
-                crate::data::database::CompactionStatus::Critical => "🟠",
-                crate::data::database::CompactionStatus::Imminent => "🔴",
-            };
+            let status_emoji = conv.token_usage.compaction_status.indicator(self.color_enabled);
             
             let style = if is_current {
                 Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
@@ -531,9 +1071,10 @@ impl Dashboard {
                     style,
                 ),
                 Span::styled(
-                    format!("{:>8} ({:>5.1}%)",
+                    format!("{:>8} ({:>5.1}%) {:>8}",
                         conv.token_usage.total_tokens,
-                        conv.token_usage.percentage
+                        conv.token_usage.percentage,
+                        self.state.config.format_cost(conv.cost_estimate)
                     ),
                     style,
                 ),
@@ -554,14 +1095,36 @@ impl Dashboard {
     }
     
     fn render_conversation_list(&self, frame: &mut Frame, area: Rect) {
-        let conversations = self.state.all_conversations.lock().unwrap();
+        let conversations = self.state.visible_conversations();
+        let search_query = self.state.search_query.lock().unwrap().clone();
         let selected_idx = *self.state.selected_conversation_index.lock().unwrap();
         
+        // Two header lines plus borders
+        let page_size = (area.height as usize).saturating_sub(4).max(1);
+        self.page_size.set(page_size);
+        // Scroll so the selected row stays on screen
+        let first_visible = selected_idx.saturating_sub(page_size - 1);
+
         let mut text = vec![];
-        text.push(Line::from("All Conversations (↑↓ to navigate, Enter to view):"));
+        match search_query {
+            Some(query) => {
+                let cursor = if self.search_editing { "_" } else { "" };
+                text.push(Line::from(vec![
+                    Span::styled("Search: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{}{}", query, cursor)),
+                    Span::styled(
+                        format!("  ({} matches, Esc to clear)", conversations.len()),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]));
+            }
+            None => {
+                text.push(Line::from("All Conversations (↑↓ PgUp/PgDn Home/End to navigate, / to search):"));
+            }
+        }
         text.push(Line::from(""));
         
-        for (idx, conv) in conversations.iter().enumerate() {
+        for (idx, conv) in conversations.iter().enumerate().skip(first_visible) {
             let is_selected = idx == selected_idx;
             let style = if is_selected {
                 Style::default().bg(Color::Gray).fg(Color::Black)
@@ -570,7 +1133,7 @@ impl Dashboard {
             };
             
             text.push(Line::from(Span::styled(
-                format!("{} - {} tokens", conv.path, conv.token_usage.total_tokens),
+                format!("{}{} - {} tokens ({})", provider_tag(conv.provider), conv.path, conv.token_usage.total_tokens, self.state.config.format_cost(conv.cost_estimate)),
                 style,
             )));
         }
@@ -586,12 +1149,22 @@ impl Dashboard {
     }
     
     fn render_session_list(&self, frame: &mut Frame, area: Rect) {
-        // Split layout: Session list on top, metrics widget at bottom
+        // Split layout: Session list on top, metrics widget at bottom.
+        // Small terminals get the compact widget without the period table,
+        // and the list alone when even that would squeeze it out.
+        let show_metrics = area.height >= SESSION_LIST_MIN_HEIGHT + METRICS_HEIGHT_COMPACT;
+        let metrics_height = if !show_metrics {
+            0
+        } else if area.height >= METRICS_TABLE_MIN_AREA {
+            METRICS_HEIGHT_WITH_TABLE
+        } else {
+            METRICS_HEIGHT_COMPACT
+        } + u16::from(show_metrics && self.state.config.monthly_budget_usd.is_some());
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Min(10),    // Session list
-                Constraint::Length(8),  // Metrics widget
+                Constraint::Min(SESSION_LIST_MIN_HEIGHT),    // Session list
+                Constraint::Length(metrics_height),  // Metrics widget
             ])
             .split(area);
         
@@ -605,28 +1178,38 @@ impl Dashboard {
             .split(chunks[0]);
         
         // Render header
-        let directory_groups = self.state.directory_groups.lock().unwrap();
+        let visible_groups = self.state.visible_session_groups();
+        let total_sessions: usize = self.state.directory_groups.lock().unwrap()
+            .iter()
+            .map(|g| g.sessions.len())
+            .sum();
         let show_active_only = *self.state.show_active_only.lock().unwrap();
+        let sort_key = *self.state.sort_key.lock().unwrap();
         let selected_idx = *self.state.selected_conversation_index.lock().unwrap();
         let last_refresh = *self.state.last_refresh.lock().unwrap();
         
         let header_text = vec![
             Line::from(Span::styled(
                 format!(
-                    "Sessions (Active: {} | Showing: {}/{}) - Last refresh: {}",
+                    "Sessions (Active: {} | Showing: {}/{} | Sort: {} ↓) - Last refresh: {}",
                     if show_active_only { "ON" } else { "OFF" },
-                    if show_active_only {
-                        directory_groups.iter().map(|g| g.active_session_count).sum::<usize>()
-                    } else {
-                        directory_groups.iter().map(|g| g.sessions.len()).sum::<usize>()
-                    },
-                    directory_groups.iter().map(|g| g.sessions.len()).sum::<usize>(),
-                    last_refresh.format("%H:%M:%S")
+                    visible_groups.iter().map(|(_, sessions)| sessions.len()).sum::<usize>(),
+                    total_sessions,
+                    sort_key.label(),
+                    self.state.config.timezone().format(&last_refresh, "%H:%M:%S")
                 ),
                 Style::default().add_modifier(Modifier::BOLD),
             )),
-            Line::from("[A] Toggle Active | [↑↓] Navigate | [Enter] View Details"),
-            Line::from("Icons: 🟢 Active (dir modified <7 days) | ⚫ Inactive | 📎 Has Context Files"),
+            Line::from("[A] Toggle Active | [O] Sort | [↑↓ PgUp/PgDn Home/End] Navigate | [Enter] View Details"),
+            Line::from(format!(
+                "Icons: {} Active (dir modified <{} days) | {} Inactive | {} Has Context Files | {} Cost Spike | {} Over Cap",
+                self.icon(Icon::Active),
+                self.state.config.active_session_days,
+                self.icon(Icon::Inactive),
+                self.icon(Icon::ContextFiles),
+                self.icon(Icon::CostSpike),
+                self.icon(Icon::OverCap),
+            )),
         ];
         
         let header = Paragraph::new(header_text)
@@ -634,106 +1217,131 @@ impl Dashboard {
         frame.render_widget(header, list_chunks[0]);
         
         // Build visible sessions list
+        let rows = session_rows(visible_groups.iter().map(|(_, sessions)| sessions.len()));
         let mut items = Vec::new();
-        let mut visible_sessions = Vec::new();
+        let mut session_idx = 0;
         
-        for group in directory_groups.iter() {
-            if show_active_only && group.active_session_count == 0 {
-                continue;
+        // Merged sources can each have a group for the same directory; its cap covers both
+        let mut group_costs: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+        for group in self.state.directory_groups.lock().unwrap().iter() {
+            *group_costs.entry(group.directory.clone()).or_default() += group.total_cost;
+        }
+
+        for (directory, sessions) in &visible_groups {
+            // Add directory header, with spend against its cap when one is set
+            let mut header = vec![Span::styled(
+                format!("{} {}{} ({} sessions)", self.icon(Icon::Folder), provider_tag(sessions[0].provider), directory, sessions.len()),
+                Style::default().fg(Color::DarkGray),
+            )];
+            if let Some(cap) = self.state.config.directory_cap(directory) {
+                let spent = group_costs.get(directory).copied().unwrap_or(0.0);
+                let config = &self.state.config;
+                let (text, style) = if spent > cap {
+                    (
+                        format!(" {} {}/{}", self.icon(Icon::OverCap), config.format_cost(spent), config.format_cost(cap)),
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    (format!(" {}/{}", config.format_cost(spent), config.format_cost(cap)), Style::default().fg(Color::DarkGray))
+                };
+                header.push(Span::styled(text, style));
             }
+            items.push(ListItem::new(Line::from(header)));
             
-            // Add directory header
-            let has_visible_sessions = group.sessions.iter()
-                .any(|s| !show_active_only || s.is_active);
+            // Add sessions
+            for session in sessions {
+                let status_icon = self.icon(if session.is_active { Icon::Active } else { Icon::Inactive });
+                let context_icon = if session.has_active_context {
+                    self.icon(Icon::ContextFiles)
+                } else if self.color_enabled {
+                    "  "
+                } else {
+                    " "
+                };
+                let spike_icon = if self.state.has_cost_spike(&session.conversation_id) {
+                    format!(" {}", self.icon(Icon::CostSpike))
+                } else {
+                    String::new()
+                };
+                // Show session cost (current conversation cost)
+                // Note: Amazon Q stores only one conversation per folder, so cumulative = current
+                let cost_text = self.state.config.format_cost(session.session_cost);
                 
-            if has_visible_sessions {
-                items.push(ListItem::new(Line::from(Span::styled(
-                    format!("📁 {} ({} sessions)", group.directory, 
-                        if show_active_only { group.active_session_count } else { group.sessions.len() }),
-                    Style::default().fg(Color::DarkGray),
-                ))));
+                // Show percentage of context window used (how much room left)
+                let window_pct = session.token_usage.percentage;
                 
-                // Add sessions
-                for session in &group.sessions {
-                    if show_active_only && !session.is_active {
-                        continue;
-                    }
-                    
-                    visible_sessions.push(session.clone());
-                    let session_idx = visible_sessions.len() - 1;
-                    
-                    let status_icon = if session.is_active { "🟢" } else { "⚫" };
-                    let context_icon = if session.has_active_context { "📎" } else { "  " };
-                    // Show session cost (current conversation cost)
-                    // Note: Amazon Q stores only one conversation per folder, so cumulative = current
-                    let cost_text = format!("${:.4}", session.session_cost);
-                    
-                    // Show percentage of context window used (how much room left)
-                    let window_pct = session.token_usage.percentage;
-                    
-                    // Add visual indicator for context window usage
-                    let usage_indicator = if window_pct > 90.0 {
-                        "🔴"  // Critical - almost full
-                    } else if window_pct > 70.0 {
-                        "🟡"  // Warning - getting full
-                    } else {
-                        ""    // Plenty of room
-                    };
-                    
-                    // Safely get conversation ID substring
-                    let conv_id = if session.conversation_id.len() >= 8 {
-                        &session.conversation_id[..8]
-                    } else {
-                        &session.conversation_id
-                    };
-                    
-                    let session_text = format!(
-                        "  {} {} {} | {}/{} ({:.1}% used) {} | {} msgs | {}",
-                        status_icon,
-                        context_icon,
-                        conv_id,
-                        session.token_usage.total_tokens,
-                        session.token_usage.context_window,
-                        if window_pct >= 100.0 { 100.0 } else if window_pct > 99.9 { 99.9 } else { window_pct },
-                        usage_indicator,
-                        session.message_count,
-                        cost_text
-                    );
-                    
-                    // Highlight selected item
-                    let style = if session_idx == selected_idx {
-                        Style::default()
-                            .bg(Color::Rgb(70, 70, 70))
-                            .fg(Color::Rgb(255, 255, 255))
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::Rgb(200, 200, 200))
-                    };
-                    
-                    items.push(ListItem::new(Line::from(Span::styled(session_text, style))));
+                // Add visual indicator for context window usage
+                let usage_indicator = if window_pct > 90.0 {
+                    self.icon(Icon::NearlyFull)  // Critical - almost full
+                } else if window_pct > 70.0 {
+                    self.icon(Icon::Filling)  // Warning - getting full
+                } else {
+                    ""    // Plenty of room
+                };
+                
+                // Safely get conversation ID substring
+                let conv_id = if session.conversation_id.len() >= 8 {
+                    &session.conversation_id[..8]
+                } else {
+                    &session.conversation_id
+                };
+                
+                let mut session_text = format!(
+                    "  {} {} {}{} | {}/{} ({:.1}% used) {} | {} msgs | {}{}",
+                    status_icon,
+                    context_icon,
+                    provider_tag(session.provider),
+                    conv_id,
+                    session.token_usage.total_tokens,
+                    session.token_usage.context_window,
+                    if window_pct >= 100.0 { 100.0 } else if window_pct > 99.9 { 99.9 } else { window_pct },
+                    usage_indicator,
+                    session.message_count,
+                    cost_text,
+                    spike_icon
+                );
+                // Amazon Q records how often `q` ran here, a usage signal independent of size
+                if let Some(invocations) = session.invocations {
+                    session_text.push_str(&format!(" | {} runs", invocations));
                 }
-            }
-        }
-        
-        // Create list widget with proper state for scrolling
-        let mut list_state = ListState::default();
-        
-        // Calculate the actual index in the items list (accounting for headers)
-        let mut actual_idx = 0;
-        let mut session_count = 0;
-        for (i, item) in items.iter().enumerate() {
-            // Check if this is a session item (starts with spaces)
-            let item_text = format!("{:?}", item);
-            if item_text.contains("  🟢") || item_text.contains("  ⚫") {
-                if session_count == selected_idx {
-                    actual_idx = i;
-                    break;
+                // Flag sessions that switched models, a common cause of cost spikes
+                if session.model_usage.len() > 1 {
+                    session_text.push_str(&format!(" ({} models)", session.model_usage.len()));
                 }
-                session_count += 1;
+                
+                // Highlight selected item
+                let style = if session_idx == selected_idx {
+                    Style::default()
+                        .bg(Color::Rgb(70, 70, 70))
+                        .fg(Color::Rgb(255, 255, 255))
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Rgb(200, 200, 200))
+                };
+                
+                items.push(ListItem::new(Line::from(Span::styled(session_text, style))));
+                session_idx += 1;
             }
         }
         
-        list_state.select(Some(actual_idx));
+        // Rows per page: the configured size when it fits, else whatever the area holds
+        let mut list_area = list_chunks[1];
+        let available = (list_area.height as usize).saturating_sub(2).max(1);
+        let page_size = match self.state.config.session_page_size {
+            Some(size) if size > 0 && size < available => {
+                list_area.height = size as u16 + 2;
+                size
+            }
+            _ => available,
+        };
+        self.page_size.set(page_size);
+
+        let selected_row = row_of_session(&rows, selected_idx).unwrap_or(0);
+        let offset = scroll_offset(&rows, self.session_list_offset.get(), selected_row, page_size);
+        self.session_list_offset.set(offset);
+        let mut list_state = ListState::default()
+            .with_offset(offset)
+            .with_selected(Some(selected_row));
         
         let list = List::new(items)
             .block(
@@ -744,10 +1352,12 @@ impl Dashboard {
             .highlight_style(Style::default())  // No additional highlight since we style items
             .highlight_symbol("▶ ");
             
-        frame.render_stateful_widget(list, list_chunks[1], &mut list_state);
+        frame.render_stateful_widget(list, list_area, &mut list_state);
         
         // Render metrics widget at bottom
-        self.render_metrics_widget(frame, chunks[1]);
+        if show_metrics {
+            self.render_metrics_widget(frame, chunks[1]);
+        }
     }
     
     fn render_session_detail(&self, frame: &mut Frame, area: Rect) {
@@ -758,13 +1368,19 @@ impl Dashboard {
             
             // Session header
             text.push(Line::from(Span::styled(
-                format!("Session: {}", session.conversation_id),
+                format!("Session: {}{}", provider_tag(session.provider), session.conversation_id),
                 Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
             )));
             text.push(Line::from(format!("Directory: {}", session.directory)));
+            if self.state.get_pinned_conversation().as_deref() == Some(session.conversation_id.as_str()) {
+                text.push(Line::from(Span::styled(
+                    format!("{} Pinned to current view (* to unpin)", self.icon(Icon::Pin)),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
             text.push(Line::from(format!(
                 "Last Activity: {}",
-                session.last_activity.format("%Y-%m-%d %H:%M:%S")
+                self.state.config.timezone().format(&session.last_activity, "%Y-%m-%d %H:%M:%S")
             )));
             text.push(Line::from(""));
             
@@ -781,6 +1397,20 @@ impl Dashboard {
                 "  Context: {} tokens",
                 session.token_usage.context_tokens
             )));
+            let (input, output) = (session.token_usage.input_tokens, session.token_usage.output_tokens);
+            if input + output > 0 {
+                text.push(Line::from(format!(
+                    "  Input: {} tokens ({:.0}%) | Output: {} tokens ({:.0}%)",
+                    input,
+                    share(input as f64, (input + output) as f64),
+                    output,
+                    share(output as f64, (input + output) as f64)
+                )));
+            }
+            if let Some(mut line) = io_ratio_line(&crate::data::database::IoSplit::for_session(session)) {
+                line.spans.insert(0, Span::raw("  "));
+                text.push(line);
+            }
             text.push(Line::from(format!(
                 "  Total: {} / {} ({:.1}% used)",
                 session.token_usage.total_tokens,
@@ -800,19 +1430,18 @@ impl Dashboard {
                 remaining_pct
             )));
             
+            if !session.context_files.is_empty() {
+                text.push(Line::from(context_files_summary(&session.context_files)));
+            }
+            
             // Compaction status
             text.push(Line::from(""));
             text.push(Line::from(Span::styled(
                 "Compaction Status:",
                 Style::default().add_modifier(Modifier::BOLD),
             )));
-            let status_text = match session.token_usage.compaction_status {
-                crate::data::database::CompactionStatus::Safe => "🟢 Safe",
-                crate::data::database::CompactionStatus::Warning => "🟡 Warning",
-                crate::data::database::CompactionStatus::Critical => "🟠 Critical",
-                crate::data::database::CompactionStatus::Imminent => "🔴 Imminent",
-            };
-            text.push(Line::from(format!("  {}", status_text)));
+            let status = &session.token_usage.compaction_status;
+            text.push(Line::from(format!("  {} {:?}", status.indicator(self.color_enabled), status)));
             
             // Cost information
             text.push(Line::from(""));
@@ -820,7 +1449,28 @@ impl Dashboard {
                 "Cost Analysis:",
                 Style::default().add_modifier(Modifier::BOLD),
             )));
-            text.push(Line::from(format!("  Session Cost: ${:.4}", session.session_cost)));
+            text.push(Line::from(format!("  Session Cost: {}", self.state.config.format_cost(session.session_cost))));
+
+            // Models used, with each one's share of tokens and cost
+            if !session.model_usage.is_empty() {
+                let total_tokens: u64 = session.model_usage.iter().map(|m| m.tokens).sum();
+                let total_cost: f64 = session.model_usage.iter().map(|m| m.cost).sum();
+                text.push(Line::from(""));
+                text.push(Line::from(Span::styled(
+                    "Models:",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                for usage in &session.model_usage {
+                    text.push(Line::from(format!(
+                        "  {}: {} tokens ({:.0}%) | {} ({:.0}%)",
+                        usage.model,
+                        format_token_count(usage.tokens),
+                        share(usage.tokens as f64, total_tokens as f64),
+                        self.state.config.format_cost(usage.cost),
+                        share(usage.cost, total_cost),
+                    )));
+                }
+            }
             
             // Message information
             text.push(Line::from(""));
@@ -832,7 +1482,7 @@ impl Dashboard {
             if session.message_count > 0 {
                 text.push(Line::from(format!(
                     "  Avg tokens/msg: {}",
-                    session.token_usage.total_tokens / session.message_count as u64
+                    safe_div(session.token_usage.total_tokens as f64, session.message_count as f64) as u64
                 )));
             }
             
@@ -842,8 +1492,27 @@ impl Dashboard {
                         .title("Session Details")
                         .borders(Borders::ALL),
                 );
-                
-            frame.render_widget(detail, area);
+
+            // Amazon Q doesn't track tokens per message, so only Claude sessions get a chart
+            if session.token_timeline.len() < 2 {
+                frame.render_widget(detail, area);
+                return;
+            }
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(10), Constraint::Length(SESSION_CHART_HEIGHT)])
+                .split(area);
+            frame.render_widget(detail, chunks[0]);
+
+            let block = Block::default()
+                .title(format!("Tokens over {} messages", session.token_timeline.len()))
+                .borders(Borders::ALL);
+            let points = resample(&session.token_timeline, block.inner(chunks[1]).width as usize);
+            let sparkline = Sparkline::default()
+                .block(block)
+                .data(&points)
+                .style(Style::default().fg(Color::Cyan));
+            frame.render_widget(sparkline, chunks[1]);
         } else {
             let msg = Paragraph::new("No session selected")
                 .block(
@@ -865,31 +1534,57 @@ impl Dashboard {
         if let Some(ref stats) = *global_stats {
             let mut text = vec![];
             
-            // Show cumulative totals (we can't determine time-based metrics without timestamps)
+            // Period rollups: a table when there's room, else just the 30-day total
             if let Some(ref periods) = *period_metrics {
-                text.push(Line::from(vec![
-                    Span::styled("📊 Cumulative Total: ", Style::default().fg(Color::Cyan)),
-                    Span::raw(format!("{} tokens (${:.2})", periods.month_tokens, periods.month_cost)),
-                ]));
+                if area.height >= METRICS_HEIGHT_WITH_TABLE {
+                    text.extend(period_table(periods, &self.state.config));
+                } else {
+                    text.push(Line::from(vec![
+                        Span::styled(format!("{} Last 30 Days: ", self.icon(Icon::Chart)), Style::default().fg(Color::Cyan)),
+                        Span::raw(format!("{} tokens ({})", periods.month_tokens, self.state.config.format_cost(periods.month_cost))),
+                    ]));
+                }
+                text.extend(period_disclaimer(self.state.get_active_data_source()));
             }
             
-            // Burn rate and cost rate
-            text.push(Line::from(vec![
-                Span::raw("🔥 Burn Rate: "),
-                Span::styled(
-                    format!("{:.1} tokens/min", burn_rate.tokens_per_minute),
-                    Style::default().fg(Color::Red),
-                ),
-                Span::raw("  💲 Cost Rate: "),
-                Span::styled(
-                    format!("${:.4}/min", burn_rate.cost_per_minute),
+            // Monthly budget burn-down
+            if let (Some(budget), Some(periods)) = (self.state.config.monthly_budget_usd, period_metrics.as_ref()) {
+                let status = BudgetStatus::new(periods.month_cost, budget, self.state.config.timezone().today());
+                text.push(self.budget_line(&status));
+            }
+            
+            // Burn rate and cost rate, with the sparkline drawn to its right
+            let burn_rate_row = text.len() as u16;
+            let unit = *self.state.burn_rate_unit.lock().unwrap();
+            let rate = unit.convert(burn_rate.ema_tokens_per_minute, self.state.config.cost_per_1k_tokens);
+            let mut burn_rate_spans = vec![Span::raw(format!("{} Burn Rate: ", self.icon(Icon::Burn)))];
+            match unit {
+                BurnRateUnit::TokensPerMinute => burn_rate_spans.extend([
+                    Span::styled(format!("{:.1} tokens/min", rate), Style::default().fg(Color::Red)),
+                    Span::raw(format!("  {} Cost Rate: ", self.icon(Icon::CostRate))),
+                    Span::styled(
+                        format!("{}/min", self.state.config.format_cost(burn_rate.cost_per_minute)),
+                        Style::default().fg(Color::Green),
+                    ),
+                ]),
+                BurnRateUnit::TokensPerHour => burn_rate_spans.extend([
+                    Span::styled(format!("{} tokens/hour", format_token_count(rate as u64)), Style::default().fg(Color::Red)),
+                    Span::raw(format!("  {} Cost Rate: ", self.icon(Icon::CostRate))),
+                    Span::styled(
+                        format!("{}/hour", self.state.config.format_cost(burn_rate.cost_per_minute * 60.0)),
+                        Style::default().fg(Color::Green),
+                    ),
+                ]),
+                BurnRateUnit::CostPerHour => burn_rate_spans.push(Span::styled(
+                    format!("{}/hour", self.state.config.format_cost(rate)),
                     Style::default().fg(Color::Green),
-                ),
-            ]));
+                )),
+            }
+            text.push(Line::from(burn_rate_spans));
+            let burn_rate_width = text[burn_rate_row as usize].width() as u16;
             
             // Message quota
-            let msg_pct = (stats.message_quota_used as f64 / stats.message_quota_limit as f64) * 100.0;
-            let msg_pct = if msg_pct >= 100.0 { 100.0 } else if msg_pct > 99.9 { 99.9 } else { msg_pct };
+            let msg_pct = stats.message_quota_percentage();
             text.push(Line::from(Span::styled(
                 format!(
                     "Message Quota (Month): {} / {} ({:.1}%)",
@@ -904,27 +1599,27 @@ impl Dashboard {
             let active_sessions = self.state.all_sessions.lock().unwrap();
             let total_context: u64 = active_sessions.iter().map(|s| s.token_usage.context_tokens).sum();
             let _total_history: u64 = active_sessions.iter().map(|s| s.token_usage.history_tokens).sum();
-            let context_percentage = if stats.total_tokens > 0 {
-                (total_context as f64 / stats.total_tokens as f64) * 100.0
-            } else {
-                0.0
-            };
+            let context_percentage = share(total_context as f64, stats.total_tokens as f64);
             
             // System-wide metrics with context breakdown
             text.push(Line::from(""));
             text.push(Line::from(format!(
-                "Total: {} sessions | {} tokens ({}% context, {}% conversation) | ${:.2}",
+                "Total: {} sessions | {} tokens ({}% context, {}% conversation) | {}",
                 stats.total_conversations,
                 stats.total_tokens,
                 context_percentage as i32,
                 (100.0 - context_percentage) as i32,
-                stats.total_cost_estimate
+                self.state.config.format_cost(stats.total_cost_estimate)
             )));
+            if let Some(label) = unreadable_label(stats.parse_failures) {
+                text.push(Line::from(Span::styled(label, Style::default().fg(Color::Red))));
+            }
             
             // Warning/critical counts
             if stats.conversations_warning > 0 || stats.conversations_critical > 0 {
                 text.push(Line::from(format!(
-                    "⚠️  {} warning | {} critical",
+                    "{} {} warning | {} critical",
+                    self.icon(Icon::Warning),
                     stats.conversations_warning,
                     stats.conversations_critical
                 )));
@@ -939,9 +1634,58 @@ impl Dashboard {
                 );
                 
             frame.render_widget(metrics, area);
+
+            // Token deltas between snapshots show whether the burn rate is rising or falling
+            let deltas = burn_rate.snapshot_deltas();
+            let inner = Block::default().borders(Borders::ALL).inner(area);
+            let sparkline_x = inner.x + burn_rate_width + 2;
+            if !deltas.is_empty() && burn_rate_row < inner.height && sparkline_x < inner.right() {
+                let sparkline_area = Rect {
+                    x: sparkline_x,
+                    y: inner.y + burn_rate_row,
+                    width: inner.right() - sparkline_x,
+                    height: 1,
+                };
+                // Show the most recent deltas that fit
+                let visible = &deltas[deltas.len().saturating_sub(sparkline_area.width as usize)..];
+                let sparkline = Sparkline::default()
+                    .data(visible)
+                    .style(Style::default().fg(Color::Red));
+                frame.render_widget(sparkline, sparkline_area);
+            }
         }
     }
     
+    /// "💰 Budget [██████░░░░] $60.00 / $100.00 (60%) → $95.00 projected"
+    fn budget_line(&self, status: &BudgetStatus) -> Line<'static> {
+        let fraction = status.fraction();
+        let color = if fraction > 1.0 {
+            Color::Red
+        } else if fraction > BUDGET_WARNING_FRACTION {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        let filled = ((fraction.min(1.0) * BUDGET_BAR_WIDTH as f64).round() as usize).min(BUDGET_BAR_WIDTH);
+        let bar = format!("{}{}", "█".repeat(filled), "░".repeat(BUDGET_BAR_WIDTH - filled));
+        let config = &self.state.config;
+
+        Line::from(vec![
+            Span::styled(format!("{} Budget ", self.icon(Icon::Budget)), Style::default().fg(Color::Cyan)),
+            Span::styled(bar, Style::default().fg(color)),
+            Span::styled(
+                format!(
+                    " {} / {} ({:.0}%)",
+                    config.format_cost(status.spent),
+                    config.format_cost(status.budget),
+                    fraction * 100.0
+                ),
+                Style::default().fg(color),
+            ),
+            Span::raw(format!(" → {} projected", config.format_cost(status.projected))),
+        ])
+    }
+
     fn render_footer(&self, frame: &mut Frame, area: Rect) {
         // Split footer into two rows: stats and keybinds
         let chunks = Layout::default()
@@ -958,48 +1702,7 @@ impl Dashboard {
         // Render keybinds at bottom
         let view_mode = self.state.view_mode.lock().unwrap().clone();
         
-        let keybinds = match view_mode {
-            crate::app::state::ViewMode::GlobalOverview => vec![
-                ("G", "Current Dir"),
-                ("L", "List All"),
-                ("S", "Sessions"),
-                ("P", "Provider"),
-                ("R", "Refresh"),
-                ("Q", "Quit"),
-            ],
-            crate::app::state::ViewMode::CurrentDirectory => vec![
-                ("G", "Global View"),
-                ("L", "List All"),
-                ("S", "Sessions"),
-                ("P", "Provider"),
-                ("R", "Refresh"),
-                ("Q", "Quit"),
-            ],
-            crate::app::state::ViewMode::ConversationList => vec![
-                ("G", "Global View"),
-                ("C", "Current Dir"),
-                ("S", "Sessions"),
-                ("P", "Provider"),
-                ("↑↓", "Navigate"),
-                ("Q", "Quit"),
-            ],
-            crate::app::state::ViewMode::SessionList => vec![
-                ("G", "Global"),
-                ("C", "Current"),
-                ("A", "Toggle Active"),
-                ("P", "Provider"),
-                ("↑↓", "Navigate"),
-                ("Enter", "Details"),
-                ("Q", "Quit"),
-            ],
-            crate::app::state::ViewMode::SessionDetail => vec![
-                ("Esc", "Back"),
-                ("G", "Global"),
-                ("S", "Sessions"),
-                ("P", "Provider"),
-                ("Q", "Quit"),
-            ],
-        };
+        let keybinds = Self::keybinds_for(&view_mode);
 
         let spans: Vec<Span> = keybinds
             .iter()
@@ -1028,6 +1731,28 @@ impl Dashboard {
     }
     
     fn render_stats_line(&self, frame: &mut Frame, area: Rect) {
+        // A fresh confirmation replaces the stats until it expires
+        if let Some(message) = self.active_flash() {
+            let flash = Paragraph::new(Span::styled(
+                message.to_string(),
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Center);
+            frame.render_widget(flash, area);
+            return;
+        }
+
+        // So do collector errors, which would otherwise go unseen
+        if let Some(message) = self.error_toast() {
+            let toast = Paragraph::new(Span::styled(
+                format!("✗ {} (! for log)", message),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Center);
+            frame.render_widget(toast, area);
+            return;
+        }
+
         let global_stats = self.state.global_stats.lock().unwrap();
         
         let mut spans = vec![];
@@ -1036,20 +1761,11 @@ impl Dashboard {
             let avg_window = 175_000u64; // Average context window
             
             // Calculate percentages with cap at 99.9%
-            let token_percentage = if stats.total_tokens > 0 && stats.total_conversations > 0 {
-                let total_capacity = avg_window * stats.total_conversations as u64;
-                let pct = (stats.total_tokens as f64 / total_capacity as f64) * 100.0;
-                if pct >= 100.0 { 100.0 } else if pct > 99.9 { 99.9 } else { pct }
-            } else {
-                0.0
-            };
+            let total_capacity = avg_window * stats.total_conversations as u64;
+            let pct = share(stats.total_tokens as f64, total_capacity as f64);
+            let token_percentage = if pct >= 100.0 { 100.0 } else if pct > 99.9 { 99.9 } else { pct };
             
-            let message_percentage = if stats.message_quota_limit > 0 {
-                let pct = (stats.message_quota_used as f64 / stats.message_quota_limit as f64) * 100.0;
-                if pct >= 100.0 { 100.0 } else if pct > 99.9 { 99.9 } else { pct }
-            } else {
-                0.0
-            };
+            let message_percentage = stats.message_quota_percentage();
             
             // Token usage
             spans.push(Span::raw("Tokens: "));
@@ -1069,7 +1785,7 @@ impl Dashboard {
             // Cost
             spans.push(Span::raw("Cost: "));
             spans.push(Span::styled(
-                format!("${:.2}", stats.total_cost_estimate),
+                self.state.config.format_cost(stats.total_cost_estimate),
                 Style::default().fg(Color::Green),
             ));
             
@@ -1101,8 +1817,266 @@ impl Dashboard {
         frame.render_widget(stats_line, area);
     }
 
-    fn render_help_overlay(&self, _frame: &mut Frame, _area: Rect) {
-        // Simplified help overlay - would be implemented with centered popup
+    fn keybinds_for(view_mode: &crate::app::state::ViewMode) -> Vec<(&'static str, &'static str)> {
+        match view_mode {
+            crate::app::state::ViewMode::GlobalOverview => vec![
+                ("G", "Current Dir"),
+                ("L", "List All"),
+                ("S", "Sessions"),
+                ("T", "Graph"),
+                ("B", "Timeline"),
+                ("P", "Provider"),
+                ("R", "Refresh"),
+                ("Q", "Quit"),
+            ],
+            crate::app::state::ViewMode::CurrentDirectory => vec![
+                ("G", "Global View"),
+                ("L", "List All"),
+                ("S", "Sessions"),
+                ("T", "Graph"),
+                ("B", "Timeline"),
+                ("P", "Provider"),
+                ("R", "Refresh"),
+                ("Q", "Quit"),
+            ],
+            crate::app::state::ViewMode::ConversationList => vec![
+                ("G", "Global View"),
+                ("C", "Current Dir"),
+                ("S", "Sessions"),
+                ("B", "Timeline"),
+                ("P", "Provider"),
+                ("/", "Search"),
+                (".", "Jump to cwd"),
+                ("↑↓", "Navigate"),
+                ("PgUp/PgDn", "Page"),
+                ("Q", "Quit"),
+            ],
+            crate::app::state::ViewMode::SessionList => vec![
+                ("G", "Global"),
+                ("C", "Current"),
+                ("A", "Toggle Active"),
+                ("O", "Sort"),
+                ("B", "Timeline"),
+                ("P", "Provider"),
+                (".", "Jump to cwd"),
+                ("↑↓", "Navigate"),
+                ("Enter", "Details"),
+                ("Y", "Copy ID"),
+                ("Q", "Quit"),
+            ],
+            crate::app::state::ViewMode::UsageGraph => vec![
+                ("G", "Global"),
+                ("C", "Current"),
+                ("S", "Sessions"),
+                ("B", "Timeline"),
+                ("P", "Provider"),
+                ("Q", "Quit"),
+            ],
+            crate::app::state::ViewMode::Timeline => vec![
+                ("G", "Global"),
+                ("C", "Current"),
+                ("S", "Sessions"),
+                ("T", "Graph"),
+                ("P", "Provider"),
+                ("Q", "Quit"),
+            ],
+            crate::app::state::ViewMode::SessionDetail => vec![
+                ("Esc", "Back"),
+                ("*", "Pin"),
+                ("Y", "Copy ID"),
+                ("G", "Global"),
+                ("S", "Sessions"),
+                ("B", "Timeline"),
+                ("P", "Provider"),
+                ("Q", "Quit"),
+            ],
+        }
+    }
+
+    fn render_help_overlay(&self, frame: &mut Frame, area: Rect) {
+        let popup = centered_rect(60, 70, area);
+        let data_source = self.state.get_active_data_source();
+
+        let view_modes = [
+            ("Global Overview", crate::app::state::ViewMode::GlobalOverview),
+            ("Current Directory", crate::app::state::ViewMode::CurrentDirectory),
+            ("Conversation List", crate::app::state::ViewMode::ConversationList),
+            ("Session List", crate::app::state::ViewMode::SessionList),
+            ("Session Detail", crate::app::state::ViewMode::SessionDetail),
+            ("Usage Graph", crate::app::state::ViewMode::UsageGraph),
+            ("Timeline", crate::app::state::ViewMode::Timeline),
+        ];
+
+        let mut text = vec![
+            Line::from(vec![
+                Span::raw("Version: "),
+                Span::styled(
+                    format!("v{}", env!("CARGO_PKG_VERSION")),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw("  Data Source: "),
+                Span::styled(
+                    data_source.display_name(),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]),
+            Line::from(""),
+        ];
+
+        for (name, mode) in view_modes.iter() {
+            text.push(Line::from(Span::styled(
+                format!("{}:", name),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            for (key, desc) in Self::keybinds_for(mode) {
+                text.push(Line::from(vec![
+                    Span::styled(
+                        format!("  [{:>5}]", key),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(format!(" {}", desc)),
+                ]));
+            }
+            text.push(Line::from(""));
+        }
+
+        text.push(Line::from(vec![
+            Span::styled(format!("  [{:>5}]", "!"), Style::default().fg(Color::Yellow)),
+            Span::raw(" Collector log (any view)"),
+        ]));
+        text.push(Line::from(vec![
+            Span::styled(format!("  [{:>5}]", "U"), Style::default().fg(Color::Yellow)),
+            Span::raw(" Burn-rate unit (any view)"),
+        ]));
+        text.push(Line::from(vec![
+            Span::styled(format!("  [{:>5}]", "X"), Style::default().fg(Color::Yellow)),
+            Span::raw(" Reset burn rate (any view)"),
+        ]));
+        text.push(Line::from(vec![
+            Span::styled(format!("  [{:>5}]", "E"), Style::default().fg(Color::Yellow)),
+            Span::raw(" Export current view (any view)"),
+        ]));
+        text.push(Line::from(""));
+
+        text.push(Line::from(Span::styled(
+            "Press ? or Esc to close",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        // Keep the last line at the bottom edge rather than scrolling past it
+        let max_scroll = text.len().saturating_sub(popup.height.saturating_sub(2) as usize);
+        let scroll = self.help_scroll.get().min(max_scroll);
+        self.help_scroll.set(scroll);
+
+        let help = Paragraph::new(text)
+            .scroll((scroll as u16, 0))
+            .block(
+                Block::default()
+                    .title("Help - Keybindings (↑↓ scroll)")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
+            );
+
+        // Clear the area first so the dashboard doesn't bleed through
+        frame.render_widget(Clear, popup);
+        frame.render_widget(help, popup);
+    }
+
+    fn render_log_pane(&self, frame: &mut Frame, area: Rect) {
+        let popup = centered_rect(80, 60, area);
+        let entries = self.state.recent_log(LOG_PANE_ENTRIES);
+
+        let mut text: Vec<Line> = if entries.is_empty() {
+            vec![Line::from(Span::styled("No errors", Style::default().fg(Color::DarkGray)))]
+        } else {
+            entries
+                .iter()
+                .rev()
+                .map(|entry| {
+                    let (label, color) = match entry.level {
+                        LogLevel::Error => ("ERROR", Color::Red),
+                        LogLevel::Warning => ("WARN ", Color::Yellow),
+                    };
+                    Line::from(vec![
+                        Span::styled(
+                            self.state.config.timezone().format(&entry.at, "%H:%M:%S"),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::styled(format!(" {} ", label), Style::default().fg(color)),
+                        Span::raw(entry.message.clone()),
+                    ])
+                })
+                .collect()
+        };
+        if !self.state.config.verbose {
+            text.push(Line::from(""));
+            text.push(Line::from(Span::styled(
+                "Run with --verbose to include warnings",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        let log = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title("Collector Log - ! or Esc to close")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red)),
+        );
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(log, popup);
+    }
+
+    fn render_source_picker(&self, frame: &mut Frame, area: Rect, picker: &SourcePicker) {
+        let current = self.state.get_active_data_source();
+        let width = 44.min(area.width);
+        let height = (picker.options.len() as u16 + 4).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let mut text: Vec<Line> = picker
+            .options
+            .iter()
+            .enumerate()
+            .map(|(idx, (source, detected))| {
+                let (status, status_color) = if *detected {
+                    ("detected", Color::Green)
+                } else {
+                    ("not found", Color::DarkGray)
+                };
+                let marker = if idx == picker.selected { "▶ " } else { "  " };
+                let name_style = if idx == picker.selected {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Line::from(vec![
+                    Span::raw(marker),
+                    Span::styled(format!("{:<12}", source.display_name()), name_style),
+                    Span::styled(format!(" [{}]", status), Style::default().fg(status_color)),
+                    Span::raw(if *source == current { " (current)" } else { "" }),
+                ])
+            })
+            .collect();
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled(
+            "↑↓ choose · Enter switch · Esc cancel",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        let picker_widget = Paragraph::new(text).block(
+            Block::default()
+                .title("Data Source")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(picker_widget, popup);
     }
 
     fn get_usage_color(&self, percentage: f64) -> Color {
@@ -1111,39 +2085,123 @@ impl Dashboard {
 
         match data_source {
             crate::data::DataSourceType::ClaudeCode => {
-                // For Claude, use configurable thresholds
+                // Claude thresholds are stored as fractions of the limit
                 let warning_threshold = self.state.config.claude_warning_threshold * 100.0;
+                let critical_threshold = self.state.config.claude_critical_threshold * 100.0;
                 match percentage {
-                    p if p >= 95.0 => Color::Red,      // Critical at 95%
+                    p if p >= critical_threshold => Color::Red,  // Critical at config threshold (default 95%)
                     p if p >= warning_threshold => Color::Yellow,  // Warning at config threshold (default 80%)
                     _ => Color::Green,
                 }
             },
             _ => {
-                // For other data sources, use the standard thresholds
+                // For other data sources, the configured warning/critical thresholds
                 match percentage {
-                    p if p >= 90.0 => Color::Red,
-                    p if p >= 70.0 => Color::Yellow,
+                    p if p >= self.state.config.critical_threshold => Color::Red,
+                    p if p >= self.state.config.warning_threshold => Color::Yellow,
                     _ => Color::Green,
                 }
             }
         }
     }
 
-    pub fn is_switching_provider(&self) -> bool {
-        self.switching_provider
+    /// Source picked in the data-source popup, if a switch is pending
+    pub fn requested_source(&self) -> Option<DataSourceType> {
+        self.requested_source
     }
 
     pub fn reset_switching_flag(&mut self) {
         self.switching_provider = false;
+        self.requested_source = None;
+    }
+
+    /// Take the text copies that had no clipboard, to print after the dashboard exits
+    pub fn take_uncopied(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.uncopied)
+    }
+
+    /// Take a pending manual refresh request, clearing it
+    pub fn take_refresh_request(&mut self) -> bool {
+        std::mem::take(&mut self.refresh_requested)
     }
 
     pub fn handle_key(&mut self, key: crossterm::event::KeyCode) -> bool {
         use crossterm::event::KeyCode;
 
+        // Help overlay is modal - only scroll, dismiss or quit while it's visible
+        if self.show_help {
+            let scroll = self.help_scroll.get();
+            match key {
+                KeyCode::Char('?') | KeyCode::Esc => self.show_help = false,
+                KeyCode::Char('q') | KeyCode::Char('Q') => return false,
+                KeyCode::Up => self.help_scroll.set(scroll.saturating_sub(1)),
+                KeyCode::Down => self.help_scroll.set(scroll + 1),
+                KeyCode::PageUp => self.help_scroll.set(scroll.saturating_sub(self.page_size.get())),
+                KeyCode::PageDown => self.help_scroll.set(scroll + self.page_size.get()),
+                _ => {}
+            }
+            return true;
+        }
+
+        // So is the log pane
+        if self.show_log {
+            match key {
+                KeyCode::Char('!') | KeyCode::Esc => self.show_log = false,
+                KeyCode::Char('q') | KeyCode::Char('Q') => return false,
+                _ => {}
+            }
+            return true;
+        }
+
+        // The source picker is modal too
+        if let Some(picker) = self.source_picker.as_mut() {
+            match key {
+                KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+                KeyCode::Down => picker.selected = (picker.selected + 1).min(picker.options.len().saturating_sub(1)),
+                KeyCode::Enter => {
+                    // Only sources found on disk can be opened
+                    if let Some(&(source, true)) = picker.options.get(picker.selected) {
+                        if source != self.state.get_active_data_source() {
+                            self.requested_source = Some(source);
+                            self.switching_provider = true;
+                        }
+                        self.source_picker = None;
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('p') | KeyCode::Char('P') => self.source_picker = None,
+                KeyCode::Char('q') | KeyCode::Char('Q') => return false,
+                _ => {}
+            }
+            return true;
+        }
+
+        // While typing a search, characters edit the query; navigation still works
+        if self.search_editing {
+            match key {
+                KeyCode::Char(c) => self.edit_search(|query| query.push(c)),
+                KeyCode::Backspace => self.edit_search(|query| {
+                    query.pop();
+                }),
+                KeyCode::Enter => self.search_editing = false,
+                KeyCode::Esc => self.clear_search(),
+                _ => {}
+            }
+            if !matches!(key, KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End) {
+                return true;
+            }
+        }
+
         let mut view_mode = self.state.view_mode.lock().unwrap();
         
         match key {
+            KeyCode::Char('/') => {
+                // Start searching the conversation list
+                if matches!(*view_mode, crate::app::state::ViewMode::ConversationList) {
+                    self.search_editing = true;
+                    self.state.search_query.lock().unwrap().get_or_insert_with(String::new);
+                }
+                true
+            }
             KeyCode::Char('g') | KeyCode::Char('G') => {
                 // Go to global overview
                 *view_mode = crate::app::state::ViewMode::GlobalOverview;
@@ -1164,6 +2222,44 @@ impl Dashboard {
                 *view_mode = crate::app::state::ViewMode::SessionList;
                 true
             }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                // Show token usage graph
+                *view_mode = crate::app::state::ViewMode::UsageGraph;
+                true
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') => {
+                // Show billing block timeline
+                *view_mode = crate::app::state::ViewMode::Timeline;
+                true
+            }
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                // Reset the burn rate, e.g. after a long break
+                self.state.reset_burn_rate();
+                self.flash = Some(("Burn rate reset".to_string(), Instant::now()));
+                true
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                // Snapshot the current view to a file
+                let message = match self.export_current_view(&view_mode) {
+                    Ok((path, count, None)) => format!("Exported {} sessions to {}", count, path.display()),
+                    Ok((path, count, Some(labels))) => format!(
+                        "Exported {} anonymized sessions to {}, labels in {}",
+                        count,
+                        path.display(),
+                        labels.display()
+                    ),
+                    Err(e) => format!("Export failed: {}", e),
+                };
+                self.flash = Some((message, Instant::now()));
+                true
+            }
+            KeyCode::Char('u') | KeyCode::Char('U') => {
+                // Cycle the burn-rate unit
+                let mut unit = self.state.burn_rate_unit.lock().unwrap();
+                *unit = unit.next();
+                self.flash = Some((format!("Burn rate in {}", unit.label()), Instant::now()));
+                true
+            }
             KeyCode::Char('a') | KeyCode::Char('A') => {
                 // Toggle active filter in session list
                 if matches!(*view_mode, crate::app::state::ViewMode::SessionList) {
@@ -1175,100 +2271,134 @@ impl Dashboard {
                 }
                 true
             }
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                // Cycle session list sort order
+                if matches!(*view_mode, crate::app::state::ViewMode::SessionList) {
+                    let mut sort_key = self.state.sort_key.lock().unwrap();
+                    *sort_key = sort_key.next();
+                    // Reset selection and scroll since positions change
+                    *self.state.selected_conversation_index.lock().unwrap() = 0;
+                    *self.state.scroll_offset.lock().unwrap() = 0;
+                }
+                true
+            }
             KeyCode::Enter => {
                 // Enter detail view from session list
                 if matches!(*view_mode, crate::app::state::ViewMode::SessionList) {
-                    let directory_groups = self.state.directory_groups.lock().unwrap();
-                    let show_active_only = *self.state.show_active_only.lock().unwrap();
                     let selected_idx = *self.state.selected_conversation_index.lock().unwrap();
-                    
-                    // Build visible sessions list (same logic as render)
-                    let mut visible_sessions = Vec::new();
-                    for group in directory_groups.iter() {
-                        if show_active_only && group.active_session_count == 0 {
-                            continue;
-                        }
-                        for session in &group.sessions {
-                            if show_active_only && !session.is_active {
-                                continue;
-                            }
-                            visible_sessions.push(session.clone());
-                        }
-                    }
+                    let visible_sessions = self.state.visible_sessions();
                     
                     if selected_idx < visible_sessions.len() {
                         let selected_session = visible_sessions[selected_idx].clone();
                         *self.state.selected_session.lock().unwrap() = Some(selected_session);
                         *view_mode = crate::app::state::ViewMode::SessionDetail;
+                        // The collector builds the token chart for the session being viewed
+                        self.refresh_requested = true;
                     }
                 }
                 true
             }
-            KeyCode::Esc => {
-                // Go back from detail view
+            KeyCode::Char('*') => {
+                // Pin or unpin the session shown in the detail view
                 if matches!(*view_mode, crate::app::state::ViewMode::SessionDetail) {
-                    *view_mode = crate::app::state::ViewMode::SessionList;
+                    let selected = self.state.selected_session.lock().unwrap().clone();
+                    if let Some(session) = selected {
+                        let pinned = self.state.toggle_pinned_conversation(&session.conversation_id);
+                        if let Err(e) = self.state.config.save_pinned_conversation(pinned.as_deref()) {
+                            tracing::debug!("Failed to persist pinned conversation: {}", e);
+                        }
+                    }
                 }
                 true
             }
-            KeyCode::Up => {
-                // Navigate up in list views
-                if matches!(*view_mode, crate::app::state::ViewMode::ConversationList | crate::app::state::ViewMode::SessionList) {
-                    let mut selected = self.state.selected_conversation_index.lock().unwrap();
-                    if *selected > 0 {
-                        *selected -= 1;
+            KeyCode::Char('.') => {
+                // Select the session for the working directory; rendering scrolls it into view
+                if matches!(
+                    *view_mode,
+                    crate::app::state::ViewMode::SessionList | crate::app::state::ViewMode::ConversationList
+                ) {
+                    let current_dir = std::env::current_dir().unwrap_or_default();
+                    match self.row_for_directory(&view_mode, &current_dir.to_string_lossy()) {
+                        Some(index) => *self.state.selected_conversation_index.lock().unwrap() = index,
+                        None => self.flash = Some(("No session for current directory".to_string(), Instant::now())),
+                    }
+                }
+                true
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                // Copy the selected session's id and directory
+                if let Some(session) = self.selected_session_for(&view_mode) {
+                    let text = format!("{}\t{}", session.conversation_id, session.directory);
+                    let message = if crate::utils::clipboard::copy_text(&text) {
+                        "Copied session id".to_string()
+                    } else {
+                        // Anything written to stderr now would be painted over by the next frame
+                        self.uncopied.push(text);
+                        format!("No clipboard - session id {} (printed on exit)", session.conversation_id)
+                    };
+                    self.flash = Some((message, Instant::now()));
+                }
+                true
+            }
+            KeyCode::Esc => {
+                // Go back from detail view, or drop the conversation search
+                match *view_mode {
+                    crate::app::state::ViewMode::SessionDetail => {
+                        *view_mode = crate::app::state::ViewMode::SessionList;
                     }
+                    crate::app::state::ViewMode::ConversationList => {
+                        drop(view_mode);
+                        self.clear_search();
+                    }
+                    _ => {}
                 }
                 true
             }
-            KeyCode::Down => {
-                // Navigate down in list views
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                // Navigate in list views, clamped to the last item
                 if matches!(*view_mode, crate::app::state::ViewMode::ConversationList | crate::app::state::ViewMode::SessionList) {
                     let max_idx = match *view_mode {
                         crate::app::state::ViewMode::ConversationList => {
-                            self.state.all_conversations.lock().unwrap().len()
+                            self.state.visible_conversations().len()
                         }
                         crate::app::state::ViewMode::SessionList => {
-                            // Count visible sessions based on filter
-                            let directory_groups = self.state.directory_groups.lock().unwrap();
-                            let show_active_only = *self.state.show_active_only.lock().unwrap();
-                            let mut count = 0;
-                            for group in directory_groups.iter() {
-                                if show_active_only && group.active_session_count == 0 {
-                                    continue;
-                                }
-                                for session in &group.sessions {
-                                    if show_active_only && !session.is_active {
-                                        continue;
-                                    }
-                                    count += 1;
-                                }
-                            }
-                            count
+                            self.state.visible_sessions().len()
                         }
                         _ => 0,
-                    };
-                    
+                    }
+                    .saturating_sub(1);
+                    let page = self.page_size.get();
+
                     let mut selected = self.state.selected_conversation_index.lock().unwrap();
-                    if *selected < max_idx.saturating_sub(1) {
-                        *selected += 1;
+                    *selected = match key {
+                        KeyCode::Up => selected.saturating_sub(1),
+                        KeyCode::Down => *selected + 1,
+                        KeyCode::PageUp => selected.saturating_sub(page),
+                        KeyCode::PageDown => *selected + page,
+                        KeyCode::Home => 0,
+                        _ => max_idx,
                     }
+                    .min(max_idx);
                 }
                 true
             }
             KeyCode::Char('p') | KeyCode::Char('P') => {
-                // Toggle provider - mark that we want to switch
-                self.switching_provider = true;
-                // The actual switching would need to be handled at a higher level
-                // since it requires restarting the collector
+                // Pick a provider; the main loop restarts the collector once one is chosen
+                self.source_picker = Some(SourcePicker::detect(self.state.get_active_data_source(), &self.state.config));
                 true
             }
             KeyCode::Char('r') | KeyCode::Char('R') => {
-                // Force refresh
+                // Force refresh - picked up by the main loop
+                self.refresh_requested = true;
+                true
+            }
+            KeyCode::Char('!') => {
+                self.show_log = true;
                 true
             }
             KeyCode::Char('?') => {
                 self.show_help = !self.show_help;
+                self.help_scroll.set(0);
                 true
             }
             KeyCode::Char('q') | KeyCode::Char('Q') => false,
@@ -1276,3 +2406,1047 @@ impl Dashboard {
         }
     }
 }
+
+/// Metrics widget height when it includes the period table
+const METRICS_HEIGHT_WITH_TABLE: u16 = 10;
+/// Metrics widget height with a single 30-day line
+const METRICS_HEIGHT_COMPACT: u16 = 8;
+/// View height needed before the metrics widget grows to fit the table
+const METRICS_TABLE_MIN_AREA: u16 = 24;
+
+/// Cells in the budget burn-down bar
+const BUDGET_BAR_WIDTH: usize = 10;
+/// Budget share past which the gauge turns yellow (red once over budget)
+const BUDGET_WARNING_FRACTION: f64 = 0.8;
+
+/// Smallest terminal the normal layout is drawn in
+const MIN_TERMINAL_WIDTH: u16 = 40;
+const MIN_TERMINAL_HEIGHT: u16 = 15;
+
+/// Rows the session list keeps before the metrics widget is dropped
+const SESSION_LIST_MIN_HEIGHT: u16 = 10;
+
+/// Height of the cumulative token chart in the session detail, borders included
+const SESSION_CHART_HEIGHT: u16 = 6;
+
+/// Context files shown in session details before the rest are elided
+const CONTEXT_FILES_SHOWN: usize = 3;
+
+/// "[Q] " / "[CC] " for entries from a merged source, empty otherwise
+fn provider_tag(provider: Option<crate::data::factory::DataSourceType>) -> String {
+    provider.map(|p| format!("[{}] ", p.tag())).unwrap_or_default()
+}
+
+/// "Context files: N (a, b, c, ...)" for session details
+fn context_files_summary(files: &[String]) -> String {
+    let mut shown = files.iter().take(CONTEXT_FILES_SHOWN).cloned().collect::<Vec<_>>().join(", ");
+    if files.len() > CONTEXT_FILES_SHOWN {
+        shown.push_str(", ...");
+    }
+    format!("  Context files: {} ({})", files.len(), shown)
+}
+
+/// `num / den`, or 0 when the denominator is zero, so empty stats never render NaN or inf
+fn safe_div(num: f64, den: f64) -> f64 {
+    if den == 0.0 { 0.0 } else { num / den }
+}
+
+/// At most `width` evenly spaced points from `values`, always keeping the last
+fn resample(values: &[u64], width: usize) -> Vec<u64> {
+    if width == 0 || values.len() <= width {
+        return values.to_vec();
+    }
+    (1..=width)
+        .map(|i| values[i * values.len() / width - 1])
+        .collect()
+}
+
+/// Create `stem.ext` in `dir`, or `stem-2.ext`, `stem-3.ext`, ... when taken, so
+/// exports made within the same second don't overwrite each other
+fn create_export_file(dir: &std::path::Path, stem: &str, extension: &str) -> std::io::Result<(std::path::PathBuf, std::fs::File)> {
+    let mut attempt = 1;
+    loop {
+        let name = if attempt == 1 {
+            format!("{}.{}", stem, extension)
+        } else {
+            format!("{}-{}.{}", stem, attempt, extension)
+        };
+        let path = dir.join(name);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A marker drawn next to dashboard text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Icon {
+    Burn,
+    Warning,
+    Live,
+    Folder,
+    Active,
+    Inactive,
+    ContextFiles,
+    CostSpike,
+    OverCap,
+    NearlyFull,
+    Filling,
+    Pin,
+    Chart,
+    CostRate,
+    Budget,
+}
+
+impl Icon {
+    /// Emoji, or an ASCII stand-in when color is disabled
+    fn glyph(self, color_enabled: bool) -> &'static str {
+        match (self, color_enabled) {
+            (Icon::Burn, true) => "🔥",
+            (Icon::Warning, true) => "⚠️ ",
+            (Icon::Live, true) => "🔴",
+            (Icon::Folder, true) => "📁",
+            (Icon::Active, true) => "🟢",
+            (Icon::Inactive, true) => "⚫",
+            (Icon::ContextFiles, true) => "📎",
+            (Icon::CostSpike, true) => "⚡",
+            (Icon::OverCap, true) => "🛑",
+            (Icon::NearlyFull, true) => "🔴",
+            (Icon::Filling, true) => "🟡",
+            (Icon::Pin, true) => "📌",
+            (Icon::Chart, true) => "📊",
+            (Icon::CostRate, true) => "💲",
+            (Icon::Budget, true) => "💰",
+            (Icon::Burn, false) => "~",
+            (Icon::Warning, false) => "!",
+            (Icon::Live, false) => "*",
+            (Icon::Folder, false) => ">",
+            (Icon::Active, false) => "+",
+            (Icon::Inactive, false) => "-",
+            (Icon::ContextFiles, false) => "@",
+            (Icon::CostSpike, false) => "^",
+            (Icon::OverCap, false) => "[CAP]",
+            (Icon::NearlyFull, false) => "[CRIT]",
+            (Icon::Filling, false) => "[WARN]",
+            (Icon::Pin, false) => "*",
+            (Icon::Chart, false) => "##",
+            (Icon::CostRate, false) => "$",
+            (Icon::Budget, false) => "$",
+        }
+    }
+}
+
+/// A row of the grouped session list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionRow {
+    /// Directory heading
+    Header,
+    /// Session, by its index among visible sessions
+    Session(usize),
+}
+
+/// Rows for groups of the given sizes: each group's header, then its sessions
+fn session_rows(group_sizes: impl IntoIterator<Item = usize>) -> Vec<SessionRow> {
+    let mut rows = Vec::new();
+    let mut session_idx = 0;
+    for size in group_sizes {
+        rows.push(SessionRow::Header);
+        rows.extend((session_idx..session_idx + size).map(SessionRow::Session));
+        session_idx += size;
+    }
+    rows
+}
+
+/// Row showing the session at `session_idx`
+fn row_of_session(rows: &[SessionRow], session_idx: usize) -> Option<usize> {
+    rows.iter().position(|row| *row == SessionRow::Session(session_idx))
+}
+
+/// First row to show so `selected` stays within a page, scrolling as little
+/// as possible from `offset` and keeping a group's header above its first session
+fn scroll_offset(rows: &[SessionRow], offset: usize, selected: usize, page_size: usize) -> usize {
+    let page_size = page_size.max(1);
+    let offset = if selected < offset {
+        selected
+    } else if selected >= offset + page_size {
+        selected + 1 - page_size
+    } else {
+        offset
+    };
+
+    let header_above = selected > 0 && rows.get(selected - 1) == Some(&SessionRow::Header);
+    let offset = if header_above && offset == selected && page_size > 1 {
+        selected - 1
+    } else {
+        offset
+    };
+    offset.min(rows.len().saturating_sub(page_size))
+}
+
+/// Percentage of `part` in `total`, 0 when there is no total
+fn share(part: f64, total: f64) -> f64 {
+    safe_div(part, total) * 100.0
+}
+
+/// "I/O Ratio: 1:0.25 (in 120K / out 30K) | output 62% of cost", None without input
+fn io_ratio_line(io: &crate::data::database::IoSplit) -> Option<Line<'static>> {
+    let ratio = io.ratio()?;
+    Some(Line::from(vec![
+        Span::raw("I/O Ratio: "),
+        Span::styled(format!("1:{:.2}", ratio), Style::default().fg(Color::Cyan)),
+        Span::raw(format!(
+            " (in {} / out {}) | output ",
+            format_token_count(io.input_tokens),
+            format_token_count(io.output_tokens)
+        )),
+        Span::styled(format!("{:.0}%", io.output_cost_percentage()), Style::default().fg(Color::Green)),
+        Span::raw(" of cost"),
+    ]))
+}
+
+/// Today / 7d / 30d / 365d rollups as period, tokens and cost rows
+fn period_table(periods: &crate::data::database::PeriodMetrics, config: &crate::app::config::AppConfig) -> Vec<Line<'static>> {
+    let columns = [
+        ("Today", periods.today_tokens, periods.today_cost),
+        ("7d", periods.week_tokens, periods.week_cost),
+        ("30d", periods.month_tokens, periods.month_cost),
+        ("365d", periods.year_tokens, periods.year_cost),
+    ];
+
+    let header: String = columns.iter().map(|(label, _, _)| format!("{:>10}", label)).collect();
+    let tokens: String = columns.iter().map(|(_, t, _)| format!("{:>10}", format_token_count(*t))).collect();
+    let costs: String = columns.iter().map(|(_, _, c)| format!("{:>10}", config.format_cost(*c))).collect();
+
+    vec![
+        Line::from(Span::styled(format!("{} Period{}", Icon::Chart.glyph(config.color_enabled()), header), Style::default().fg(Color::Cyan))),
+        Line::from(format!("   Tokens{}", tokens)),
+        Line::from(format!("   Cost  {}", costs)),
+    ]
+}
+
+/// Footnote for Amazon Q, which has no per-message timestamps, so each
+/// conversation lands in the periods of its directory's last `q` run
+fn period_disclaimer(source: DataSourceType) -> Option<Line<'static>> {
+    (source == DataSourceType::AmazonQ).then(|| {
+        Line::from(Span::styled(
+            "Amazon Q periods are bucketed by last activity per directory",
+            Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        ))
+    })
+}
+
+/// Format a token count compactly, e.g. 420K or 1.2M
+pub(crate) fn format_token_count(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{}K", tokens / 1_000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Format a duration compactly, e.g. 3h12m or 45m
+fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes >= 60 {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// "(3 unreadable conversations)" when some stored conversations couldn't be parsed
+fn unreadable_label(parse_failures: usize) -> Option<String> {
+    match parse_failures {
+        0 => None,
+        1 => Some("(1 unreadable conversation)".to_string()),
+        n => Some(format!("({} unreadable conversations)", n)),
+    }
+}
+
+/// Longest time-to-threshold worth showing; beyond this the estimate means little
+const MAX_THRESHOLD_ETA_MINUTES: f64 = 24.0 * 60.0;
+
+/// How long until `used` reaches `threshold_tokens` at `tokens_per_minute`
+///
+/// None when the rate is zero, the threshold is already passed, or the estimate
+/// is more than a day out; shorter estimates are rounded up to at least a minute.
+fn time_to_threshold(used: u64, threshold_tokens: f64, tokens_per_minute: f64) -> Option<chrono::Duration> {
+    let remaining = threshold_tokens - used as f64;
+    if tokens_per_minute <= 0.0 || remaining <= 0.0 {
+        return None;
+    }
+    let minutes = (remaining / tokens_per_minute).ceil().max(1.0);
+    (minutes <= MAX_THRESHOLD_ETA_MINUTES).then(|| chrono::Duration::minutes(minutes as i64))
+}
+
+/// "Block resets in 1h47m", and whether the reset is close enough to highlight
+fn block_reset_label(
+    end_time: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<(String, bool)> {
+    let remaining = end_time - now;
+    (remaining > chrono::Duration::zero()).then(|| {
+        (
+            format!("Block resets in {}", format_duration(remaining)),
+            remaining.num_minutes() < BLOCK_RESET_SOON_MINUTES,
+        )
+    })
+}
+
+/// "stale 4m" once no new data has arrived for `stale_after_secs`
+fn stale_label(
+    last_refresh: chrono::DateTime<chrono::Local>,
+    now: chrono::DateTime<chrono::Local>,
+    stale_after_secs: u64,
+) -> Option<String> {
+    let age = now - last_refresh;
+    (stale_after_secs > 0 && age.num_seconds() >= stale_after_secs as i64)
+        .then(|| format!("stale {}", format_duration(age)))
+}
+
+/// Compute a rectangle centered within `area` using percentage dimensions
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::AppConfig;
+    use crossterm::event::KeyCode;
+
+    fn test_dashboard() -> Dashboard {
+        Dashboard::new(Arc::new(AppState::new(AppConfig::default())))
+    }
+
+    fn empty_stats() -> crate::data::database::GlobalStats {
+        crate::data::database::GlobalStats::default()
+    }
+
+    /// Draw the whole dashboard on a test terminal and return its cells as text
+    fn render_to_string(dashboard: &Dashboard, width: u16, height: u16) -> String {
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| dashboard.render(f)).unwrap();
+        terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect()
+    }
+
+    #[test]
+    fn test_claude_usage_color_uses_claude_thresholds() {
+        let config = AppConfig { claude_critical_threshold: 0.9, imminent_threshold: 99.0, ..AppConfig::default() };
+        let dashboard = Dashboard::new(Arc::new(AppState::new(config)));
+        dashboard.state.set_active_data_source(DataSourceType::ClaudeCode);
+
+        assert_eq!(dashboard.get_usage_color(92.0), Color::Red);
+        assert_eq!(dashboard.get_usage_color(85.0), Color::Yellow);
+        assert_eq!(dashboard.get_usage_color(50.0), Color::Green);
+    }
+
+    #[test]
+    fn test_help_toggle() {
+        let mut dashboard = test_dashboard();
+        assert!(!dashboard.show_help);
+
+        assert!(dashboard.handle_key(KeyCode::Char('?')));
+        assert!(dashboard.show_help);
+
+        assert!(dashboard.handle_key(KeyCode::Char('?')));
+        assert!(!dashboard.show_help);
+    }
+
+    #[test]
+    fn test_help_scrolls_to_the_last_line() {
+        let mut dashboard = test_dashboard();
+        let screen = |dashboard: &Dashboard| render_to_string(dashboard, 100, 30);
+
+        dashboard.handle_key(KeyCode::Char('?'));
+        assert!(!screen(&dashboard).contains("Press ? or Esc to close"));
+
+        for _ in 0..200 {
+            dashboard.handle_key(KeyCode::Down);
+        }
+        assert!(screen(&dashboard).contains("Press ? or Esc to close"));
+
+        // Scrolling back up starts from the clamped position, not from 200
+        dashboard.handle_key(KeyCode::Up);
+        assert!(!screen(&dashboard).contains("Press ? or Esc to close"));
+        assert!(dashboard.show_help);
+    }
+
+    #[test]
+    fn test_refresh_key_requests_refresh_once() {
+        let mut dashboard = test_dashboard();
+        assert!(!dashboard.take_refresh_request());
+
+        assert!(dashboard.handle_key(KeyCode::Char('r')));
+        assert!(dashboard.take_refresh_request());
+        assert!(!dashboard.take_refresh_request());
+    }
+
+    #[test]
+    fn test_copy_key_needs_a_selection() {
+        let mut dashboard = test_dashboard();
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::GlobalOverview;
+        assert!(dashboard.handle_key(KeyCode::Char('y')));
+        assert!(dashboard.active_flash().is_none());
+
+        // Confirmations expire
+        dashboard.flash = Some(("Copied session id".to_string(), Instant::now()));
+        assert_eq!(dashboard.active_flash(), Some("Copied session id"));
+        dashboard.flash = Some(("Copied session id".to_string(), Instant::now() - FLASH_DURATION));
+        assert!(dashboard.active_flash().is_none());
+    }
+
+    #[test]
+    fn test_copy_without_clipboard_shows_and_keeps_the_id() {
+        let mut dashboard = test_dashboard();
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionDetail;
+        *dashboard.state.selected_session.lock().unwrap() = Some(crate::data::database::Session::test("/work/api", 100, 0.0));
+
+        assert!(dashboard.handle_key(KeyCode::Char('y')));
+        let flash = dashboard.active_flash().unwrap().to_string();
+        let uncopied = dashboard.take_uncopied();
+        // Headless test runs have no clipboard, but a desktop one may
+        if flash == "Copied session id" {
+            assert!(uncopied.is_empty());
+        } else {
+            assert!(flash.contains("/work/api"), "{}", flash);
+            assert_eq!(uncopied, vec!["/work/api\t/work/api".to_string()]);
+            assert!(dashboard.take_uncopied().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_help_dismissed_with_esc() {
+        let mut dashboard = test_dashboard();
+        dashboard.handle_key(KeyCode::Char('?'));
+        assert!(dashboard.show_help);
+
+        assert!(dashboard.handle_key(KeyCode::Esc));
+        assert!(!dashboard.show_help);
+    }
+
+    #[test]
+    fn test_centered_rect() {
+        let area = Rect::new(0, 0, 100, 50);
+        let popup = centered_rect(60, 70, area);
+        assert_eq!(popup.width, 60);
+        assert_eq!(popup.height, 35);
+        assert_eq!(popup.x, 20);
+    }
+
+    #[test]
+    fn test_usage_graph_keybind() {
+        let mut dashboard = test_dashboard();
+        assert!(dashboard.handle_key(KeyCode::Char('t')));
+        assert!(matches!(
+            *dashboard.state.view_mode.lock().unwrap(),
+            crate::app::state::ViewMode::UsageGraph
+        ));
+    }
+
+    #[test]
+    fn test_render_usage_graph() {
+        let dashboard = test_dashboard();
+        let now = chrono::Local::now();
+        dashboard.state.usage_history.lock().unwrap().extend([
+            (now - chrono::Duration::minutes(10), 1_000),
+            (now, 5_000),
+        ]);
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| dashboard.render_usage_graph(f, f.size())).unwrap();
+
+        let rendered: String = terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect();
+        assert!(rendered.contains("Token Usage - Last 24h"));
+        assert!(rendered.contains(&now.format("%H:%M").to_string()));
+    }
+
+    #[test]
+    fn test_render_timeline_shows_idle_gaps() {
+        use crate::utils::session_blocks::{identify_session_blocks, ClaudeMessage, ClaudeTokenUsage, ClaudeUsageEntry};
+
+        let entry = |timestamp: chrono::DateTime<chrono::Utc>| ClaudeUsageEntry {
+            timestamp: timestamp.to_rfc3339(),
+            session_id: None,
+            message: ClaudeMessage {
+                usage: ClaudeTokenUsage { input_tokens: 1_000, output_tokens: 500, ..Default::default() },
+                model: None,
+                id: None,
+            },
+            cost_usd: Some(0.5),
+            request_id: None,
+            cwd: None,
+            version: None,
+            is_api_error_message: None,
+        };
+        // Two blocks separated by a long idle stretch
+        let start = chrono::Utc::now() - chrono::Duration::days(2);
+        let entries = vec![entry(start), entry(start + chrono::Duration::hours(8) + chrono::Duration::minutes(12))];
+
+        let dashboard = test_dashboard();
+        *dashboard.state.claude_blocks.lock().unwrap() = identify_session_blocks(&entries, None);
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|f| dashboard.render_timeline(f, f.size())).unwrap();
+
+        let rendered: String = terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect();
+        assert!(rendered.contains("idle 3h12m"));
+        assert!(rendered.contains("1K tokens"));
+
+        // Gaps can be hidden without touching the blocks themselves
+        let config = AppConfig { show_gap_blocks: false, ..AppConfig::default() };
+        let dashboard = Dashboard::new(Arc::new(AppState::new(config)));
+        *dashboard.state.claude_blocks.lock().unwrap() = identify_session_blocks(&entries, None);
+        terminal.draw(|f| dashboard.render_timeline(f, f.size())).unwrap();
+        let rendered: String = terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect();
+        assert!(!rendered.contains("idle"));
+        assert_eq!(dashboard.state.claude_blocks.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_metrics_widget_period_table() {
+        let dashboard = test_dashboard();
+        *dashboard.state.global_stats.lock().unwrap() = Some(crate::data::database::GlobalStats {
+            total_conversations: 1,
+            total_tokens: 1_000,
+            average_tokens: 1_000,
+            total_messages: 1,
+            message_quota_used: 1,
+            message_quota_limit: 5_000,
+            ..empty_stats()
+        });
+        *dashboard.state.period_metrics.lock().unwrap() = Some(crate::data::database::PeriodMetrics {
+            today_tokens: 1_500,
+            today_cost: 0.25,
+            week_tokens: 12_000,
+            week_cost: 1.5,
+            month_tokens: 40_000,
+            month_cost: 6.0,
+            year_tokens: 2_500_000,
+            year_cost: 90.0,
+        });
+
+        let render = |height| {
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, height)).unwrap();
+            terminal.draw(|f| dashboard.render_metrics_widget(f, f.size())).unwrap();
+            terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect::<String>()
+        };
+
+        let full = render(METRICS_HEIGHT_WITH_TABLE);
+        for expected in ["Today", "7d", "30d", "365d", "2.5M", "$90.00", "$0.25"] {
+            assert!(full.contains(expected), "missing {}", expected);
+        }
+
+        // Short widgets fall back to the single 30-day line
+        let compact = render(METRICS_HEIGHT_COMPACT);
+        assert!(compact.contains("Last 30 Days"));
+        assert!(!compact.contains("365d"));
+    }
+
+    #[test]
+    fn test_period_disclaimer_only_for_amazon_q() {
+        let dashboard = test_dashboard();
+        *dashboard.state.global_stats.lock().unwrap() = Some(empty_stats());
+        *dashboard.state.period_metrics.lock().unwrap() = Some(crate::data::database::PeriodMetrics {
+            today_tokens: 0,
+            today_cost: 0.0,
+            week_tokens: 0,
+            week_cost: 0.0,
+            month_tokens: 0,
+            month_cost: 0.0,
+            year_tokens: 0,
+            year_cost: 0.0,
+        });
+
+        let render = |source| {
+            dashboard.state.set_active_data_source(source);
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 24)).unwrap();
+            terminal.draw(|f| {
+                let halves = Layout::default()
+                    .constraints([Constraint::Length(METRICS_HEIGHT_WITH_TABLE), Constraint::Min(0)])
+                    .split(f.size());
+                dashboard.render_metrics_widget(f, halves[0]);
+                dashboard.render_global_stats(f, halves[1]);
+            }).unwrap();
+            terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect::<String>()
+        };
+
+        let note = "Amazon Q periods are bucketed by last activity per directory";
+        assert_eq!(render(DataSourceType::AmazonQ).matches(note).count(), 2);
+        assert!(!render(DataSourceType::ClaudeCode).contains(note));
+    }
+
+    #[test]
+    fn test_safe_div() {
+        assert_eq!(safe_div(10.0, 4.0), 2.5);
+        assert_eq!(safe_div(10.0, 0.0), 0.0);
+        assert_eq!(safe_div(0.0, 0.0), 0.0);
+        assert_eq!(share(1.0, 4.0), 25.0);
+        assert_eq!(share(1.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_light_theme_remaps_dashboard_colors() {
+        let render = |theme| {
+            let config = AppConfig { theme, ..AppConfig::default() };
+            let dashboard = Dashboard::new(Arc::new(AppState::new(config)));
+            *dashboard.state.global_stats.lock().unwrap() = Some(empty_stats());
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(120, 40)).unwrap();
+            terminal.draw(|f| dashboard.render(f)).unwrap();
+            terminal.backend().buffer().content.iter().map(|c| c.fg).collect::<Vec<_>>()
+        };
+
+        let dark = render(crate::app::config::Theme::Dark);
+        let light = render(crate::app::config::Theme::Light);
+        assert!(dark.contains(&Color::Yellow));
+        assert!(!light.contains(&Color::Yellow) && !light.contains(&Color::White));
+        assert_ne!(dark, light);
+    }
+
+    #[test]
+    fn test_render_with_all_zero_stats() {
+        let dashboard = test_dashboard();
+        *dashboard.state.global_stats.lock().unwrap() = Some(empty_stats());
+        {
+            let mut usage = dashboard.state.token_usage.lock().unwrap();
+            usage.used = 0;
+            usage.limit = 0;
+            usage.context_window = 0;
+            usage.message_count = 0;
+        }
+
+        let screen = render_to_string(&dashboard, 120, 50);
+        assert!(!screen.contains("NaN"));
+        assert!(!screen.contains("inf%"));
+    }
+
+    #[test]
+    fn test_resample_keeps_shape_and_last_point() {
+        let values: Vec<u64> = (1..=10).collect();
+        assert_eq!(resample(&values, 20), values);
+        assert_eq!(resample(&values, 5), vec![2, 4, 6, 8, 10]);
+        assert_eq!(resample(&values, 3), vec![3, 6, 10]);
+    }
+
+    #[test]
+    fn test_tiny_terminal_shows_size_message() {
+        let dashboard = test_dashboard();
+        let render = |width, height| render_to_string(&dashboard, width, height);
+
+        let tiny = render(30, 8);
+        assert!(tiny.contains("Terminal"));
+        assert!(tiny.contains("small"));
+        assert!(!render(MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT).contains("too small"));
+
+        // A short session list drops the metrics widget rather than squeezing it
+        *dashboard.state.global_stats.lock().unwrap() = Some(empty_stats());
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionList;
+        assert!(!render(80, 20).contains("System Metrics"));
+        assert!(render(80, 40).contains("System Metrics"));
+    }
+
+    #[test]
+    fn test_session_list_flags_directories_over_cap() {
+        let config = AppConfig {
+            directory_caps: [("api".to_string(), 10.0), ("web".to_string(), 50.0)].into_iter().collect(),
+            ..AppConfig::default()
+        };
+        let dashboard = Dashboard::new(Arc::new(AppState::new(config)));
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionList;
+
+        let group = |directory: &str, cost: f64| {
+            let mut session = crate::data::database::Session::test(directory, 1_000, cost);
+            session.conversation_id = format!("conv-{}", cost);
+            session.token_usage.percentage = 0.5;
+            crate::data::database::DirectoryGroup {
+                directory: directory.to_string(),
+                sessions: vec![session],
+                total_tokens: 1_000,
+                total_cost: cost,
+                active_session_count: 1,
+            }
+        };
+        *dashboard.state.directory_groups.lock().unwrap() = vec![group("/work/api", 12.5), group("/work/web", 5.0)];
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(140, 40)).unwrap();
+        terminal.draw(|f| dashboard.render(f)).unwrap();
+        let lines: Vec<String> = terminal.backend().buffer().content
+            .chunks(140)
+            .map(|row| row.iter().map(|c| c.symbol()).collect())
+            .collect();
+
+        let api = lines.iter().find(|l| l.contains("/work/api (")).unwrap();
+        assert!(api.contains("🛑") && api.contains("$12.50/$10.00"));
+        let web = lines.iter().find(|l| l.contains("/work/web (")).unwrap();
+        assert!(!web.contains("🛑") && web.contains("$5.00/$50.00"));
+
+        // --no-color swaps every marker for its ASCII stand-in
+        let mono = Dashboard::new(Arc::new(AppState::new(AppConfig { no_color: true, ..dashboard.state.config.clone() })));
+        *mono.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionList;
+        *mono.state.directory_groups.lock().unwrap() = dashboard.state.directory_groups.lock().unwrap().clone();
+        *mono.state.global_stats.lock().unwrap() = Some(empty_stats());
+        let screen = render_to_string(&mono, 140, 40);
+        assert!(screen.contains("> /work/api (1 sessions) [CAP] $12.50/$10.00"));
+        assert!(screen.contains("~ Burn Rate"));
+        assert!(!screen.chars().any(|c| ('\u{1F300}'..='\u{1FAFF}').contains(&c) || c == '⚡' || c == '⚫'));
+    }
+
+    #[test]
+    fn test_export_key_writes_current_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig {
+            export_dir: Some(dir.path().join("exports")),
+            export_format: crate::app::config::ExportFormat::Csv,
+            ..AppConfig::default()
+        };
+        let mut dashboard = Dashboard::new(Arc::new(AppState::new(config)));
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionList;
+        *dashboard.state.sort_key.lock().unwrap() = crate::app::state::SortKey::Cost;
+
+        // Nothing collected yet
+        assert!(dashboard.handle_key(KeyCode::Char('e')));
+        assert_eq!(dashboard.active_flash(), Some("Export failed: Configuration error: No data collected yet"));
+
+        let group = |directory: &str, cost: f64, is_active: bool| {
+            let mut session = crate::data::database::Session::test(directory, 1_000, cost);
+            session.conversation_id = format!("conv-{}", cost);
+            session.token_usage.percentage = 0.5;
+            session.is_active = is_active;
+            crate::data::database::DirectoryGroup {
+                directory: directory.to_string(),
+                sessions: vec![session],
+                total_tokens: 1_000,
+                total_cost: cost,
+                active_session_count: usize::from(is_active),
+            }
+        };
+        *dashboard.state.directory_groups.lock().unwrap() = vec![
+            group("/work/web", 5.0, true),
+            group("/work/api", 12.5, true),
+            group("/work/old", 99.0, false),
+        ];
+        *dashboard.state.global_stats.lock().unwrap() = Some(empty_stats());
+
+        assert!(dashboard.handle_key(KeyCode::Char('e')));
+        let flash = dashboard.active_flash().unwrap().to_string();
+        assert!(flash.starts_with("Exported 2 sessions to "), "{}", flash);
+
+        let written: Vec<_> = std::fs::read_dir(dir.path().join("exports")).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(written.len(), 1);
+        assert!(flash.ends_with(&written[0].display().to_string()));
+        assert_eq!(written[0].extension().unwrap(), "csv");
+
+        // Inactive sessions stay filtered out and rows follow the cost sort
+        let written_first = written[0].clone();
+        let csv = std::fs::read_to_string(&written_first).unwrap();
+        let rows: Vec<&str> = csv.lines().skip(1).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("conv-12.5,/work/api,1000,0.5,1,12.5000,"));
+        assert!(rows[1].starts_with("conv-5,/work/web,"));
+
+        // A second export in the same second gets its own file
+        assert!(dashboard.handle_key(KeyCode::Char('e')));
+        assert!(dashboard.handle_key(KeyCode::Char('e')));
+        let written = std::fs::read_dir(dir.path().join("exports")).unwrap().count();
+        assert_eq!(written, 3);
+        assert_eq!(std::fs::read_to_string(&written_first).unwrap(), csv);
+
+        // Anonymized exports keep the label mapping beside the config, not with the report
+        let config = AppConfig {
+            anonymize: true,
+            export_dir: Some(dir.path().join("shared")),
+            config_path: Some(dir.path().join("config.toml")),
+            ..dashboard.state.config.clone()
+        };
+        let mut anonymized = Dashboard::new(Arc::new(AppState::new(config)));
+        *anonymized.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionList;
+        *anonymized.state.directory_groups.lock().unwrap() = dashboard.state.directory_groups.lock().unwrap().clone();
+        *anonymized.state.global_stats.lock().unwrap() = Some(empty_stats());
+
+        assert!(anonymized.handle_key(KeyCode::Char('e')));
+        let labels = dir.path().join("anonymized-labels.txt");
+        let flash = anonymized.active_flash().unwrap().to_string();
+        assert!(flash.ends_with(&format!("labels in {}", labels.display())), "{}", flash);
+        let shared: Vec<_> = std::fs::read_dir(dir.path().join("shared")).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(shared.len(), 1);
+        assert!(!std::fs::read_to_string(&shared[0]).unwrap().contains("/work/api"));
+        assert!(std::fs::read_to_string(&labels).unwrap().contains("  /work/api\n"));
+    }
+
+    #[test]
+    fn test_provider_tag_only_for_merged_sessions() {
+        let dashboard = test_dashboard();
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionList;
+        let session = |id: &str, provider| crate::data::database::Session {
+            conversation_id: id.to_string(),
+            provider,
+            ..crate::data::database::Session::test("/work/api", 1_000, 0.0)
+        };
+        let sessions = vec![session("plain-id", None), session("merged-id", Some(DataSourceType::ClaudeCode))];
+        *dashboard.state.directory_groups.lock().unwrap() = vec![crate::data::database::DirectoryGroup {
+            directory: "/work/api".to_string(),
+            sessions: sessions.clone(),
+            total_tokens: 2_000,
+            total_cost: 0.0,
+            active_session_count: 2,
+        }];
+
+        let screen = |dashboard: &Dashboard| render_to_string(dashboard, 140, 30);
+
+        let list = screen(&dashboard);
+        assert!(list.contains("[CC] merged-i"));
+        assert!(!list.contains("] plain-id"));
+
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::SessionDetail;
+        *dashboard.state.selected_session.lock().unwrap() = Some(sessions[1].clone());
+        assert!(screen(&dashboard).contains("Session: [CC] merged-id"));
+        *dashboard.state.selected_session.lock().unwrap() = Some(sessions[0].clone());
+        assert!(screen(&dashboard).contains("Session: plain-id"));
+    }
+
+    #[test]
+    fn test_collector_errors_toast_and_log_pane() {
+        let mut dashboard = test_dashboard();
+        assert!(dashboard.error_toast().is_none());
+
+        dashboard.state.log_error("Data collection error: database is locked");
+        assert_eq!(dashboard.error_toast().as_deref(), Some("Data collection error: database is locked"));
+
+        assert!(render_to_string(&dashboard, 100, 30).contains("database is locked (! for log)"));
+
+        // The pane is modal until closed
+        assert!(dashboard.handle_key(KeyCode::Char('!')));
+        assert!(dashboard.show_log);
+        assert!(dashboard.handle_key(KeyCode::Char('t')));
+        assert!(dashboard.show_log);
+        assert!(render_to_string(&dashboard, 100, 30).contains("Collector Log"));
+        assert!(dashboard.handle_key(KeyCode::Esc));
+        assert!(!dashboard.show_log);
+    }
+
+    #[test]
+    fn test_session_rows_map_headers_and_sessions() {
+        let rows = session_rows([2, 0, 1]);
+        assert_eq!(
+            rows,
+            vec![
+                SessionRow::Header,
+                SessionRow::Session(0),
+                SessionRow::Session(1),
+                SessionRow::Header,
+                SessionRow::Header,
+                SessionRow::Session(2),
+            ]
+        );
+        assert_eq!(row_of_session(&rows, 0), Some(1));
+        assert_eq!(row_of_session(&rows, 2), Some(5));
+        assert_eq!(row_of_session(&rows, 3), None);
+
+        // Scrolling down keeps the selection on the last row of the page
+        assert_eq!(scroll_offset(&rows, 0, 2, 2), 1);
+        // Within the page nothing moves
+        assert_eq!(scroll_offset(&rows, 1, 2, 2), 1);
+        // Scrolling up to a group's first session also shows its header
+        assert_eq!(scroll_offset(&rows, 4, 1, 3), 0);
+        assert_eq!(scroll_offset(&rows, 5, 5, 3), 3);
+    }
+
+    #[test]
+    fn test_paging_keys_clamp_to_list() {
+        let dashboard_state = Arc::new(AppState::new(AppConfig::default()));
+        *dashboard_state.view_mode.lock().unwrap() = crate::app::state::ViewMode::ConversationList;
+        *dashboard_state.all_conversations.lock().unwrap() = (0..25)
+            .map(|i| crate::data::database::ConversationSummary::test(&i.to_string(), &format!("/work/{}", i), 0))
+            .collect();
+        let mut dashboard = Dashboard::new(dashboard_state.clone());
+        let selected = || *dashboard_state.selected_conversation_index.lock().unwrap();
+
+        dashboard.handle_key(KeyCode::PageDown);
+        assert_eq!(selected(), DEFAULT_PAGE_SIZE);
+        dashboard.handle_key(KeyCode::End);
+        assert_eq!(selected(), 24);
+        dashboard.handle_key(KeyCode::PageDown);
+        assert_eq!(selected(), 24);
+        dashboard.handle_key(KeyCode::PageUp);
+        assert_eq!(selected(), 24 - DEFAULT_PAGE_SIZE);
+        dashboard.handle_key(KeyCode::Home);
+        assert_eq!(selected(), 0);
+        dashboard.handle_key(KeyCode::Up);
+        assert_eq!(selected(), 0);
+    }
+
+    #[test]
+    fn test_source_picker_selects_detected_source() {
+        let mut dashboard = Dashboard::new(Arc::new(AppState::new(AppConfig::default())));
+        let current = dashboard.state.get_active_data_source();
+        let options = DataSourceType::all()
+            .into_iter()
+            .map(|source| (source, source != DataSourceType::ClaudeCode))
+            .collect();
+        dashboard.source_picker = Some(SourcePicker::new(options, current));
+        let select = |dashboard: &mut Dashboard, source: DataSourceType| {
+            for _ in 0..3 {
+                dashboard.handle_key(KeyCode::Up);
+            }
+            let idx = DataSourceType::all().iter().position(|s| *s == source).unwrap();
+            for _ in 0..idx {
+                dashboard.handle_key(KeyCode::Down);
+            }
+            dashboard.handle_key(KeyCode::Enter);
+        };
+
+        // A source that wasn't found can't be chosen
+        select(&mut dashboard, DataSourceType::ClaudeCode);
+        assert!(dashboard.source_picker.is_some());
+        assert_eq!(dashboard.requested_source(), None);
+
+        select(&mut dashboard, DataSourceType::Merged);
+        assert!(dashboard.source_picker.is_none());
+        assert_eq!(dashboard.requested_source(), Some(DataSourceType::Merged));
+
+        dashboard.reset_switching_flag();
+        assert_eq!(dashboard.requested_source(), None);
+    }
+
+    #[test]
+    fn test_jump_to_current_directory() {
+        let cwd = std::env::current_dir().unwrap().to_string_lossy().to_string();
+        let conversations = |paths: &[&str]| -> Vec<crate::data::database::ConversationSummary> {
+            paths.iter()
+                .map(|path| crate::data::database::ConversationSummary::test(path, path, 0))
+                .collect()
+        };
+
+        let mut dashboard = test_dashboard();
+        *dashboard.state.view_mode.lock().unwrap() = crate::app::state::ViewMode::ConversationList;
+        *dashboard.state.all_conversations.lock().unwrap() = conversations(&["/work/api", "/work/web", &cwd, "/home/notes"]);
+
+        assert!(dashboard.handle_key(KeyCode::Char('.')));
+        assert_eq!(*dashboard.state.selected_conversation_index.lock().unwrap(), 2);
+        assert!(dashboard.active_flash().is_none());
+
+        // No match keeps the selection and says why
+        *dashboard.state.all_conversations.lock().unwrap() = conversations(&["/work/api", "/work/web"]);
+        *dashboard.state.selected_conversation_index.lock().unwrap() = 1;
+        assert!(dashboard.handle_key(KeyCode::Char('.')));
+        assert_eq!(*dashboard.state.selected_conversation_index.lock().unwrap(), 1);
+        assert_eq!(dashboard.active_flash(), Some("No session for current directory"));
+    }
+
+    #[test]
+    fn test_search_filters_conversation_list() {
+        let dashboard_state = Arc::new(AppState::new(AppConfig::default()));
+        *dashboard_state.view_mode.lock().unwrap() = crate::app::state::ViewMode::ConversationList;
+        *dashboard_state.all_conversations.lock().unwrap() = ["/work/api", "/work/web", "/home/notes"]
+            .iter()
+            .map(|path| crate::data::database::ConversationSummary::test(path, path, 0))
+            .collect();
+        let mut dashboard = Dashboard::new(dashboard_state.clone());
+
+        dashboard.handle_key(KeyCode::Char('/'));
+        for c in "WORK".chars() {
+            dashboard.handle_key(KeyCode::Char(c));
+        }
+        assert_eq!(dashboard_state.visible_conversations().len(), 2);
+
+        // Typing 'q' extends the query instead of quitting
+        assert!(dashboard.handle_key(KeyCode::Char('q')));
+        assert!(dashboard_state.visible_conversations().is_empty());
+        dashboard.handle_key(KeyCode::Backspace);
+
+        dashboard.handle_key(KeyCode::Down);
+        assert_eq!(*dashboard_state.selected_conversation_index.lock().unwrap(), 1);
+        dashboard.handle_key(KeyCode::Down);
+        assert_eq!(*dashboard_state.selected_conversation_index.lock().unwrap(), 1);
+
+        dashboard.handle_key(KeyCode::Esc);
+        assert!(dashboard_state.search_query.lock().unwrap().is_none());
+        assert_eq!(dashboard_state.visible_conversations().len(), 3);
+    }
+
+    #[test]
+    fn test_block_reset_label() {
+        use chrono::TimeZone;
+        let end = chrono::Utc.with_ymd_and_hms(2024, 6, 1, 15, 0, 0).unwrap();
+        let before = |minutes| end - chrono::Duration::minutes(minutes);
+
+        assert_eq!(block_reset_label(end, before(107)), Some(("Block resets in 1h47m".to_string(), false)));
+        assert_eq!(block_reset_label(end, before(10)), Some(("Block resets in 10m".to_string(), false)));
+        assert_eq!(block_reset_label(end, before(9)), Some(("Block resets in 9m".to_string(), true)));
+        assert_eq!(block_reset_label(end, end), None);
+    }
+
+    #[test]
+    fn test_stale_label() {
+        use chrono::TimeZone;
+        let refreshed = chrono::Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let at = |secs| refreshed + chrono::Duration::seconds(secs);
+
+        assert_eq!(stale_label(refreshed, at(299), 300), None);
+        assert_eq!(stale_label(refreshed, at(300), 300).as_deref(), Some("stale 5m"));
+        assert_eq!(stale_label(refreshed, at(4 * 3600 + 125), 300).as_deref(), Some("stale 4h02m"));
+        // 0 turns the indicator off
+        assert_eq!(stale_label(refreshed, at(86_400), 0), None);
+    }
+
+    #[test]
+    fn test_unreadable_conversations_are_reported() {
+        assert_eq!(unreadable_label(0), None);
+        assert_eq!(unreadable_label(1).unwrap(), "(1 unreadable conversation)");
+
+        let dashboard = test_dashboard();
+        let stats = crate::data::database::GlobalStats { parse_failures: 3, ..empty_stats() };
+        *dashboard.state.global_stats.lock().unwrap() = Some(stats);
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|f| dashboard.render_global_stats(f, f.size())).unwrap();
+        let rendered: String = terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect();
+        assert!(rendered.contains("Total Conversations: 0 (3 unreadable conversations)"));
+    }
+
+    #[test]
+    fn test_time_to_threshold() {
+        // 14,000 tokens left at 1,000 tokens/min
+        assert_eq!(time_to_threshold(143_500, 157_500.0, 1_000.0), Some(chrono::Duration::minutes(14)));
+        // Partial minutes round up, and tiny gaps still show a minute
+        assert_eq!(time_to_threshold(0, 1_500.0, 1_000.0), Some(chrono::Duration::minutes(2)));
+        assert_eq!(time_to_threshold(999, 1_000.0, 1_000.0), Some(chrono::Duration::minutes(1)));
+        // Hidden with no burn, past the threshold, or more than a day out
+        assert_eq!(time_to_threshold(100, 1_000.0, 0.0), None);
+        assert_eq!(time_to_threshold(2_000, 1_000.0, 500.0), None);
+        assert_eq!(time_to_threshold(0, 2_000_000.0, 1.0), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::minutes(45)), "45m");
+        assert_eq!(format_duration(chrono::Duration::minutes(192)), "3h12m");
+    }
+
+    #[test]
+    fn test_format_token_count() {
+        assert_eq!(format_token_count(950), "950");
+        assert_eq!(format_token_count(420_500), "420K");
+        assert_eq!(format_token_count(1_250_000), "1.2M");
+    }
+}
@@ -1,3 +1,5 @@
 pub mod dashboard;
+pub mod terminal;
+pub mod theme;
 
 pub use dashboard::Dashboard;
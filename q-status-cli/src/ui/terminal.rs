@@ -0,0 +1,120 @@
+// ABOUTME: Terminal teardown that runs however the dashboard exits
+// Restores cooked mode and the main screen on errors, panics and signals
+
+use crossterm::{
+    cursor::Show,
+    event::DisableMouseCapture,
+    execute,
+    terminal::{disable_raw_mode, LeaveAlternateScreen},
+};
+use std::io;
+
+/// Put the terminal back the way the shell expects it
+///
+/// Works on stdout directly so it can run from a panic hook or signal task
+/// that has no access to the `Terminal`. Safe to call more than once.
+pub fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show)
+}
+
+/// Runs a teardown function when dropped, including during unwinding
+pub struct RestoreGuard<F: FnMut()> {
+    restore: Option<F>,
+}
+
+impl<F: FnMut()> RestoreGuard<F> {
+    pub fn new(restore: F) -> Self {
+        Self { restore: Some(restore) }
+    }
+}
+
+impl<F: FnMut()> Drop for RestoreGuard<F> {
+    fn drop(&mut self) {
+        if let Some(mut restore) = self.restore.take() {
+            restore();
+        }
+    }
+}
+
+/// Guard that restores the real terminal when the event loop is left
+pub fn terminal_guard() -> RestoreGuard<impl FnMut()> {
+    RestoreGuard::new(|| {
+        let _ = restore_terminal();
+    })
+}
+
+/// Restore the terminal before the default panic message is printed,
+/// so the message is readable and the shell is usable afterwards
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
+}
+
+/// Restore the terminal and exit when the process is asked to stop
+///
+/// In raw mode Ctrl+C arrives as a key press, so this mainly covers
+/// SIGTERM/SIGHUP from `kill` or a closing terminal window.
+pub fn spawn_signal_handler() -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async {
+        let code = wait_for_signal().await;
+        let _ = restore_terminal();
+        std::process::exit(code);
+    })
+}
+
+/// Wait for a termination signal and return the conventional exit code (128 + signal)
+#[cfg(unix)]
+async fn wait_for_signal() -> i32 {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (Ok(mut term), Ok(mut hangup)) = (signal(SignalKind::terminate()), signal(SignalKind::hangup())) else {
+        let _ = tokio::signal::ctrl_c().await;
+        return 130;
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => 130,
+        _ = term.recv() => 143,
+        _ = hangup.recv() => 129,
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> i32 {
+    let _ = tokio::signal::ctrl_c().await;
+    130
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn failing_event_loop(restored: &Cell<u32>) -> Result<(), String> {
+        let _guard = RestoreGuard::new(|| restored.set(restored.get() + 1));
+        Err("draw failed".to_string())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_guard_restores_on_error_path() {
+        let restored = Cell::new(0);
+        assert!(failing_event_loop(&restored).is_err());
+        assert_eq!(restored.get(), 1);
+    }
+
+    #[test]
+    fn test_guard_restores_during_panic() {
+        let restored = Cell::new(0);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = RestoreGuard::new(|| restored.set(restored.get() + 1));
+            panic!("render panicked");
+        }));
+        assert!(result.is_err());
+        assert_eq!(restored.get(), 1);
+    }
+}
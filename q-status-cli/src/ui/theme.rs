@@ -0,0 +1,87 @@
+// ABOUTME: Foreground palettes for dark and light terminal backgrounds
+// Dashboard styles are written for dark terminals; the light palette remaps them when drawn
+
+use crate::app::config::Theme;
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+/// Colors the dashboard draws with, resolved from the configured theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    Dark,
+    Light,
+}
+
+impl Palette {
+    /// Resolve a theme; `Auto` reads the terminal background from `COLORFGBG`
+    pub fn for_theme(theme: &Theme) -> Self {
+        match theme {
+            Theme::Dark => Self::Dark,
+            Theme::Light => Self::Light,
+            Theme::Auto => std::env::var("COLORFGBG")
+                .ok()
+                .and_then(|value| palette_from_colorfgbg(&value))
+                .unwrap_or(Self::Dark),
+        }
+    }
+
+    /// The foreground color to draw in place of a dark-theme one
+    pub fn color(&self, color: Color) -> Color {
+        match self {
+            Self::Dark => color,
+            // Bright colors wash out on white; use darker shades of the same hue
+            Self::Light => match color {
+                Color::Yellow => Color::Rgb(150, 110, 0),
+                Color::Cyan => Color::Rgb(0, 120, 140),
+                Color::Green => Color::Rgb(0, 130, 0),
+                Color::Red => Color::Rgb(190, 0, 0),
+                Color::Blue => Color::Rgb(0, 70, 180),
+                Color::Magenta => Color::Rgb(150, 0, 150),
+                Color::Gray => Color::DarkGray,
+                Color::White => Color::Black,
+                other => other,
+            },
+        }
+    }
+
+    /// Remap the foreground of every drawn cell
+    pub fn apply(&self, buffer: &mut Buffer) {
+        if *self == Self::Dark {
+            return;
+        }
+        for cell in buffer.content.iter_mut() {
+            let fg = self.color(cell.fg);
+            cell.set_fg(fg);
+        }
+    }
+}
+
+/// `COLORFGBG` is "fg;bg" (sometimes "fg;default;bg") in ANSI color numbers;
+/// 7 and 15 are white backgrounds
+fn palette_from_colorfgbg(value: &str) -> Option<Palette> {
+    let background: u8 = value.rsplit(';').next()?.trim().parse().ok()?;
+    Some(if matches!(background, 7 | 15) { Palette::Light } else { Palette::Dark })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_and_dark_differ() {
+        for color in [Color::Yellow, Color::Cyan, Color::Green, Color::Gray, Color::White] {
+            assert_eq!(Palette::Dark.color(color), color);
+            assert_ne!(Palette::Light.color(color), color);
+        }
+        assert_eq!(Palette::Light.color(Color::Reset), Color::Reset);
+        assert_eq!(Palette::for_theme(&Theme::Light), Palette::Light);
+    }
+
+    #[test]
+    fn test_background_from_colorfgbg() {
+        assert_eq!(palette_from_colorfgbg("0;15"), Some(Palette::Light));
+        assert_eq!(palette_from_colorfgbg("15;0"), Some(Palette::Dark));
+        assert_eq!(palette_from_colorfgbg("0;default;7"), Some(Palette::Light));
+        assert_eq!(palette_from_colorfgbg("garbage"), None);
+    }
+}
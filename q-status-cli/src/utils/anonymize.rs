@@ -0,0 +1,135 @@
+// ABOUTME: Anonymization of export data for --anonymize
+// Swaps paths and conversation ids for stable hashed labels before serialization
+
+use crate::utils::html_report::ReportData;
+use std::collections::BTreeMap;
+
+/// Hex digits in a path label; more are used only to break a collision
+const PATH_LABEL_DIGITS: usize = 4;
+
+/// Hex digits in an anonymized conversation id
+const ID_LABEL_DIGITS: usize = 8;
+
+/// Replaces paths and conversation ids with short labels, remembering the originals
+///
+/// Labels come from a fixed hash, so the same path gets the same label in every
+/// export and shared reports can be compared with each other.
+#[derive(Debug, Default)]
+pub struct Anonymizer {
+    paths: BTreeMap<String, String>,
+    ids: BTreeMap<String, String>,
+}
+
+impl Anonymizer {
+    /// `project-a1b2` for a directory or file path
+    pub fn path_label(&mut self, path: &str) -> String {
+        Self::label(&mut self.paths, path, "project-", PATH_LABEL_DIGITS)
+    }
+
+    /// Short hash standing in for a conversation id
+    pub fn id_label(&mut self, id: &str) -> String {
+        Self::label(&mut self.ids, id, "", ID_LABEL_DIGITS)
+    }
+
+    fn label(labels: &mut BTreeMap<String, String>, original: &str, prefix: &str, digits: usize) -> String {
+        if let Some(label) = labels.get(original) {
+            return label.clone();
+        }
+        let hash = format!("{:016x}", stable_hash(original));
+        // Lengthen the label rather than give two originals the same one
+        let label = (digits..=hash.len())
+            .map(|len| format!("{}{}", prefix, &hash[..len]))
+            .find(|label| !labels.values().any(|used| used == label))
+            .unwrap_or_else(|| format!("{}{}-{}", prefix, hash, labels.len()));
+        labels.insert(original.to_string(), label.clone());
+        label
+    }
+
+    /// Replace every path and conversation id in the report, keeping all numbers
+    pub fn anonymize_report(&mut self, data: &mut ReportData) {
+        for session in &mut data.sessions {
+            session.directory = self.path_label(&session.directory);
+            session.conversation_id = self.id_label(&session.conversation_id);
+            session.context_files = session.context_files.iter().map(|file| self.path_label(file)).collect();
+        }
+        if let Some(largest) = data.stats.largest_conversation.as_mut() {
+            largest.path = self.path_label(&largest.path);
+            largest.conversation_id = self.id_label(&largest.conversation_id);
+        }
+    }
+
+    /// One `label  original` line per replacement, paths first
+    pub fn mapping(&self) -> String {
+        self.paths
+            .iter()
+            .chain(&self.ids)
+            .map(|(original, label)| format!("{}  {}\n", label, original))
+            .collect()
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is the same across builds
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::ExportFormat;
+    use crate::data::database::{GlobalStats, Session};
+    use crate::utils::report::render_report;
+    use chrono::Local;
+
+    fn session(directory: &str, id: &str, tokens: u64, cost: f64) -> Session {
+        Session {
+            conversation_id: id.to_string(),
+            context_files: vec![format!("{}/README.md", directory)],
+            ..Session::test(directory, tokens, cost)
+        }
+    }
+
+    #[test]
+    fn test_anonymized_exports_hide_paths_and_ids() {
+        let sessions = vec![
+            session("/Users/alice/work/secret-api", "5f0c2a9e-1111-4a7b-9d3e-0123456789ab", 40_000, 10.0),
+            session("/Users/alice/work/web", "conv-web-original", 8_000, 2.5),
+        ];
+        let data = ReportData {
+            source: "Amazon Q".to_string(),
+            generated_at: Local::now(),
+            stats: GlobalStats {
+                total_conversations: 2,
+                total_tokens: 48_000,
+                total_cost_estimate: 12.5,
+                ..GlobalStats::default()
+            },
+            sessions,
+            cost_decimals: None,
+            display_currency: "USD".to_string(),
+            usd_exchange_rate: 1.0,
+        };
+
+        let mut anonymizer = Anonymizer::default();
+        let mut anonymized = data.clone();
+        anonymizer.anonymize_report(&mut anonymized);
+
+        for format in [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Markdown, ExportFormat::Html] {
+            let output = render_report(&anonymized, &format);
+            assert!(!output.contains("/Users/alice"), "{:?} leaked a path", format);
+            assert!(!output.contains("secret-api"), "{:?} leaked a path", format);
+            assert!(!output.contains("conv-web-original"), "{:?} leaked an id", format);
+            assert!(output.contains("40000"), "{:?} lost token counts", format);
+        }
+
+        // Labels are stable, and the mapping leads back to the originals
+        let api_label = anonymized.sessions[0].directory.clone();
+        assert!(api_label.starts_with("project-") && api_label.len() == "project-".len() + 4);
+        assert_eq!(Anonymizer::default().path_label("/Users/alice/work/secret-api"), api_label);
+        assert_eq!(anonymized.sessions[1].conversation_id.len(), 8);
+        assert_eq!(anonymized.sessions[0].session_cost, 10.0);
+        assert!(anonymizer.mapping().contains(&format!("{}  /Users/alice/work/secret-api\n", api_label)));
+    }
+}
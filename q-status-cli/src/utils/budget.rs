@@ -0,0 +1,89 @@
+// ABOUTME: Monthly cost budget tracking with an end-of-month projection
+// Extrapolates month-to-date spend at the current daily burn
+
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+
+/// Spend against a monthly budget, all in USD
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetStatus {
+    pub spent: f64,
+    pub budget: f64,
+    /// Spend by month end if the average daily burn so far continues
+    pub projected: f64,
+}
+
+impl BudgetStatus {
+    pub fn new(spent: f64, budget: f64, today: NaiveDate) -> Self {
+        Self {
+            spent,
+            budget,
+            projected: project_month_end(spent, today),
+        }
+    }
+
+    /// Share of the budget spent (1.0 = all of it)
+    pub fn fraction(&self) -> f64 {
+        if self.budget > 0.0 {
+            self.spent / self.budget
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Month cost / days elapsed × days in month, counting today as elapsed
+pub fn project_month_end(spent: f64, today: NaiveDate) -> f64 {
+    let days_elapsed = today.day() as f64;
+    spent / days_elapsed * days_in_month(today) as f64
+}
+
+/// Cap for the longest key contained in `directory`, so `repo/api` beats `repo`
+pub fn directory_cap(caps: &BTreeMap<String, f64>, directory: &str) -> Option<f64> {
+    caps.iter()
+        .filter(|(key, _)| !key.is_empty() && directory.contains(key.as_str()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, cap)| *cap)
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projection_over_partial_month() {
+        // $30 in the first 10 days of a 30-day month
+        let status = BudgetStatus::new(30.0, 100.0, NaiveDate::from_ymd_opt(2024, 6, 10).unwrap());
+        assert!((status.projected - 90.0).abs() < 1e-9);
+        assert!((status.fraction() - 0.3).abs() < 1e-9);
+
+        // February in a leap year has 29 days
+        let feb = project_month_end(14.5, NaiveDate::from_ymd_opt(2024, 2, 5).unwrap());
+        assert!((feb - 84.1).abs() < 1e-9);
+
+        // On the last day the projection is what was spent
+        assert_eq!(project_month_end(42.0, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()), 42.0);
+    }
+
+    #[test]
+    fn test_directory_cap_prefers_most_specific_key() {
+        let caps = BTreeMap::from([
+            ("acme".to_string(), 100.0),
+            ("acme/billing".to_string(), 20.0),
+        ]);
+        assert_eq!(directory_cap(&caps, "/home/dev/acme/web"), Some(100.0));
+        assert_eq!(directory_cap(&caps, "/home/dev/acme/billing-api"), Some(20.0));
+        assert_eq!(directory_cap(&caps, "/home/dev/other"), None);
+    }
+}
@@ -0,0 +1,18 @@
+// ABOUTME: System clipboard access for copying ids out of the dashboard
+// Wraps arboard; callers show the text themselves where no clipboard is available
+
+/// Copy text to the system clipboard
+///
+/// Returns false when there is no clipboard (headless, SSH without a display).
+/// Nothing is printed, since stderr is hidden behind the dashboard.
+pub fn copy_text(text: &str) -> bool {
+    let result = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!("Clipboard unavailable: {}", e);
+            false
+        }
+    }
+}
@@ -1,16 +1,23 @@
 // ABOUTME: cost_calculator provides sophisticated cost calculation logic from ccusage
 // This module calculates costs for Claude API usage with support for cache tokens and multiple pricing modes
 
+use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Timeout for fetching remote pricing data
+const PRICING_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Cost calculation modes matching ccusage behavior
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CostMode {
     /// Use pre-calculated costs when available, otherwise calculate from tokens
+    #[default]
     Auto,
     /// Always calculate costs from token counts using model pricing
     Calculate,
@@ -18,9 +25,15 @@ pub enum CostMode {
     Display,
 }
 
-impl Default for CostMode {
-    fn default() -> Self {
-        CostMode::Auto
+impl CostMode {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "calculate" => Some(Self::Calculate),
+            "display" => Some(Self::Display),
+            _ => None,
+        }
     }
 }
 
@@ -97,6 +110,20 @@ pub struct CostCalculator {
     pricing_data: HashMap<String, ModelPricing>,
     /// Default model for fallback
     default_model: String,
+    /// Flat price used instead of the default model's when a model is unknown
+    fallback_pricing: Option<ModelPricing>,
+}
+
+impl CostCalculator {
+    /// Prices to use under `config`: the cached remote table when a pricing
+    /// URL is set (it is fetched at startup), otherwise the built-in one
+    pub fn for_config(config: &crate::app::config::AppConfig) -> Self {
+        if config.pricing_url.is_some() {
+            Self::load_cached().unwrap_or_default()
+        } else {
+            Self::new()
+        }
+    }
 }
 
 impl Default for CostCalculator {
@@ -110,6 +137,26 @@ impl CostCalculator {
     pub fn new() -> Self {
         let mut pricing_data = HashMap::new();
 
+        // Claude Sonnet 4 variants
+        pricing_data.insert(
+            "claude-sonnet-4".to_string(),
+            ModelPricing::from_million_tokens(3.0, 15.0, None, None, Some(200_000))
+        );
+        pricing_data.insert(
+            "claude-sonnet-4-20250514".to_string(),
+            ModelPricing::from_million_tokens(3.0, 15.0, None, None, Some(200_000))
+        );
+
+        // Claude Opus 4 variants
+        pricing_data.insert(
+            "claude-opus-4".to_string(),
+            ModelPricing::from_million_tokens(15.0, 75.0, None, None, Some(200_000))
+        );
+        pricing_data.insert(
+            "claude-opus-4-20250514".to_string(),
+            ModelPricing::from_million_tokens(15.0, 75.0, None, None, Some(200_000))
+        );
+
         // Claude 3.5 Sonnet variants
         pricing_data.insert(
             "claude-3-5-sonnet".to_string(),
@@ -155,11 +202,15 @@ impl CostCalculator {
         // Claude 3.5 Haiku (newer, different pricing)
         pricing_data.insert(
             "claude-3-5-haiku".to_string(),
-            ModelPricing::from_million_tokens(1.0, 5.0, None, None, Some(200_000))
+            ModelPricing::from_million_tokens(0.8, 4.0, None, None, Some(200_000))
         );
         pricing_data.insert(
             "claude-3-5-haiku-20241022".to_string(),
-            ModelPricing::from_million_tokens(1.0, 5.0, None, None, Some(200_000))
+            ModelPricing::from_million_tokens(0.8, 4.0, None, None, Some(200_000))
+        );
+        pricing_data.insert(
+            "claude-3-5-haiku-latest".to_string(),
+            ModelPricing::from_million_tokens(0.8, 4.0, None, None, Some(200_000))
         );
 
         // Legacy models
@@ -179,6 +230,7 @@ impl CostCalculator {
         Self {
             pricing_data,
             default_model: "claude-3-5-sonnet-20241022".to_string(),
+            fallback_pricing: None,
         }
     }
 
@@ -190,9 +242,107 @@ impl CostCalculator {
         Ok(Self {
             pricing_data,
             default_model: "claude-3-5-sonnet-20241022".to_string(),
+            fallback_pricing: None,
         })
     }
 
+    /// Fetch LiteLLM-style pricing from a URL and merge it over the built-in defaults
+    ///
+    /// The fetched JSON is cached in the project cache dir so later offline
+    /// runs can use it via [`CostCalculator::load_cached`].
+    pub async fn load_from_url(url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_from_url_with_cache(url, Self::pricing_cache_path().as_deref()).await
+    }
+
+    /// Fetch pricing from a URL, writing the raw JSON to `cache_path` on success
+    pub async fn load_from_url_with_cache(
+        url: &str,
+        cache_path: Option<&Path>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = if let Some(path) = url.strip_prefix("file://") {
+            fs::read_to_string(path)?
+        } else {
+            reqwest::Client::builder()
+                .timeout(PRICING_FETCH_TIMEOUT)
+                .build()?
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?
+        };
+
+        let mut calculator = Self::new();
+        calculator.merge_pricing(Self::parse_litellm_pricing(&content)?);
+
+        // Caching is best-effort; a read-only cache dir shouldn't lose fresh prices
+        if let Some(cache_path) = cache_path {
+            if let Some(parent) = cache_path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let _ = fs::write(cache_path, &content);
+        }
+
+        Ok(calculator)
+    }
+
+    /// Load pricing previously cached by [`CostCalculator::load_from_url`]
+    pub fn load_cached() -> Option<Self> {
+        Self::load_cached_from(&Self::pricing_cache_path()?)
+    }
+
+    /// Load cached LiteLLM pricing from a specific file, merged over the defaults
+    pub fn load_cached_from(cache_path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(cache_path).ok()?;
+        let mut calculator = Self::new();
+        calculator.merge_pricing(Self::parse_litellm_pricing(&content).ok()?);
+        Some(calculator)
+    }
+
+    /// Location of the cached remote pricing JSON
+    pub fn pricing_cache_path() -> Option<PathBuf> {
+        ProjectDirs::from("com", "q-status", "q-status")
+            .map(|dirs| dirs.cache_dir().join("pricing.json"))
+    }
+
+    /// Parse LiteLLM's `model_prices_and_context_window.json` format
+    ///
+    /// Entries without per-token prices (such as `sample_spec`) are skipped.
+    fn parse_litellm_pricing(content: &str) -> Result<HashMap<String, ModelPricing>, serde_json::Error> {
+        let raw: HashMap<String, Value> = serde_json::from_str(content)?;
+
+        Ok(raw
+            .into_iter()
+            .filter_map(|(model, value)| {
+                let pricing: ModelPricing = serde_json::from_value(value).ok()?;
+                pricing.input_cost_per_token?;
+                Some((model.to_lowercase(), pricing))
+            })
+            .collect())
+    }
+
+    /// Price unknown models at a flat `cost_per_1k` for every token, rather
+    /// than at the default Sonnet rates
+    pub fn with_fallback_rate(mut self, cost_per_1k: f64) -> Self {
+        let per_token = cost_per_1k / 1000.0;
+        self.fallback_pricing = Some(ModelPricing {
+            input_cost_per_token: Some(per_token),
+            output_cost_per_token: Some(per_token),
+            cache_creation_cost_per_token: Some(per_token),
+            cache_read_cost_per_token: Some(per_token),
+            max_tokens: Some(200_000),
+            max_input_tokens: None,
+            max_output_tokens: None,
+        });
+        self
+    }
+
+    /// Merge pricing entries over the existing data, replacing matching models
+    pub fn merge_pricing(&mut self, pricing: HashMap<String, ModelPricing>) {
+        self.pricing_data.extend(pricing);
+    }
+
     /// Calculate cost based on tokens and model with specified mode
     pub fn calculate_cost(
         &self,
@@ -222,6 +372,23 @@ impl CostCalculator {
         }
     }
 
+    /// Money saved by cache reads: their cost at the full input price minus
+    /// their cost at the discounted cache-read price
+    pub fn cache_savings(&self, tokens: &TokenUsage, model: &str) -> f64 {
+        let pricing = self.get_pricing(model);
+        let cache_read_tokens = tokens.cache_read_input_tokens.unwrap_or(0) as f64;
+        let full_price = pricing.input_cost_per_token.unwrap_or(0.0);
+        let cache_price = pricing.cache_read_cost_per_token.unwrap_or(full_price);
+
+        cache_read_tokens * (full_price - cache_price)
+    }
+
+    /// Cost of just the output tokens at the model's output price
+    pub fn output_cost(&self, tokens: &TokenUsage, model: &str) -> f64 {
+        let pricing = self.get_pricing(model);
+        tokens.output_tokens as f64 * pricing.output_cost_per_token.unwrap_or(0.0)
+    }
+
     /// Calculate cost from token counts and model pricing
     fn calculate_from_tokens(&self, tokens: &TokenUsage, model: &str) -> f64 {
         let pricing = self.get_pricing(model);
@@ -274,6 +441,11 @@ impl CostCalculator {
             return pricing;
         }
 
+        if let Some(pricing) = &self.fallback_pricing {
+            tracing::debug!("No pricing for model '{}', using cost_per_1k_tokens", model);
+            return pricing;
+        }
+
         // Fallback to default model
         self.pricing_data.get(&self.default_model)
             .unwrap_or(&ModelPricing {
@@ -288,6 +460,10 @@ impl CostCalculator {
     }
 
     /// Normalize model name by removing provider prefixes and standardizing format
+    ///
+    /// Bedrock ids (`us.anthropic.claude-3-5-sonnet-20241022-v2:0`) lose their
+    /// region and vendor prefixes and version suffix; Vertex ids
+    /// (`claude-3-5-sonnet-v2@20241022`) have the date moved after a dash.
     fn normalize_model_name(&self, model: &str) -> String {
         let mut normalized = model.to_lowercase();
 
@@ -299,9 +475,36 @@ impl CostCalculator {
             }
         }
 
+        // Bedrock: optional cross-region inference prefix, then the vendor
+        for region in ["us.", "eu.", "apac."] {
+            if let Some(rest) = normalized.strip_prefix(region) {
+                if rest.starts_with("anthropic.") {
+                    normalized = rest.to_string();
+                }
+            }
+        }
+        if let Some(rest) = normalized.strip_prefix("anthropic.") {
+            normalized = rest.to_string();
+        }
+
+        // Legacy Bedrock ids carry the version in the suffix only
+        match normalized.as_str() {
+            "claude-v2:1" => return "claude-2.1".to_string(),
+            "claude-v2" | "claude-v2:0" => return "claude-2.0".to_string(),
+            "claude-instant-v1" | "claude-instant-v1:2" => return "claude-instant-1.2".to_string(),
+            _ => {}
+        }
+
+        // Vertex puts the release date after '@'
+        if let Some((name, date)) = normalized.split_once('@') {
+            normalized = format!("{}-{}", strip_model_version_suffix(name), date);
+        }
+        normalized = strip_model_version_suffix(&normalized).to_string();
+
         // Handle special cases
         normalized = normalized
             .replace("claude-3.5-", "claude-3-5-")
+            .replace("claude-3.7-", "claude-3-7-")
             .replace("claude3.5", "claude-3-5")
             .replace("claude3-", "claude-3-");
 
@@ -310,15 +513,16 @@ impl CostCalculator {
 
     /// Fuzzy match model name to find best pricing match
     fn fuzzy_match_model(&self, model: &str) -> Option<&ModelPricing> {
-        // Check for model family matches
-        if model.contains("opus") {
+        // Check for model family matches (current generation first)
+        if model.contains("opus-4") {
+            self.pricing_data.get("claude-opus-4")
+        } else if model.contains("sonnet-4") {
+            self.pricing_data.get("claude-sonnet-4")
+        } else if model.contains("opus") {
             self.pricing_data.get("claude-3-opus")
         } else if model.contains("sonnet") {
-            if model.contains("3-5") || model.contains("3.5") {
-                self.pricing_data.get("claude-3-5-sonnet")
-            } else {
-                self.pricing_data.get("claude-3-5-sonnet") // Default to 3.5
-            }
+            // All Sonnet variants default to 3.5 pricing
+            self.pricing_data.get("claude-3-5-sonnet")
         } else if model.contains("haiku") {
             if model.contains("3-5") || model.contains("3.5") {
                 self.pricing_data.get("claude-3-5-haiku")
@@ -348,11 +552,51 @@ impl CostCalculator {
 
     /// Format cost as USD string
     pub fn format_cost(cost: f64) -> String {
-        if cost < 0.01 {
-            format!("${:.4}", cost)
-        } else {
-            format!("${:.2}", cost)
-        }
+        format_cost_in(cost, "USD", 1.0, None)
+    }
+}
+
+/// Drop a Bedrock/Vertex model version suffix such as `-v2:0` or `-v1`
+fn strip_model_version_suffix(model: &str) -> &str {
+    let Some(index) = model.rfind("-v") else {
+        return model;
+    };
+    let version = &model[index + 2..];
+    let is_version = version.starts_with(|c: char| c.is_ascii_digit())
+        && version.chars().all(|c| c.is_ascii_digit() || c == ':');
+    if is_version {
+        &model[..index]
+    } else {
+        model
+    }
+}
+
+/// Symbol shown before amounts in a currency, by ISO 4217 code
+fn currency_symbol(currency: &str) -> Option<&'static str> {
+    match currency.to_ascii_uppercase().as_str() {
+        "USD" => Some("$"),
+        "EUR" => Some("€"),
+        "GBP" => Some("£"),
+        "JPY" | "CNY" => Some("¥"),
+        "INR" => Some("₹"),
+        "KRW" => Some("₩"),
+        _ => None,
+    }
+}
+
+/// Format a USD cost for display in another currency
+///
+/// `rate` is units of `currency` per US dollar. Costs are only converted here;
+/// everything upstream stays in USD. Sub-cent amounts keep four decimals.
+///
+/// `decimals` fixes the precision; without it sub-cent amounts get 4 places
+/// and everything else 2.
+pub fn format_cost_in(cost_usd: f64, currency: &str, rate: f64, decimals: Option<usize>) -> String {
+    let amount = cost_usd * rate;
+    let decimals = decimals.unwrap_or(if amount != 0.0 && amount.abs() < 0.01 { 4 } else { 2 });
+    match currency_symbol(currency) {
+        Some(symbol) => format!("{}{:.*}", symbol, decimals, amount),
+        None => format!("{} {:.*}", currency.to_ascii_uppercase(), decimals, amount),
     }
 }
 
@@ -424,6 +668,47 @@ mod tests {
         assert!((cost - 0.00875).abs() < 0.000001);
     }
 
+    #[test]
+    fn test_cost_calculation_current_generation() {
+        let calculator = CostCalculator::new();
+        let tokens = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_input_tokens: Some(100),
+            cache_read_input_tokens: Some(200),
+        };
+
+        // Sonnet 4: (1000 * 3/1M) + (500 * 15/1M) + (100 * 3.75/1M) + (200 * 0.3/1M) = 0.010935
+        let sonnet = calculator.calculate_cost(&tokens, "claude-sonnet-4-20250514", CostMode::Calculate, None);
+        assert!((sonnet - 0.010935).abs() < 0.000001);
+
+        // Opus 4: (1000 * 15/1M) + (500 * 75/1M) + (100 * 18.75/1M) + (200 * 1.5/1M) = 0.054675
+        let opus = calculator.calculate_cost(&tokens, "claude-opus-4-20250514", CostMode::Calculate, None);
+        assert!((opus - 0.054675).abs() < 0.000001);
+
+        // Haiku 3.5: (1000 * 0.8/1M) + (500 * 4/1M) + (100 * 1/1M) + (200 * 0.08/1M) = 0.002916
+        let haiku = calculator.calculate_cost(&tokens, "claude-3-5-haiku-latest", CostMode::Calculate, None);
+        assert!((haiku - 0.002916).abs() < 0.000001);
+    }
+
+    #[test]
+    fn test_fuzzy_match_current_generation() {
+        let calculator = CostCalculator::new();
+        let tokens = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 0,
+            cache_creation_input_tokens: None,
+            cache_read_input_tokens: None,
+        };
+
+        // Unlisted Opus 4.x releases use Opus 4 pricing rather than falling back to Sonnet
+        let opus = calculator.calculate_cost(&tokens, "claude-opus-4-1-20250805", CostMode::Calculate, None);
+        assert!((opus - 15.0).abs() < 0.000001);
+
+        let sonnet = calculator.calculate_cost(&tokens, "anthropic/claude-sonnet-4-5", CostMode::Calculate, None);
+        assert!((sonnet - 3.0).abs() < 0.000001);
+    }
+
     #[test]
     fn test_cost_mode_display() {
         let calculator = CostCalculator::new();
@@ -510,6 +795,38 @@ mod tests {
         assert_eq!(cost1, cost2);
     }
 
+    #[test]
+    fn test_bedrock_and_vertex_model_ids() {
+        let calculator = CostCalculator::new();
+        let cases = [
+            ("anthropic.claude-3-5-sonnet-20241022-v2:0", "claude-3-5-sonnet-20241022"),
+            ("us.anthropic.claude-3-5-haiku-20241022-v1:0", "claude-3-5-haiku-20241022"),
+            ("bedrock/anthropic.claude-3-opus-20240229-v1:0", "claude-3-opus-20240229"),
+            ("anthropic.claude-sonnet-4-20250514-v1:0", "claude-sonnet-4-20250514"),
+            ("anthropic.claude-v2:1", "claude-2.1"),
+            ("anthropic.claude-instant-v1", "claude-instant-1.2"),
+            ("claude-3-5-sonnet-v2@20241022", "claude-3-5-sonnet-20241022"),
+            ("vertex/claude-3-haiku@20240307", "claude-3-haiku-20240307"),
+            ("claude-opus-4@20250514", "claude-opus-4-20250514"),
+        ];
+        for (id, expected) in cases {
+            assert_eq!(calculator.normalize_model_name(id), expected, "{}", id);
+        }
+
+        // Resolves to real pricing rather than the Sonnet fallback
+        let tokens = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_input_tokens: None,
+            cache_read_input_tokens: None,
+        };
+        let bedrock = calculator.calculate_cost(&tokens, "us.anthropic.claude-3-haiku-20240307-v1:0", CostMode::Calculate, None);
+        let direct = calculator.calculate_cost(&tokens, "claude-3-haiku-20240307", CostMode::Calculate, None);
+        let fallback = calculator.calculate_cost(&tokens, "unknown-model-xyz", CostMode::Calculate, None);
+        assert_eq!(bedrock, direct);
+        assert_ne!(bedrock, fallback);
+    }
+
     #[test]
     fn test_unknown_model_fallback() {
         let calculator = CostCalculator::new();
@@ -531,6 +848,26 @@ mod tests {
         assert!((cost - 0.0105).abs() < 0.000001);
     }
 
+    #[test]
+    fn test_unknown_model_uses_configured_fallback_rate() {
+        let calculator = CostCalculator::new().with_fallback_rate(0.01);
+        let tokens = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_input_tokens: None,
+            cache_read_input_tokens: Some(500),
+        };
+
+        // 2000 tokens at $0.01 per 1K
+        let cost = calculator.calculate_cost(&tokens, "bogus-model-9000", CostMode::Calculate, None);
+        assert!((cost - 0.02).abs() < 0.000001);
+
+        // Known models keep their own pricing
+        let sonnet = calculator.calculate_cost(&tokens, "claude-3-5-sonnet", CostMode::Calculate, None);
+        let default = CostCalculator::new().calculate_cost(&tokens, "claude-3-5-sonnet", CostMode::Calculate, None);
+        assert_eq!(sonnet, default);
+    }
+
     #[test]
     fn test_cache_token_costs() {
         let calculator = CostCalculator::new();
@@ -554,6 +891,57 @@ mod tests {
         assert!((cost - 0.00405).abs() < 0.000001);
     }
 
+    #[tokio::test]
+    async fn test_load_from_url_merges_and_caches() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pricing_path = temp_dir.path().join("prices.json");
+        let cache_path = temp_dir.path().join("cache").join("pricing.json");
+        fs::write(&pricing_path, r#"{
+            "sample_spec": {"max_tokens": "set to max tokens", "input_cost_per_token": "per token"},
+            "claude-3-5-sonnet-20241022": {"input_cost_per_token": 0.000004, "output_cost_per_token": 0.00002, "max_tokens": 8192, "litellm_provider": "anthropic"}
+        }"#).unwrap();
+
+        let url = format!("file://{}", pricing_path.display());
+        let calculator = CostCalculator::load_from_url_with_cache(&url, Some(&cache_path)).await.unwrap();
+        let tokens = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_creation_input_tokens: None,
+            cache_read_input_tokens: None,
+        };
+
+        // Remote price overrides the built-in, other defaults remain
+        let cost = calculator.calculate_cost(&tokens, "claude-3-5-sonnet-20241022", CostMode::Calculate, None);
+        assert!((cost - 0.014).abs() < 0.000001);
+        assert!(calculator.available_models().contains(&"claude-3-opus".to_string()));
+        assert!(!calculator.available_models().contains(&"sample_spec".to_string()));
+
+        // Offline: a failed fetch leaves the cache usable
+        let missing = format!("file://{}", temp_dir.path().join("missing.json").display());
+        assert!(CostCalculator::load_from_url_with_cache(&missing, Some(&cache_path)).await.is_err());
+        let cached = CostCalculator::load_cached_from(&cache_path).unwrap();
+        let cached_cost = cached.calculate_cost(&tokens, "claude-3-5-sonnet-20241022", CostMode::Calculate, None);
+        assert!((cached_cost - 0.014).abs() < 0.000001);
+    }
+
+    #[test]
+    fn test_cache_savings() {
+        let calculator = CostCalculator::new();
+        let tokens = TokenUsage {
+            input_tokens: 1_000,
+            output_tokens: 500,
+            cache_creation_input_tokens: Some(2_000),
+            cache_read_input_tokens: Some(1_000_000),
+        };
+
+        // Sonnet 3.5: $3/M input vs $0.30/M cache read, so 1M reads save $2.70
+        let savings = calculator.cache_savings(&tokens, "claude-3-5-sonnet-20241022");
+        assert!((savings - 2.70).abs() < 0.000001);
+
+        let no_reads = TokenUsage { cache_read_input_tokens: None, ..tokens };
+        assert_eq!(calculator.cache_savings(&no_reads, "claude-3-5-sonnet-20241022"), 0.0);
+    }
+
     #[test]
     fn test_format_cost() {
         assert_eq!(CostCalculator::format_cost(0.0001), "$0.0001");
@@ -561,4 +949,23 @@ mod tests {
         assert_eq!(CostCalculator::format_cost(1.234), "$1.23");
         assert_eq!(CostCalculator::format_cost(1234.56), "$1234.56");
     }
+
+    #[test]
+    fn test_format_cost_in_currency() {
+        assert_eq!(format_cost_in(12.5, "USD", 1.0, None), "$12.50");
+        assert_eq!(format_cost_in(10.0, "eur", 0.92, None), "€9.20");
+        assert_eq!(format_cost_in(10.0, "GBP", 0.79, None), "£7.90");
+        assert_eq!(format_cost_in(0.001, "GBP", 0.79, None), "£0.0008");
+        assert_eq!(format_cost_in(0.0, "USD", 1.0, None), "$0.00");
+        assert_eq!(format_cost_in(2.0, "CHF", 0.9, None), "CHF 1.80");
+    }
+
+    #[test]
+    fn test_format_cost_with_fixed_decimals() {
+        assert_eq!(format_cost_in(12.3456, "USD", 1.0, Some(0)), "$12");
+        assert_eq!(format_cost_in(12.3456, "USD", 1.0, Some(3)), "$12.346");
+        // A fixed precision also applies to sub-cent amounts
+        assert_eq!(format_cost_in(0.001, "USD", 1.0, Some(2)), "$0.00");
+        assert_eq!(format_cost_in(2.0, "CHF", 0.9, Some(1)), "CHF 1.8");
+    }
 }
\ No newline at end of file
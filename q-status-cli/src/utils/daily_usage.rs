@@ -0,0 +1,130 @@
+// ABOUTME: Per-day usage aggregation and CSV output for billing reconciliation
+// Buckets session block entries by local calendar day
+
+use crate::utils::session_blocks::SessionBlock;
+use crate::utils::timezone::DisplayZone;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Token and cost totals for a single local calendar day
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl DailyUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_tokens
+    }
+}
+
+/// Bucket every entry in the given blocks by calendar day in `timezone`, oldest first
+///
+/// Entry costs are taken as already resolved by the data source; days with no
+/// activity are omitted.
+pub fn aggregate_daily_usage(blocks: &[SessionBlock], timezone: DisplayZone) -> Vec<DailyUsage> {
+    let mut days: BTreeMap<NaiveDate, DailyUsage> = BTreeMap::new();
+
+    for entry in blocks.iter().filter(|b| !b.is_gap).flat_map(|b| &b.entries) {
+        let Some(timestamp) = entry.date() else {
+            continue;
+        };
+        let date = timezone.date_of(&timestamp);
+        let usage = &entry.message.usage;
+
+        let day = days.entry(date).or_insert_with(|| DailyUsage {
+            date,
+            ..Default::default()
+        });
+        day.input_tokens += usage.input_tokens as u64;
+        day.output_tokens += usage.output_tokens as u64;
+        day.cache_tokens += usage.cache_creation_input_tokens.unwrap_or(0) as u64
+            + usage.cache_read_input_tokens.unwrap_or(0) as u64;
+        day.cost_usd += entry.cost_usd.unwrap_or(0.0);
+    }
+
+    days.into_values().collect()
+}
+
+/// Write daily usage rows as CSV with a header line
+pub fn write_daily_csv<W: Write>(writer: &mut W, days: &[DailyUsage]) -> std::io::Result<()> {
+    writeln!(writer, "date,input_tokens,output_tokens,cache_tokens,total_tokens,cost_usd")?;
+    for day in days {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{:.6}",
+            day.date.format("%Y-%m-%d"),
+            day.input_tokens,
+            day.output_tokens,
+            day.cache_tokens,
+            day.total_tokens(),
+            day.cost_usd
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::session_blocks::{identify_session_blocks, ClaudeMessage, ClaudeTokenUsage, ClaudeUsageEntry};
+    use chrono::{Local, TimeZone, Utc};
+
+    fn entry(timestamp: chrono::DateTime<Utc>, input: u32, cache_read: u32, cost: f64) -> ClaudeUsageEntry {
+        ClaudeUsageEntry {
+            timestamp: timestamp.to_rfc3339(),
+            session_id: None,
+            message: ClaudeMessage {
+                usage: ClaudeTokenUsage {
+                    input_tokens: input,
+                    output_tokens: 10,
+                    cache_creation_input_tokens: None,
+                    cache_read_input_tokens: Some(cache_read),
+                },
+                model: None,
+                id: None,
+            },
+            cost_usd: Some(cost),
+            request_id: None,
+            cwd: None,
+            version: None,
+            is_api_error_message: None,
+        }
+    }
+
+    #[test]
+    fn test_daily_buckets_skip_empty_days() {
+        // Noon local time avoids day boundaries shifting with the test machine's timezone
+        let day1 = Local.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap().with_timezone(&Utc);
+        let day3 = Local.with_ymd_and_hms(2024, 1, 12, 12, 0, 0).unwrap().with_timezone(&Utc);
+        let entries = vec![
+            entry(day1, 100, 50, 0.5),
+            entry(day1 + chrono::Duration::minutes(5), 200, 0, 0.25),
+            entry(day3, 300, 0, 1.0),
+        ];
+
+        let blocks = identify_session_blocks(&entries, None);
+        let days = aggregate_daily_usage(&blocks, DisplayZone::Local);
+
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2024, 1, 10).unwrap());
+        assert_eq!(days[0].input_tokens, 300);
+        assert_eq!(days[0].cache_tokens, 50);
+        assert_eq!(days[0].total_tokens(), 370);
+        assert!((days[0].cost_usd - 0.75).abs() < 1e-9);
+        assert_eq!(days[1].date, NaiveDate::from_ymd_opt(2024, 1, 12).unwrap());
+
+        let mut out = Vec::new();
+        write_daily_csv(&mut out, &days).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "date,input_tokens,output_tokens,cache_tokens,total_tokens,cost_usd");
+        assert_eq!(lines[1], "2024-01-10,300,20,50,370,0.750000");
+        assert_eq!(lines.len(), 3);
+    }
+}
@@ -0,0 +1,104 @@
+// ABOUTME: Date range filtering for the --since / --until flags
+// Parses absolute (YYYY-MM-DD) and relative (7d, 12h, 2w) bounds
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+
+/// Optional inclusive time window applied to usage data
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DateRange {
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+}
+
+impl DateRange {
+    pub fn new(since: Option<DateTime<Local>>, until: Option<DateTime<Local>>) -> Self {
+        Self { since, until }
+    }
+
+    /// Whether neither bound is set
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    /// Whether a timestamp falls within the range (bounds are inclusive)
+    pub fn contains<Tz: TimeZone>(&self, timestamp: &DateTime<Tz>) -> bool {
+        let timestamp = timestamp.with_timezone(&Local);
+        self.since.is_none_or(|since| timestamp >= since)
+            && self.until.is_none_or(|until| timestamp <= until)
+    }
+}
+
+/// Parse a date bound relative to `now`
+///
+/// Accepts `YYYY-MM-DD` (start of day, or end of day when `end_of_day` is set)
+/// and relative forms such as `7d`, `12h` or `2w` meaning that long before now.
+pub fn parse_date_bound(value: &str, end_of_day: bool, now: DateTime<Local>) -> Result<DateTime<Local>, String> {
+    let value = value.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let time = if end_of_day {
+            date.and_hms_opt(23, 59, 59)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        return time
+            .and_then(|t| Local.from_local_datetime(&t).earliest())
+            .ok_or_else(|| format!("Invalid local date: {}", value));
+    }
+
+    let invalid = || format!("Invalid date '{}': expected YYYY-MM-DD or a relative form like 7d", value);
+    let unit_start = value.char_indices().last().map_or(0, |(i, _)| i);
+    let (amount, unit) = value.split_at(unit_start);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    let offset = match unit {
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => return Err(format!("Invalid date '{}': relative units are h, d or w", value)),
+    };
+
+    // Amounts beyond chrono's range are rejected rather than panicking
+    offset
+        .and_then(|offset| now.checked_sub_signed(offset))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_absolute_and_relative() {
+        let now = Local.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+
+        let since = parse_date_bound("2024-03-01", false, now).unwrap();
+        assert_eq!(since, Local.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+
+        let until = parse_date_bound("2024-03-01", true, now).unwrap();
+        assert_eq!(until, Local.with_ymd_and_hms(2024, 3, 1, 23, 59, 59).unwrap());
+
+        assert_eq!(parse_date_bound("7d", false, now).unwrap(), now - Duration::days(7));
+        assert_eq!(parse_date_bound("2w", false, now).unwrap(), now - Duration::weeks(2));
+        assert!(parse_date_bound("yesterday", false, now).is_err());
+        assert!(parse_date_bound("7y", false, now).is_err());
+        assert!(parse_date_bound("7é", false, now).is_err());
+        assert!(parse_date_bound("é", false, now).is_err());
+        assert!(parse_date_bound("", false, now).is_err());
+        assert!(parse_date_bound("99999999999999d", false, now).is_err());
+        assert!(parse_date_bound("9223372036854775807w", false, now).is_err());
+    }
+
+    #[test]
+    fn test_range_contains() {
+        let start = Local.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let end = Local.with_ymd_and_hms(2024, 3, 7, 23, 59, 59).unwrap();
+        let range = DateRange::new(Some(start), Some(end));
+
+        assert!(range.contains(&start));
+        assert!(range.contains(&end));
+        assert!(!range.contains(&(start - Duration::seconds(1))));
+        assert!(!range.contains(&(end + Duration::seconds(1))));
+        assert!(DateRange::default().contains(&start));
+    }
+}
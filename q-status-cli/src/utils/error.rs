@@ -36,4 +36,22 @@ impl<T> From<crossbeam_channel::SendError<T>> for QStatusError {
     }
 }
 
+impl QStatusError {
+    /// Whether the data source itself is gone (deleted, rotated or unreadable)
+    /// rather than a single query failing
+    pub fn is_source_unavailable(&self) -> bool {
+        match self {
+            QStatusError::DatabaseNotFound | QStatusError::Io(_) => true,
+            QStatusError::Database(rusqlite::Error::SqliteFailure(e, _)) => matches!(
+                e.code,
+                rusqlite::ErrorCode::CannotOpen
+                    | rusqlite::ErrorCode::SystemIoFailure
+                    | rusqlite::ErrorCode::NotADatabase
+                    | rusqlite::ErrorCode::DatabaseCorrupt
+            ),
+            _ => false,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, QStatusError>;
@@ -0,0 +1,189 @@
+// ABOUTME: Standalone HTML usage report for sharing outside the terminal
+// Renders summary totals, a sortable sessions table and per-model cost bars
+
+use crate::data::database::{GlobalStats, Session};
+use crate::utils::cost_calculator::format_cost_in;
+use chrono::{DateTime, Local};
+use std::fmt::Write as _;
+
+/// Everything a report shows, gathered once from the data source
+#[derive(Debug, Clone)]
+pub struct ReportData {
+    pub source: String,
+    pub generated_at: DateTime<Local>,
+    pub stats: GlobalStats,
+    pub sessions: Vec<Session>,
+    /// Fixed decimal places for costs, as in `AppConfig::cost_decimals`
+    pub cost_decimals: Option<usize>,
+    /// Currency costs are shown in, as in `AppConfig::display_currency`
+    pub display_currency: String,
+    /// Units of `display_currency` per US dollar
+    pub usd_exchange_rate: f64,
+}
+
+impl ReportData {
+    /// A USD cost in the report's currency, the way the dashboard shows it
+    pub fn format_cost(&self, cost_usd: f64) -> String {
+        format_cost_in(cost_usd, &self.display_currency, self.usd_exchange_rate, self.cost_decimals)
+    }
+}
+
+/// Click-to-sort for the sessions table; numeric cells sort by `data-value`
+const SORT_SCRIPT: &str = r#"
+document.querySelectorAll('th[data-col]').forEach(th => th.addEventListener('click', () => {
+  const tbody = th.closest('table').tBodies[0];
+  const col = +th.dataset.col;
+  const asc = th.dataset.asc !== 'true';
+  th.dataset.asc = asc;
+  const key = td => td.dataset.value !== undefined ? +td.dataset.value : td.textContent;
+  [...tbody.rows]
+    .sort((a, b) => {
+      const x = key(a.cells[col]), y = key(b.cells[col]);
+      return (x > y ? 1 : x < y ? -1 : 0) * (asc ? 1 : -1);
+    })
+    .forEach(row => tbody.appendChild(row));
+}));
+"#;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, system-ui, sans-serif; margin: 2rem; color: #222; }
+.summary { display: flex; gap: 2rem; flex-wrap: wrap; }
+.summary div { background: #f4f6f8; padding: 0.8rem 1.2rem; border-radius: 6px; }
+.summary strong { display: block; font-size: 1.4rem; }
+table { border-collapse: collapse; width: 100%; margin-top: 1rem; }
+th, td { padding: 0.3rem 0.6rem; border-bottom: 1px solid #ddd; text-align: left; }
+th[data-col] { cursor: pointer; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { background: #4a90d9; height: 0.9rem; border-radius: 3px; }
+"#;
+
+/// Render the report as a single HTML page with inline CSS and JS
+pub fn render_html(data: &ReportData) -> String {
+    let stats = &data.stats;
+    let cost = |usd: f64| data.format_cost(usd);
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Q-Status Report - {source}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <h1>Q-Status Report</h1>\n<p>{source} &middot; generated {generated}</p>\n",
+        source = escape(&data.source),
+        generated = data.generated_at.format("%Y-%m-%d %H:%M %Z"),
+    );
+
+    // Summary header
+    let _ = write!(
+        html,
+        "<section class=\"summary\">\n\
+         <div>Conversations<strong>{}</strong></div>\n\
+         <div>Total tokens<strong>{}</strong></div>\n\
+         <div>Total cost<strong>{}</strong></div>\n\
+         <div>Messages<strong>{}</strong></div>\n\
+         </section>\n",
+        stats.total_conversations, stats.total_tokens, cost(stats.total_cost_estimate), stats.total_messages,
+    );
+
+    // Sessions table, largest first until the reader re-sorts it
+    let mut sessions: Vec<&Session> = data.sessions.iter().collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.token_usage.total_tokens));
+
+    html.push_str(
+        "<h2>Sessions</h2>\n<table>\n<thead><tr>\
+         <th data-col=\"0\">Directory</th><th data-col=\"1\">Tokens</th>\
+         <th data-col=\"2\">Context %</th><th data-col=\"3\">Messages</th>\
+         <th data-col=\"4\">Cost</th><th data-col=\"5\">Last activity</th>\
+         </tr></thead>\n<tbody>\n",
+    );
+    for session in sessions {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\" data-value=\"{tokens}\">{tokens}</td>\
+             <td class=\"num\" data-value=\"{pct:.1}\">{pct:.1}%</td>\
+             <td class=\"num\" data-value=\"{messages}\">{messages}</td>\
+             <td class=\"num\" data-value=\"{usd:.4}\">{cost}</td><td>{}</td></tr>",
+            escape(&session.directory),
+            session.last_activity.format("%Y-%m-%d %H:%M"),
+            tokens = session.token_usage.total_tokens,
+            pct = session.token_usage.percentage,
+            messages = session.message_count,
+            usd = session.session_cost,
+            cost = cost(session.session_cost),
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    // Per-model cost bars, scaled to the most expensive model
+    if !stats.cost_by_model.is_empty() {
+        let mut models: Vec<(&String, &f64)> = stats.cost_by_model.iter().collect();
+        models.sort_by(|a, b| b.1.total_cmp(a.1));
+        let max_cost = models.first().map(|(_, c)| **c).unwrap_or(0.0);
+
+        html.push_str("<h2>Cost by model</h2>\n<table>\n<tbody>\n");
+        for (model, model_cost) in models {
+            let width = if max_cost > 0.0 { model_cost / max_cost * 100.0 } else { 0.0 };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td>\
+                 <td style=\"width:60%\"><div class=\"bar\" style=\"width:{:.1}%\"></div></td></tr>",
+                escape(model),
+                cost(*model_cost),
+                width,
+            );
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    let _ = write!(html, "<script>{SORT_SCRIPT}</script>\n</body>\n</html>\n");
+    html
+}
+
+/// Escape text for use in HTML content and attributes
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_report_contains_totals() {
+        let data = ReportData {
+            source: "Claude Code".to_string(),
+            generated_at: Local::now(),
+            stats: GlobalStats {
+                total_conversations: 2,
+                total_tokens: 48_000,
+                average_tokens: 24_000,
+                total_cost_estimate: 12.34,
+                total_messages: 8,
+                message_quota_used: 8,
+                message_quota_limit: 5_000,
+                cost_by_model: HashMap::from([
+                    ("claude-sonnet-4".to_string(), 10.0),
+                    ("claude-3-5-haiku".to_string(), 2.34),
+                ]),
+                ..GlobalStats::default()
+            },
+            sessions: vec![Session::test("/work/api", 40_000, 10.0), Session::test("/work/<web>", 8_000, 2.34)],
+            cost_decimals: None,
+            display_currency: "USD".to_string(),
+            usd_exchange_rate: 1.0,
+        };
+
+        let html = render_html(&data);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<strong>48000</strong>"));
+        assert!(html.contains("<strong>$12.34</strong>"));
+        assert!(html.contains("/work/&lt;web&gt;"));
+        assert!(html.contains("claude-sonnet-4"));
+        assert!(html.contains("width:100.0%"));
+        // Self-contained: nothing loaded from elsewhere
+        assert!(!html.contains("src=\"http") && !html.contains("href=\"http"));
+    }
+}
@@ -0,0 +1,59 @@
+// ABOUTME: Tracing output to a daily rolling file for --log-file
+// Lets collector and parse diagnostics be captured while the dashboard owns the terminal
+
+use crate::utils::error::{QStatusError, Result};
+use std::path::Path;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+
+/// Filter used for file logging unless RUST_LOG says otherwise
+pub const DEFAULT_LOG_FILTER: &str = "q_status=debug";
+
+/// Non-blocking writer for a file rolled daily next to `path`
+///
+/// Files are named `<file name>.<YYYY-MM-DD>` in the parent directory, which is
+/// created if needed. Buffered events are flushed when the guard is dropped, so
+/// keep it alive for as long as logging should continue.
+pub fn file_writer(path: &Path) -> Result<(NonBlocking, WorkerGuard)> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| QStatusError::Config(format!("Log file path has no file name: {}", path.display())))?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)?;
+
+    let appender = tracing_appender::rolling::daily(dir, file_name);
+    Ok(tracing_appender::non_blocking(appender))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_receives_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("q-status.log");
+
+        let (writer, guard) = file_writer(&path).unwrap();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(DEFAULT_LOG_FILTER)
+            .with_writer(writer)
+            .with_ansi(false)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("collector refreshed 3 sessions");
+        });
+        drop(guard);
+
+        let written: Vec<_> = std::fs::read_dir(dir.path().join("logs"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(written.len(), 1);
+        assert!(written[0].file_name().unwrap().to_string_lossy().starts_with("q-status.log"));
+        let contents = std::fs::read_to_string(&written[0]).unwrap();
+        assert!(contents.contains("collector refreshed 3 sessions"));
+    }
+}
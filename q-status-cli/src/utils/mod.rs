@@ -1,6 +1,17 @@
+pub mod anonymize;
+pub mod budget;
+pub mod clipboard;
 pub mod cost_calculator;
+pub mod daily_usage;
+pub mod date_range;
 pub mod error;
+pub mod html_report;
+pub mod logging;
+pub mod notifications;
+pub mod report;
 pub mod session_blocks;
+pub mod timezone;
+pub mod tokenizer;
 
 pub use cost_calculator::{CostCalculator, CostMode, ModelPricing, TokenUsage};
 pub use error::{QStatusError, Result};
@@ -0,0 +1,75 @@
+// ABOUTME: Desktop notifications for token usage threshold crossings and cost spikes
+// Wraps notify-rust so failures never interrupt data collection
+
+use crate::data::database::CompactionStatus;
+use notify_rust::Notification;
+
+/// Human-readable label for a compaction status
+fn status_label(status: &CompactionStatus) -> &'static str {
+    match status {
+        CompactionStatus::Safe => "Safe",
+        CompactionStatus::Warning => "Warning",
+        CompactionStatus::Critical => "Critical",
+        CompactionStatus::Imminent => "Compaction imminent",
+    }
+}
+
+/// Build the notification body for a threshold crossing
+pub fn threshold_message(status: &CompactionStatus, percentage: f64, conversation_id: &str) -> String {
+    format!(
+        "{}: {:.1}% of context used in conversation {}",
+        status_label(status),
+        percentage,
+        conversation_id
+    )
+}
+
+/// Show a desktop notification that usage crossed a threshold
+///
+/// Errors (e.g. no notification daemon) are logged and otherwise ignored.
+pub fn notify_threshold(status: &CompactionStatus, percentage: f64, conversation_id: &str) {
+    let result = Notification::new()
+        .summary("Q-Status: token usage threshold reached")
+        .body(&threshold_message(status, percentage, conversation_id))
+        .appname("q-status")
+        .show();
+
+    if let Err(e) = result {
+        tracing::debug!("Failed to show desktop notification: {}", e);
+    }
+}
+
+/// Build the notification body for a session whose cost jumped
+pub fn cost_spike_message(conversation_id: &str, previous_cost: &str, current_cost: &str) -> String {
+    format!("Cost of conversation {} jumped from {} to {}", conversation_id, previous_cost, current_cost)
+}
+
+/// Show a desktop notification that a session's cost spiked
+pub fn notify_cost_spike(conversation_id: &str, previous_cost: &str, current_cost: &str) {
+    let result = Notification::new()
+        .summary("Q-Status: cost spike")
+        .body(&cost_spike_message(conversation_id, previous_cost, current_cost))
+        .appname("q-status")
+        .show();
+
+    if let Err(e) = result {
+        tracing::debug!("Failed to show desktop notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_message() {
+        let message = threshold_message(&CompactionStatus::Critical, 91.25, "conv-123");
+        assert_eq!(message, "Critical: 91.2% of context used in conversation conv-123");
+    }
+
+    #[test]
+    fn test_cost_spike_message() {
+        let message = cost_spike_message("conv-123", "$0.50", "$2.25");
+        assert_eq!(message, "Cost of conversation conv-123 jumped from $0.50 to $2.25");
+    }
+}
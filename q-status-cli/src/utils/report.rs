@@ -0,0 +1,137 @@
+// ABOUTME: Report serialization for every export format
+// Shared by --export and the in-dashboard export key; HTML lives in html_report
+
+use crate::app::config::ExportFormat;
+use crate::utils::html_report::{render_html, ReportData};
+use std::fmt::Write as _;
+
+/// Render the report in `format`, keeping sessions in the order given
+///
+/// The HTML report re-sorts by tokens and lets the reader sort further.
+pub fn render_report(data: &ReportData, format: &ExportFormat) -> String {
+    match format {
+        ExportFormat::Html => render_html(data),
+        ExportFormat::Json => render_json(data),
+        ExportFormat::Csv => render_csv(data),
+        ExportFormat::Markdown => render_markdown(data),
+    }
+}
+
+fn render_json(data: &ReportData) -> String {
+    let sessions: Vec<_> = data
+        .sessions
+        .iter()
+        .map(|session| {
+            serde_json::json!({
+                "conversation_id": session.conversation_id,
+                "directory": session.directory,
+                "tokens": session.token_usage.total_tokens,
+                "context_percentage": session.token_usage.percentage,
+                "messages": session.message_count,
+                "cost": session.session_cost,
+                "last_activity": session.last_activity.to_rfc3339(),
+            })
+        })
+        .collect();
+    let report = serde_json::json!({
+        "source": data.source,
+        "generated_at": data.generated_at.to_rfc3339(),
+        "total_conversations": data.stats.total_conversations,
+        "total_tokens": data.stats.total_tokens,
+        "total_cost": data.stats.total_cost_estimate,
+        "total_messages": data.stats.total_messages,
+        "sessions": sessions,
+    });
+    let mut json = serde_json::to_string_pretty(&report).unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+fn render_csv(data: &ReportData) -> String {
+    let mut csv = String::from("conversation_id,directory,tokens,context_percentage,messages,cost,last_activity\n");
+    for session in &data.sessions {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{:.1},{},{:.4},{}",
+            csv_field(&session.conversation_id),
+            csv_field(&session.directory),
+            session.token_usage.total_tokens,
+            session.token_usage.percentage,
+            session.message_count,
+            session.session_cost,
+            session.last_activity.to_rfc3339(),
+        );
+    }
+    csv
+}
+
+fn render_markdown(data: &ReportData) -> String {
+    let stats = &data.stats;
+    let cost = |usd: f64| data.format_cost(usd);
+    let mut md = format!(
+        "# Q-Status Report\n\n{} - generated {}\n\n\
+         - Conversations: {}\n- Total tokens: {}\n- Total cost: {}\n- Messages: {}\n\n\
+         | Directory | Tokens | Context % | Messages | Cost | Last activity |\n\
+         |---|---:|---:|---:|---:|---|\n",
+        data.source,
+        data.generated_at.format("%Y-%m-%d %H:%M %Z"),
+        stats.total_conversations,
+        stats.total_tokens,
+        cost(stats.total_cost_estimate),
+        stats.total_messages,
+    );
+    for session in &data.sessions {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {:.1}% | {} | {} | {} |",
+            session.directory.replace('|', "\\|"),
+            session.token_usage.total_tokens,
+            session.token_usage.percentage,
+            session.message_count,
+            cost(session.session_cost),
+            session.last_activity.format("%Y-%m-%d %H:%M"),
+        );
+    }
+    md
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::database::{GlobalStats, Session};
+
+    #[test]
+    fn test_csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("/work/api"), "/work/api");
+        assert_eq!(csv_field("/work/a,b"), "\"/work/a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_markdown_costs_use_display_currency() {
+        let data = ReportData {
+            source: "Claude Code".to_string(),
+            generated_at: chrono::Local::now(),
+            stats: GlobalStats { total_cost_estimate: 12.0, ..GlobalStats::default() },
+            sessions: vec![Session::test("/work/api", 1_000, 3.0)],
+            cost_decimals: None,
+            display_currency: "EUR".to_string(),
+            usd_exchange_rate: 0.5,
+        };
+
+        let md = render_report(&data, &ExportFormat::Markdown);
+        assert!(md.contains("- Total cost: €6.00"));
+        assert!(md.contains("| €1.50 |"));
+        assert!(!md.contains('$'));
+        assert!(render_report(&data, &ExportFormat::Html).contains("<strong>€6.00</strong>"));
+    }
+}
@@ -344,6 +344,81 @@ pub fn project_block_usage(block: &SessionBlock) -> Option<ProjectedUsage> {
     })
 }
 
+/// Minutes until an active block reaches `limit` at its current burn rate
+///
+/// Returns None unless the block is projected to exceed the limit before it
+/// resets, so the warning clears once the burn rate drops.
+pub fn minutes_until_limit(block: &SessionBlock, limit: u64) -> Option<u64> {
+    let projection = project_block_usage(block)?;
+    if projection.total_tokens <= limit {
+        return None;
+    }
+
+    let current = block.token_counts.total_tokens();
+    if current >= limit {
+        return Some(0);
+    }
+
+    let burn_rate = calculate_burn_rate(block)?;
+    Some(((limit - current) as f64 / burn_rate.tokens_per_minute).round() as u64)
+}
+
+/// Per-block token limits of the known Claude plans (Pro, Max 5x, Max 20x)
+pub const PLAN_TOKEN_LIMITS: [u64; 3] = [19_000, 88_000, 220_000];
+
+/// Infer the plan's per-block token limit from the busiest block seen
+///
+/// The peak is rounded up to the smallest plan tier that fits it. Peaks above
+/// every tier are returned as-is, and with no usage the largest tier is assumed.
+pub fn detect_token_limit(blocks: &[SessionBlock]) -> u64 {
+    let peak = blocks
+        .iter()
+        .filter(|b| !b.is_gap)
+        .map(|b| b.token_counts.total_tokens())
+        .max();
+
+    let Some(peak) = peak else {
+        return PLAN_TOKEN_LIMITS[PLAN_TOKEN_LIMITS.len() - 1];
+    };
+
+    PLAN_TOKEN_LIMITS
+        .iter()
+        .copied()
+        .find(|&limit| peak <= limit)
+        .unwrap_or(peak)
+}
+
+/// Whether Claude usage is inside a billing block right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    Active,
+    /// No active block; the last recorded activity was at this time
+    Idle { last_activity: DateTime<Utc> },
+    /// No usage recorded at all
+    NoData,
+}
+
+/// Classify the current billing state from the full block list
+pub fn block_status(blocks: &[SessionBlock]) -> BlockStatus {
+    if blocks.iter().any(|b| b.is_active && !b.is_gap) {
+        return BlockStatus::Active;
+    }
+
+    blocks
+        .iter()
+        .filter(|b| !b.is_gap)
+        .filter_map(|b| b.actual_end_time)
+        .max()
+        .map_or(BlockStatus::NoData, |last_activity| BlockStatus::Idle { last_activity })
+}
+
+/// Blocks to draw, leaving out gap blocks unless `show_gaps`
+///
+/// Only the display is filtered; totals and burn rates should keep using the full list.
+pub fn display_blocks(blocks: &[SessionBlock], show_gaps: bool) -> Vec<&SessionBlock> {
+    blocks.iter().filter(|b| show_gaps || !b.is_gap).collect()
+}
+
 /// Filters session blocks to include only recent ones and active blocks
 pub fn filter_recent_blocks(blocks: &[SessionBlock], days: Option<i64>) -> Vec<SessionBlock> {
     let days = days.unwrap_or(3);
@@ -392,6 +467,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_minutes_until_limit() {
+        // 10K tokens over the last 10 minutes of an active block: 1K tokens/min
+        let now = Utc::now();
+        let entries = vec![
+            create_mock_entry(now - Duration::minutes(10), 5_000, 0, "claude-sonnet", 0.0),
+            create_mock_entry(now, 5_000, 0, "claude-sonnet", 0.0),
+        ];
+        let blocks = identify_session_blocks(&entries, None);
+        let block = blocks.iter().find(|b| b.is_active && !b.is_gap).unwrap();
+
+        assert_eq!(minutes_until_limit(block, 33_000), Some(23));
+        assert_eq!(minutes_until_limit(block, 5_000), Some(0));
+        // Plenty of headroom for the rest of the block: no warning
+        assert_eq!(minutes_until_limit(block, 10_000_000), None);
+    }
+
+    #[test]
+    fn test_block_status() {
+        assert_eq!(block_status(&[]), BlockStatus::NoData);
+
+        let now = Utc::now();
+        let active = identify_session_blocks(&[create_mock_entry(now, 10, 0, "claude-sonnet", 0.0)], None);
+        assert_eq!(block_status(&active), BlockStatus::Active);
+
+        let last = now - Duration::hours(8);
+        let stale = identify_session_blocks(
+            &[
+                create_mock_entry(last - Duration::hours(1), 10, 0, "claude-sonnet", 0.0),
+                create_mock_entry(last, 10, 0, "claude-sonnet", 0.0),
+            ],
+            None,
+        );
+        match block_status(&stale) {
+            BlockStatus::Idle { last_activity } => assert_eq!(last_activity.timestamp(), last.timestamp()),
+            other => panic!("expected idle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_detect_token_limit_tiers() {
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let limit_for_peak = |tokens: u32| {
+            let entries = vec![
+                create_mock_entry(base_time, tokens, 0, "claude-sonnet", 0.0),
+                // A quieter block later on doesn't lower the peak
+                create_mock_entry(base_time + Duration::hours(12), 10, 0, "claude-sonnet", 0.0),
+            ];
+            detect_token_limit(&identify_session_blocks(&entries, None))
+        };
+
+        assert_eq!(limit_for_peak(1_000), 19_000);
+        assert_eq!(limit_for_peak(19_000), 19_000);
+        assert_eq!(limit_for_peak(19_001), 88_000);
+        assert_eq!(limit_for_peak(88_000), 88_000);
+        assert_eq!(limit_for_peak(88_001), 220_000);
+        assert_eq!(limit_for_peak(220_000), 220_000);
+        assert_eq!(limit_for_peak(250_000), 250_000);
+        assert_eq!(detect_token_limit(&[]), 220_000);
+    }
+
     #[test]
     fn test_empty_entries() {
         let blocks = identify_session_blocks(&[], None);
@@ -478,6 +614,28 @@ mod tests {
         assert_eq!(blocks[2].entries.len(), 1);
     }
 
+    #[test]
+    fn test_display_blocks_hides_gaps_not_totals() {
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let entries = vec![
+            create_mock_entry(base_time, 1000, 500, "claude-sonnet", 0.01),
+            create_mock_entry(base_time + Duration::hours(8), 2000, 500, "claude-sonnet", 0.02),
+        ];
+        let blocks = identify_session_blocks(&entries, None);
+        assert_eq!(blocks.len(), 3);
+
+        assert_eq!(display_blocks(&blocks, true).len(), 3);
+        let shown = display_blocks(&blocks, false);
+        assert_eq!(shown.len(), 2);
+        assert!(shown.iter().all(|b| !b.is_gap));
+
+        // The underlying list, and anything derived from it, is unchanged
+        assert_eq!(blocks.len(), 3);
+        let total: u64 = blocks.iter().map(|b| b.token_counts.total_tokens()).sum();
+        assert_eq!(total, 4000);
+        assert_eq!(detect_token_limit(&blocks), 19_000);
+    }
+
     #[test]
     fn test_floor_to_hour() {
         let entry_time = Utc.with_ymd_and_hms(2024, 1, 1, 10, 55, 30).unwrap();
@@ -0,0 +1,75 @@
+// ABOUTME: Time zone used to bucket usage into calendar days and show times
+// Either the system local zone or an IANA zone named in the config
+
+use super::error::{QStatusError, Result};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Zone that decides which day a timestamp belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayZone {
+    /// The system's local zone
+    #[default]
+    Local,
+    /// An IANA zone such as `Europe/Berlin`
+    Named(Tz),
+}
+
+impl DisplayZone {
+    /// Parse an IANA zone name; empty or "local" means the system zone
+    pub fn parse(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if name.is_empty() || name.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        name.parse::<Tz>()
+            .map(Self::Named)
+            .map_err(|_| QStatusError::Config(format!("Unknown time zone '{}'", name)))
+    }
+
+    /// Calendar date of `timestamp` in this zone
+    pub fn date_of<Z: TimeZone>(&self, timestamp: &DateTime<Z>) -> NaiveDate {
+        match self {
+            Self::Local => timestamp.with_timezone(&chrono::Local).date_naive(),
+            Self::Named(tz) => timestamp.with_timezone(tz).date_naive(),
+        }
+    }
+
+    /// Today's date in this zone
+    pub fn today(&self) -> NaiveDate {
+        self.date_of(&Utc::now())
+    }
+
+    /// `timestamp` rendered with a strftime pattern in this zone
+    pub fn format<Z: TimeZone>(&self, timestamp: &DateTime<Z>, pattern: &str) -> String {
+        match self {
+            Self::Local => timestamp.with_timezone(&chrono::Local).format(pattern).to_string(),
+            Self::Named(tz) => timestamp.with_timezone(tz).format(pattern).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zone_names() {
+        assert_eq!(DisplayZone::parse("").unwrap(), DisplayZone::Local);
+        assert_eq!(DisplayZone::parse("Local").unwrap(), DisplayZone::Local);
+        assert_eq!(DisplayZone::parse("Asia/Tokyo").unwrap(), DisplayZone::Named(chrono_tz::Asia::Tokyo));
+        assert!(DisplayZone::parse("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_near_midnight_lands_on_the_zone_day() {
+        // 23:30 UTC on the 10th is already the 11th in Tokyo and still the 10th in New York
+        let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 23, 30, 0).unwrap();
+        let tokyo = DisplayZone::parse("Asia/Tokyo").unwrap();
+        let new_york = DisplayZone::parse("America/New_York").unwrap();
+
+        assert_eq!(tokyo.date_of(&timestamp), NaiveDate::from_ymd_opt(2024, 3, 11).unwrap());
+        assert_eq!(new_york.date_of(&timestamp), NaiveDate::from_ymd_opt(2024, 3, 10).unwrap());
+        assert_eq!(tokyo.format(&timestamp, "%m-%d %H:%M"), "03-11 08:30");
+    }
+}
@@ -0,0 +1,62 @@
+// ABOUTME: BPE tokenizer wrapper for estimating token counts from raw text
+// Uses cl100k_base via tiktoken-rs with a 4:1 char-to-token fallback
+
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+/// Characters per token used by the approximate fallback
+pub const APPROX_CHARS_PER_TOKEN: usize = 4;
+
+static TOKENIZER: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+/// Lazily initialize the shared tokenizer, returning None if it can't be loaded
+fn tokenizer() -> Option<&'static CoreBPE> {
+    TOKENIZER
+        .get_or_init(|| tiktoken_rs::cl100k_base().ok())
+        .as_ref()
+}
+
+/// Whether the BPE tokenizer is available (false means counts are approximate)
+pub fn is_available() -> bool {
+    tokenizer().is_some()
+}
+
+/// Count tokens in text using the BPE tokenizer, falling back to the 4:1 heuristic
+pub fn count_tokens(text: &str) -> usize {
+    match tokenizer() {
+        Some(bpe) => bpe.encode_ordinary(text).len(),
+        None => approximate_tokens(text),
+    }
+}
+
+/// Estimate tokens using the 4:1 character-to-token ratio
+pub fn approximate_tokens(text: &str) -> usize {
+    text.len() / APPROX_CHARS_PER_TOKEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_tokens_known_string() {
+        assert!(is_available());
+        assert_eq!(count_tokens("hello world"), 2);
+        assert_eq!(count_tokens(""), 0);
+    }
+
+    #[test]
+    fn test_approximate_tokens() {
+        assert_eq!(approximate_tokens("hello world"), 2);
+        assert_eq!(approximate_tokens("abcdefgh"), 2);
+    }
+
+    #[test]
+    fn test_tokenizer_diverges_from_estimate_on_code() {
+        let code = "fn main() { let x: Vec<u8> = vec![0x1f, 0x2e]; println!(\"{:?}\", x); }";
+        let estimate = approximate_tokens(code);
+        let actual = count_tokens(code);
+        assert_ne!(estimate, actual);
+        assert!(actual > estimate);
+    }
+}